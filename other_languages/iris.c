@@ -1,15 +1,38 @@
 #ifndef SAFE_IRIS
 #define SAFE_IRIS
 
-extern void iris_new_request(char* req);
-extern void iris_load_file(char* path);
+extern int iris_init();
+extern int iris_new_request(char* req);
+extern int iris_load_file(char* path);
+extern char* iris_get_results();
+extern char* iris_last_error();
+extern void iris_free_string(char* string);
 
-void new_request(char* req) {
-	iris_new_request(req);
+/* Each function returns 0 on success and -1 on failure, use last_error to get the reason */
+int init() {
+	return iris_init();
 }
 
-void load_file(char* path) {
-	iris_load_file(path);
+int new_request(char* req) {
+	return iris_new_request(req);
+}
+
+int load_file(char* path) {
+	return iris_load_file(path);
+}
+
+/* NULL if the last call succeeded, the message has to be released with free_results */
+char* last_error() {
+	return iris_last_error();
+}
+
+/* The returned json has to be released with free_results */
+char* get_results() {
+	return iris_get_results();
+}
+
+void free_results(char* results) {
+	iris_free_string(results);
 }
 
 #endif
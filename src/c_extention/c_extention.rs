@@ -1,60 +1,107 @@
-use std::os::raw::c_char;
-use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ffi::{CStr, CString};
 use crate::{
-    Interpreteur,
-    Tokenizer,
-    TokenizerMessage,
-    OneFile,
-    OneQuery,
-    pre_init_database
+    Connection,
+    QueryResult,
+    IrisError,
+    get_iris_path
 };
-use std::sync::mpsc::{Receiver, channel};
-static mut TOKENIZER: Option<Tokenizer> = None;
-static mut INTERPRETEUR: Option<Interpreteur> = None;
-static mut RECEIVER: Option<Receiver<TokenizerMessage>> = None;
+use serde_json::Value as JsonValue;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
-#[no_mangle]
-pub extern "C" fn iris_init() {
-    pre_init_database();
-    let (sender, receiver) = channel::<TokenizerMessage>();
-    unsafe {
-        INTERPRETEUR = Some(Interpreteur::new());
-        TOKENIZER = Some(Tokenizer::new(sender));
-        RECEIVER = Some(receiver);
-    }
+static CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+static RESULTS: Mutex<Vec<QueryResult>> = Mutex::new(Vec::new());
+static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Lock the shared state, a panic while it was locked doesn't prevent the next calls from C
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
-unsafe fn try_to_init() {
-    if TOKENIZER.is_none() {
-        iris_init();
+/// Store the outcome of a call for iris_get_results and iris_last_error, and convert it in a status code for C.
+fn report(outcome: Result<Vec<QueryResult>, IrisError>) -> c_int {
+    match outcome {
+        Ok(results) => {
+            *lock(&RESULTS) = results;
+            *lock(&LAST_ERROR) = None;
+            0
+        }
+        Err(e) => {
+            lock(&RESULTS).clear();
+            *lock(&LAST_ERROR) = Some(e.to_string());
+            -1
+        }
     }
 }
 
+fn with_connection(f: impl FnOnce(&mut Connection) -> Result<Vec<QueryResult>, IrisError>) -> c_int {
+    let mut connection = lock(&CONNECTION);
+    let connection = match connection.as_mut() {
+        Some(c) => c,
+        None => match Connection::open(get_iris_path()) {
+            Ok(c) => connection.insert(c),
+            Err(e) => return report(Err(e))
+        }
+    };
+    report(f(connection))
+}
+
 unsafe fn extract_rust_string(dangerous_string: *const c_char) -> String {
     CStr::from_ptr(dangerous_string)
         .to_string_lossy()
         .into_owned()
 }
 
+/// A nul byte would end the string in C, they are removed
+fn into_c_string(string: String) -> *mut c_char {
+    CString::new(string.replace('\0', "")).unwrap_or_default().into_raw()
+}
+
+/// Open the database, returns 0 on success and -1 on failure, the error is given by iris_last_error.
 #[no_mangle]
-pub unsafe extern "C" fn iris_new_request(dangerous_req: *const c_char) {
-    try_to_init();
-    let req = extract_rust_string(dangerous_req);
-    TOKENIZER = Some(OneQuery::new(req).execute(
-        TOKENIZER.take().unwrap(),
-        INTERPRETEUR.as_mut().unwrap(),
-        RECEIVER.as_ref().unwrap()
-    ));
+pub extern "C" fn iris_init() -> c_int {
+    with_connection(|_| Ok(Vec::new()))
+}
 
+/// # Safety
+/// The request must be a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iris_new_request(dangerous_req: *const c_char) -> c_int {
+    let req = extract_rust_string(dangerous_req);
+    with_connection(|connection| connection.execute_all(&req))
 }
+
+/// # Safety
+/// The path must be a valid nul terminated string.
 #[no_mangle]
-pub unsafe extern "C" fn iris_load_file(dangerous_file_path: *const c_char) {
-    try_to_init();
+pub unsafe extern "C" fn iris_load_file(dangerous_file_path: *const c_char) -> c_int {
     let path = extract_rust_string(dangerous_file_path);
-    TOKENIZER = Some(OneFile::new(path).execute(
-        TOKENIZER.take().unwrap(),
-        INTERPRETEUR.as_mut().unwrap(),
-        RECEIVER.as_ref().unwrap()
-    ))
+    with_connection(|connection| connection.execute_file(&path))
+}
+
+/// Returns the results of the last request or file as a json array, one object per request.
+/// The string has to be released with iris_free_string.
+#[no_mangle]
+pub extern "C" fn iris_get_results() -> *mut c_char {
+    let json = JsonValue::Array(lock(&RESULTS).iter().map(|r| r.to_json()).collect());
+    into_c_string(json.to_string())
 }
 
+/// Returns the message of the error of the last call, or NULL if it succeeded.
+/// The string has to be released with iris_free_string.
+#[no_mangle]
+pub extern "C" fn iris_last_error() -> *mut c_char {
+    match lock(&LAST_ERROR).clone() {
+        Some(e) => into_c_string(e),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// # Safety
+/// The string must come from iris_get_results or iris_last_error and must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn iris_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
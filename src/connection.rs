@@ -0,0 +1,139 @@
+use crate::interpreteur::{
+    interpreteur::Interpreteur,
+    query_result::QueryResult
+};
+use crate::tokenizer::{
+    include::TokenizerMessage,
+    tokenizer::Tokenizer
+};
+use crate::error::IrisError;
+use std::sync::mpsc::{Receiver, channel};
+use std::thread::spawn;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+/// An open Iris database, the entry point to execute requests from Rust.
+pub struct Connection {
+    tokenizer: Option<Tokenizer>,
+    interpreteur: Interpreteur,
+    receiver: Receiver<TokenizerMessage>
+}
+
+impl Connection {
+
+    /// Open the database stored in the given directory, the directory is created if it doesn't exist yet.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Connection, IrisError> {
+        let dir = path.as_ref();
+        if let Err(e) = create_dir_all(dir) {
+            return Err(IrisError::io(&format!("Failed to create the database directory {}", dir.display()), e))
+        }
+        let (sender, receiver) = channel::<TokenizerMessage>();
+        Ok(Connection {
+            tokenizer: Some(Tokenizer::new(sender)),
+            interpreteur: Interpreteur::new(dir)?,
+            receiver
+        })
+    }
+
+    /// Limit the memory used by the pages of the tables kept in memory, 64 MiB by default.
+    /// The pages modified by the current request or transaction are kept until it ends, even beyond the limit.
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.interpreteur.set_memory_limit(bytes)
+    }
+
+    /// Execute the given requests and return the result of the last one.
+    pub fn execute(&mut self, sql: &str) -> Result<QueryResult, IrisError> {
+        Ok(self.execute_all(sql)?.pop().unwrap_or_else(QueryResult::empty))
+    }
+
+    /// Execute the given requests and return the result of each of them.
+    pub fn execute_all(&mut self, sql: &str) -> Result<Vec<QueryResult>, IrisError> {
+        let tokenizer = self.take_tokenizer();
+        let query = sql.to_string();
+        spawn(move ||
+              tokenizer.tokenize_query(query)
+        );
+        self.wait_results()
+    }
+
+    /// Execute the requests of the given file and return the result of each of them.
+    pub fn execute_file(&mut self, path: &str) -> Result<Vec<QueryResult>, IrisError> {
+        let tokenizer = self.take_tokenizer();
+        let path = path.to_string();
+        spawn(move ||
+              tokenizer.tokenize_file(path)
+        );
+        self.wait_results()
+    }
+
+    fn take_tokenizer(&mut self) -> Tokenizer {
+        self.tokenizer.take().expect("Connection: The tokenizer is still in use")
+    }
+
+    /// Give the tokens to the interpreteur until the tokenizer comes back, the first error stops the execution.
+    fn wait_results(&mut self) -> Result<Vec<QueryResult>, IrisError> {
+        let mut error: Option<IrisError> = None;
+        while self.tokenizer.is_none() {
+            match self.receiver.recv() {
+                Ok(TokenizerMessage::Token(token)) => {
+                    if error.is_none() {
+                        error = self.interpreteur.new_token(token).err();
+                    }
+                }
+                Ok(TokenizerMessage::Error(e)) => {
+                    error.get_or_insert(e);
+                }
+                Ok(TokenizerMessage::Tokenizer(tokenizer)) => self.tokenizer = Some(tokenizer),
+                Err(_) => {
+                    let (sender, receiver) = channel::<TokenizerMessage>();
+                    self.tokenizer = Some(Tokenizer::new(sender));
+                    self.receiver = receiver;
+                    error.get_or_insert(IrisError::Syntax(String::from("The tokenizer stopped unexpectedly")));
+                }
+            }
+        }
+        if error.is_none() && self.interpreteur.request_in_treatment() {
+            error = Some(IrisError::Syntax(String::from("The last request is not terminated, you probably forgot a ';'")));
+        }
+        match error {
+            Some(e) => {
+                self.interpreteur.abort_request()?;
+                Err(e)
+            }
+            None => Ok(self.interpreteur.take_results())
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreteur::test_dir::TestDir;
+
+    fn ids(connection: &mut Connection, table: &str) -> Vec<String> {
+        let result = connection.execute(&format!("SELECT id FROM {table};")).unwrap();
+        result.rows().iter().map(|row| row[0].string().clone()).collect()
+    }
+
+    #[test]
+    fn failed_writes_are_forgotten() {
+        let dir = TestDir::new("failed-write");
+        let mut connection = Connection::open(dir.path()).unwrap();
+        connection.execute_all("CREATE TABLE A (id INT PRIMARY KEY); INSERT INTO A (id) VALUES (1);").unwrap();
+        connection.interpreteur.fail_writes();
+        assert!(matches!(connection.execute("INSERT INTO A (id) VALUES (2);"), Err(IrisError::Io(_))));
+        assert_eq!(ids(&mut connection, "A"), vec!("1"));
+        connection.execute_all("BEGIN; INSERT INTO A (id) VALUES (3); CREATE TABLE B (id INT PRIMARY KEY);").unwrap();
+        connection.interpreteur.fail_writes();
+        assert!(matches!(connection.execute("COMMIT;"), Err(IrisError::Io(_))));
+        assert_eq!(ids(&mut connection, "A"), vec!("1"));
+        assert!(matches!(connection.execute("SELECT id FROM B;"), Err(IrisError::UnknownTable(_))));
+        connection.execute("INSERT INTO A (id) VALUES (4);").unwrap();
+        drop(connection);
+        let mut connection = Connection::open(dir.path()).unwrap();
+        assert_eq!(ids(&mut connection, "A"), vec!("1", "4"));
+    }
+
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Every error that can stop the execution of a request.
+#[derive(Debug)]
+pub enum IrisError {
+    /// The request doesn't follow the grammar of Iris.
+    Syntax(String),
+    UnknownTable(String),
+    UnknownColumn {
+        table: String,
+        column: String
+    },
+    /// A value doesn't have the type expected by its column or its operator.
+    TypeMismatch(String),
+    /// An expression can't be computed, like a division by zero.
+    Evaluation(String),
+    ConstraintViolation(String),
+    /// The request is well formed but can't be executed on this database, like a table created twice.
+    InvalidRequest(String),
+    /// A file of the database doesn't contain what Iris wrote in it.
+    Corrupted(String),
+    Io(String)
+}
+
+impl IrisError {
+
+    pub fn io(context: &str, error: std::io::Error) -> IrisError {
+        IrisError::Io(format!("{context}: {error}"))
+    }
+
+}
+
+impl fmt::Display for IrisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IrisError::Syntax(msg) => write!(f, "Syntax error: {msg}"),
+            IrisError::UnknownTable(table) => write!(f, "The table {table} doesn't exist."),
+            IrisError::UnknownColumn { table, column } => write!(f, "The column {column} doesn't exist in the table {table}."),
+            IrisError::TypeMismatch(msg) => write!(f, "Type mismatch: {msg}"),
+            IrisError::Evaluation(msg) => write!(f, "Evaluation error: {msg}"),
+            IrisError::ConstraintViolation(msg) => write!(f, "Constraint violation: {msg}"),
+            IrisError::InvalidRequest(msg) => write!(f, "{msg}"),
+            IrisError::Corrupted(msg) => write!(f, "Corrupted database: {msg}"),
+            IrisError::Io(msg) => write!(f, "IO error: {msg}")
+        }
+    }
+}
+
+impl std::error::Error for IrisError {}
@@ -0,0 +1,226 @@
+use super::include::{
+    JsonValue,
+    Map,
+    HashMap,
+    IrisError,
+    ConsumeResult,
+    Type,
+    compare_json_values
+};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max
+}
+
+/// An aggregate function applied on a column, or on the lines themselves for COUNT(*)
+#[derive(Debug, Clone)]
+pub struct Aggregate {
+    function: Function,
+    column: Option<String>,
+    key: Option<String>,
+    result_type: Type
+}
+
+impl Aggregate {
+
+    pub fn new(name: &str, column: &str) -> Result<Aggregate, IrisError> {
+        let function = match name {
+            "COUNT" => Function::Count,
+            "SUM" => Function::Sum,
+            "AVG" => Function::Avg,
+            "MIN" => Function::Min,
+            "MAX" => Function::Max,
+            _ => return Err(IrisError::Syntax(format!("Unknow aggregate function: {name}")))
+        };
+        let column = if column == "*" { None } else { Some(column.to_string()) };
+        if column.is_none() && function != Function::Count {
+            return Err(IrisError::InvalidRequest(format!("Only COUNT accepts * as argument, {name} needs a column.")))
+        }
+        Ok(Aggregate {
+            function,
+            key: column.clone(),
+            column,
+            result_type: Type::Int
+        })
+    }
+
+    /// The name of the aggregate as it appears in the request, like COUNT(*) or SUM(age)
+    pub fn name(&self) -> String {
+        format!("{}({})", format!("{:?}", self.function).to_uppercase(), self.column.as_deref().unwrap_or("*"))
+    }
+
+    pub fn result_type(&self) -> Type {
+        self.result_type
+    }
+
+    /// The column as written in the request, None for *
+    pub fn column(&self) -> Option<&String> {
+        self.column.as_ref()
+    }
+
+    /// Give the name of the aggregated column in the browsed lines and its type, refused if the function can't aggregate this type.
+    pub fn set_column_key(&mut self, key: String, column_type: Type) -> ConsumeResult {
+        match self.function {
+            Function::Sum | Function::Avg if !column_type.is_numeric() =>
+                return Err(IrisError::TypeMismatch(format!("{} can't be computed on the column {key} of type {column_type}.", self.name()))),
+            Function::Min | Function::Max => self.result_type = column_type,
+            Function::Avg => self.result_type = Type::Float,
+            Function::Sum if column_type == Type::Float => self.result_type = Type::Float,
+            _ => ()
+        }
+        self.key = Some(key);
+        Ok(())
+    }
+
+}
+
+/// The state of an aggregate for one group while the lines are browsed,
+/// the INT values are summed exactly and the FLOAT values apart.
+#[derive(Default)]
+struct Accumulator {
+    count: i64,
+    sum: i128,
+    float_sum: f64,
+    min: Option<JsonValue>,
+    max: Option<JsonValue>
+}
+
+impl Accumulator {
+
+    fn add(&mut self, aggregate: &Aggregate, line: &Map<String, JsonValue>) {
+        let value = match &aggregate.key {
+            Some(column) => match line.get(column) {
+                Some(value) if !value.is_null() => value,
+                _ => return
+            },
+            None => {
+                self.count += 1;
+                return
+            }
+        };
+        self.count += 1;
+        match aggregate.function {
+            Function::Sum | Function::Avg => match value.as_i64() {
+                Some(number) => self.sum += number as i128,
+                None => self.float_sum += value.as_f64().unwrap_or_default()
+            },
+            Function::Min => if self.min.as_ref().is_none_or(|min| compare_json_values(value, min) == Ordering::Less) {
+                self.min = Some(value.clone())
+            },
+            Function::Max => if self.max.as_ref().is_none_or(|max| compare_json_values(value, max) == Ordering::Greater) {
+                self.max = Some(value.clone())
+            },
+            Function::Count => ()
+        }
+    }
+
+    /// The result of the aggregate, NULL for a function other than COUNT without any value
+    fn result(&self, aggregate: &Aggregate) -> Result<JsonValue, IrisError> {
+        Ok(match aggregate.function {
+            Function::Count => JsonValue::from(self.count),
+            _ if self.count == 0 => JsonValue::Null,
+            Function::Sum if aggregate.result_type == Type::Float => JsonValue::from(self.checked_float_sum(aggregate)?),
+            Function::Sum => JsonValue::from(self.checked_sum(aggregate)?),
+            Function::Avg => JsonValue::from(self.checked_float_sum(aggregate)? / self.count as f64),
+            Function::Min => self.min.clone().unwrap_or_default(),
+            Function::Max => self.max.clone().unwrap_or_default()
+        })
+    }
+
+    fn checked_sum(&self, aggregate: &Aggregate) -> Result<i64, IrisError> {
+        i64::try_from(self.sum).map_err(|_| IrisError::InvalidRequest(format!("The result of {} doesn't fit in an INT.", aggregate.name())))
+    }
+
+    fn checked_float_sum(&self, aggregate: &Aggregate) -> Result<f64, IrisError> {
+        let sum = self.float_sum + self.sum as f64;
+        if !sum.is_finite() {
+            return Err(IrisError::InvalidRequest(format!("The result of {} doesn't fit in a FLOAT.", aggregate.name())))
+        }
+        Ok(sum)
+    }
+
+}
+
+struct Group {
+    values: Map<String, JsonValue>,
+    accumulators: Vec<Accumulator>
+}
+
+/// Split the browsed lines in groups according to the GROUP BY columns and compute the aggregates of each group.
+pub struct Aggregator {
+    group_by: Vec<String>,
+    aggregates: Vec<Aggregate>,
+    groups: Vec<Group>,
+    group_index: HashMap<String, usize>
+}
+
+impl Aggregator {
+
+    pub fn new() -> Aggregator {
+        Aggregator {
+            group_by: Vec::new(),
+            aggregates: Vec::new(),
+            groups: Vec::new(),
+            group_index: HashMap::new()
+        }
+    }
+
+    /// Prepare the aggregation of the lines, every aggregate is computed only once even if it is asked several times.
+    pub fn init(&mut self, group_by: Vec<String>, aggregates: Vec<Aggregate>) {
+        self.group_by = group_by;
+        self.aggregates.clear();
+        for aggregate in aggregates {
+            if !self.aggregates.iter().any(|a| a.name() == aggregate.name()) {
+                self.aggregates.push(aggregate);
+            }
+        }
+        self.groups.clear();
+        self.group_index.clear();
+    }
+
+    pub fn add_line(&mut self, line: &Map<String, JsonValue>) {
+        let values = self.group_by.iter().map(|col| (col.clone(), line.get(col).cloned().unwrap_or_default())).collect::<Map<_, _>>();
+        let key = JsonValue::Object(values.clone()).to_string();
+        let group = match self.group_index.get(&key) {
+            Some(group) => *group,
+            None => {
+                self.group_index.insert(key, self.groups.len());
+                self.groups.push(Group {
+                    values,
+                    accumulators: self.aggregates.iter().map(|_| Accumulator::default()).collect()
+                });
+                self.groups.len() - 1
+            }
+        };
+        for (aggregate, accumulator) in self.aggregates.iter().zip(self.groups[group].accumulators.iter_mut()) {
+            accumulator.add(aggregate, line);
+        }
+    }
+
+    /// Returns a line for each group in the order they appeared, containing the grouped columns and the result of every aggregate under its name.
+    /// Without GROUP BY, the whole table is one group even if it's empty.
+    pub fn take_groups(&mut self) -> Result<Vec<Map<String, JsonValue>>, IrisError> {
+        if self.group_by.is_empty() && self.groups.is_empty() {
+            self.groups.push(Group {
+                values: Map::new(),
+                accumulators: self.aggregates.iter().map(|_| Accumulator::default()).collect()
+            });
+        }
+        let mut res = Vec::new();
+        for mut group in self.groups.drain(..) {
+            for (aggregate, accumulator) in self.aggregates.iter().zip(group.accumulators.iter()) {
+                group.values.insert(aggregate.name(), accumulator.result(aggregate)?);
+            }
+            res.push(group.values);
+        }
+        self.group_index.clear();
+        Ok(res)
+    }
+
+}
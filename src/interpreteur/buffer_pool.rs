@@ -0,0 +1,260 @@
+use super::include::IrisError;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The size of the pages of the data files
+pub const PAGE_SIZE: usize = 4096;
+
+/// The memory used by the pages kept in the buffer pool when no other limit is given
+pub const DEFAULT_MEMORY_LIMIT: usize = 64 << 20;
+
+/// The buffer pool shared by the tables of a database
+#[derive(Debug, Clone)]
+pub struct SharedPool(Arc<Mutex<BufferPool>>);
+
+impl SharedPool {
+
+    pub fn new(dir: &Path, memory_limit: usize) -> SharedPool {
+        SharedPool(Arc::new(Mutex::new(BufferPool::new(dir, memory_limit))))
+    }
+
+    /// A panic while the pool was locked doesn't leave its pages inconsistent, every change of a page is made at once
+    pub fn lock(&self) -> MutexGuard<'_, BufferPool> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+}
+
+#[derive(Debug)]
+struct Frame {
+    data: Box<[u8]>,
+    /// The last use of the page, its key in the least recently used pages while it is clean
+    used: u64
+}
+
+/// The pages of a data file kept in memory
+#[derive(Debug, Default)]
+struct PoolFile {
+    handle: Option<File>,
+    frames: HashMap<u32, Frame>,
+    dirty: BTreeSet<u32>
+}
+
+/// Keep in memory the last used pages of the data files, up to the memory limit.
+/// The modified pages stay in memory until they are taken to be written, so the files are never modified by a request which isn't over.
+#[derive(Debug)]
+pub struct BufferPool {
+    dir: PathBuf,
+    files: HashMap<String, PoolFile>,
+    /// The clean pages ordered from the least recently used, they are the ones evicted when the pool is full
+    lru: BTreeMap<u64, (String, u32)>,
+    clock: u64,
+    frame_count: usize,
+    limit: usize
+}
+
+impl BufferPool {
+
+    fn new(dir: &Path, memory_limit: usize) -> BufferPool {
+        BufferPool {
+            dir: dir.to_path_buf(),
+            files: HashMap::new(),
+            lru: BTreeMap::new(),
+            clock: 0,
+            frame_count: 0,
+            limit: BufferPool::pages_in(memory_limit)
+        }
+    }
+
+    /// At least one page is kept, the one in use
+    fn pages_in(memory_limit: usize) -> usize {
+        (memory_limit / PAGE_SIZE).max(1)
+    }
+
+    /// The memory used by the pages in the pool, the modified pages are kept even if they go beyond it
+    pub fn memory_limit(&self) -> usize {
+        self.limit * PAGE_SIZE
+    }
+
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.limit = BufferPool::pages_in(memory_limit);
+        self.evict(0);
+    }
+
+    /// Read the page of the file, from the pool if it is there
+    pub fn read(&mut self, file: &str, page: u32) -> Result<&[u8], IrisError> {
+        self.load(file, page)?;
+        Ok(&self.frame(file, page).data)
+    }
+
+    /// Give the page of the file to modify, it stays in the pool until it is taken to be written
+    pub fn write(&mut self, file: &str, page: u32) -> Result<&mut [u8], IrisError> {
+        self.load(file, page)?;
+        let used = self.frame(file, page).used;
+        if self.files.get_mut(file).is_some_and(|f| f.dirty.insert(page)) {
+            self.lru.remove(&used);
+        }
+        Ok(&mut self.frame(file, page).data)
+    }
+
+    /// Add a new empty page to the file, or empty one of its pages
+    pub fn create(&mut self, file: &str, page: u32) -> &mut [u8] {
+        self.discard(file, page);
+        self.evict(1);
+        let used = self.tick();
+        let pool_file = self.files.entry(file.to_string()).or_default();
+        pool_file.frames.insert(page, Frame { data: vec![0; PAGE_SIZE].into_boxed_slice(), used });
+        pool_file.dirty.insert(page);
+        self.frame_count += 1;
+        &mut self.frame(file, page).data
+    }
+
+    /// Take a copy of every modified page of the file to write it, the pages become clean
+    pub fn take_dirty(&mut self, file: &str) -> Vec<(u32, Vec<u8>)> {
+        let Some(pool_file) = self.files.get_mut(file) else {
+            return Vec::new()
+        };
+        let mut pages = Vec::new();
+        for page in std::mem::take(&mut pool_file.dirty) {
+            let frame = &pool_file.frames[&page];
+            pages.push((page, frame.data.to_vec()));
+            self.lru.insert(frame.used, (file.to_string(), page));
+        }
+        self.evict(0);
+        pages
+    }
+
+    pub fn has_dirty(&self, file: &str) -> bool {
+        self.files.get(file).is_some_and(|f| !f.dirty.is_empty())
+    }
+
+    /// Forget the pages of the file from the given one, the file will be truncated before them
+    pub fn truncate(&mut self, file: &str, from: u32) {
+        let pages = self.files.get(file).map(|f| f.frames.keys().filter(|page| **page >= from).copied().collect::<Vec<_>>()).unwrap_or_default();
+        for page in pages {
+            self.discard(file, page);
+        }
+    }
+
+    /// Forget every page of the file, modified or not, and close it
+    pub fn forget(&mut self, file: &str) {
+        if let Some(pool_file) = self.files.remove(file) {
+            for frame in pool_file.frames.values() {
+                self.lru.remove(&frame.used);
+            }
+            self.frame_count -= pool_file.frames.len();
+        }
+    }
+
+    fn discard(&mut self, file: &str, page: u32) {
+        let Some(pool_file) = self.files.get_mut(file) else {
+            return
+        };
+        if let Some(frame) = pool_file.frames.remove(&page) {
+            self.lru.remove(&frame.used);
+            pool_file.dirty.remove(&page);
+            self.frame_count -= 1;
+        }
+    }
+
+    fn frame(&mut self, file: &str, page: u32) -> &mut Frame {
+        self.files.get_mut(file).and_then(|f| f.frames.get_mut(&page)).expect("The page was just loaded in the buffer pool")
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Bring the page in the pool and mark it as the most recently used
+    fn load(&mut self, file: &str, page: u32) -> Result<(), IrisError> {
+        let used = self.tick();
+        if let Some(pool_file) = self.files.get_mut(file) {
+            if let Some(frame) = pool_file.frames.get_mut(&page) {
+                let old = std::mem::replace(&mut frame.used, used);
+                if self.lru.remove(&old).is_some() {
+                    self.lru.insert(used, (file.to_string(), page));
+                }
+                return Ok(())
+            }
+        }
+        self.evict(1);
+        let path = self.dir.join(file);
+        let pool_file = self.files.entry(file.to_string()).or_default();
+        let mut data = vec![0; PAGE_SIZE].into_boxed_slice();
+        let handle = match pool_file.handle.as_mut() {
+            Some(handle) => handle,
+            None => pool_file.handle.insert(File::open(&path).map_err(|e| IrisError::io(&format!("Failed to open the data file {}", path.display()), e))?)
+        };
+        handle.seek(SeekFrom::Start(page as u64 * PAGE_SIZE as u64))
+            .and_then(|_| handle.read_exact(&mut data))
+            .map_err(|e| IrisError::io(&format!("Failed to read the page {page} of the data file {}", path.display()), e))?;
+        pool_file.frames.insert(page, Frame { data, used });
+        self.lru.insert(used, (file.to_string(), page));
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Evict the least recently used clean pages until the given number of pages can be added without going beyond the limit
+    fn evict(&mut self, room: usize) {
+        while self.frame_count + room > self.limit {
+            let Some((_, (file, page))) = self.lru.pop_first() else {
+                break
+            };
+            if let Some(pool_file) = self.files.get_mut(&file) {
+                pool_file.frames.remove(&page);
+                self.frame_count -= 1;
+            }
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_dir::TestDir;
+
+    fn pool_with_pages(dir: &TestDir, page_count: usize, memory_limit: usize) -> BufferPool {
+        let content = (0..page_count).flat_map(|page| vec!(page as u8; PAGE_SIZE)).collect::<Vec<_>>();
+        std::fs::write(dir.path().join("t"), content).unwrap();
+        BufferPool::new(dir.path(), memory_limit)
+    }
+
+    #[test]
+    fn clean_pages_are_evicted_beyond_the_limit() {
+        let dir = TestDir::new("pool-evict");
+        let mut pool = pool_with_pages(&dir, 6, 2 * PAGE_SIZE);
+        for page in 0..6 {
+            assert!(pool.read("t", page).unwrap().iter().all(|byte| *byte == page as u8));
+            assert!(pool.frame_count <= 2);
+        }
+        pool.read("t", 4).unwrap();
+        pool.read("t", 0).unwrap();
+        assert!(pool.files["t"].frames.contains_key(&0) && pool.files["t"].frames.contains_key(&4));
+        pool.set_memory_limit(PAGE_SIZE / 2);
+        assert_eq!(pool.frame_count, 1);
+        assert!(pool.files["t"].frames.contains_key(&0));
+    }
+
+    #[test]
+    fn modified_pages_stay_until_they_are_taken() {
+        let dir = TestDir::new("pool-dirty");
+        let mut pool = pool_with_pages(&dir, 6, 2 * PAGE_SIZE);
+        for page in 0..3 {
+            pool.write("t", page).unwrap()[0] = 100 + page as u8;
+        }
+        pool.read("t", 5).unwrap();
+        assert_eq!(pool.frame_count, 4);
+        assert!(pool.has_dirty("t"));
+        let pages = pool.take_dirty("t");
+        assert_eq!(pages.iter().map(|(page, data)| (*page, data[0])).collect::<Vec<_>>(), vec!((0, 100), (1, 101), (2, 102)));
+        assert!(pool.frame_count <= 2);
+        assert!(!pool.has_dirty("t"));
+    }
+
+}
@@ -1,22 +1,230 @@
 use super::stack::Stack;
 use std::collections::HashMap;
-use super::include::{JsonValue, Value, Map};
+use super::include::{JsonValue, Map, IrisError, Temporal, json, TEMPORAL_FUNCTIONS, TEMPORAL_FIELDS, NEGATION};
+use std::fmt;
+use std::cmp::Ordering;
 
 type Number = i64;
-type Operation = fn(Number, Number) -> Number;
+type Float = f64;
+type Operation = fn(Number, Number) -> Option<Number>;
 
+#[derive(Debug, Clone)]
 pub struct ExpressionEvaluator {
     op_stack: Stack<String>,
     pf_exp: Vec<ExpTokenType>,
     operator_priority: HashMap<String, u8>,
     op_map: HashMap<String, Operation>,
+    null_test: Option<bool>
 }
 
-#[derive(Debug)]
-enum ExpTokenType {
-    Operator(Operation),
+/// The operator giving the same result once its operands are swapped.
+fn mirror_comparison(op: &str) -> String {
+    String::from(match op {
+        "<" => ">",
+        "<=" => ">=",
+        ">" => "<",
+        ">=" => "<=",
+        _ => op
+    })
+}
+
+/// A value handled by an expression, strings are compared by content in the lexicographic order.
+/// A number and a float can be mixed, the number is then converted to a float.
+/// Comparisons give booleans, the logical operators only accept booleans.
+/// The temporal values are compared with the strings written in ISO-8601, and two such strings are compared as temporal values.
+/// NULL is an unknown value, most operations involving it give NULL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
     Number(Number),
-    Field(String)
+    Float(Float),
+    Bool(bool),
+    String(String),
+    Temporal(Temporal),
+    Null
+}
+
+impl Operand {
+
+    fn from_json(data: &JsonValue) -> Result<Operand, IrisError> {
+        let unexpected = || IrisError::Evaluation(format!("The value {data} can't be used in an expression."));
+        Ok(match data {
+            JsonValue::String(string) => Operand::String(string.clone()),
+            JsonValue::Bool(boolean) => Operand::Bool(*boolean),
+            JsonValue::Number(number) => match number.as_i64() {
+                Some(number) => Operand::Number(number),
+                None => Operand::Float(number.as_f64().ok_or_else(unexpected)?)
+            },
+            JsonValue::Null => Operand::Null,
+            _ => return Err(unexpected())
+        })
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, Operand::String(_))
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Operand::Null
+    }
+
+    /// The temporal value of the operand or of its string
+    fn as_temporal(&self) -> Option<Temporal> {
+        match self {
+            Operand::Temporal(temporal) => Some(*temporal),
+            Operand::String(string) => Temporal::parse(string),
+            _ => None
+        }
+    }
+
+    /// The value of a number or of a float as a float
+    fn as_float(&self) -> Option<Float> {
+        match self {
+            Operand::Number(number) => Some(*number as Float),
+            Operand::Float(float) => Some(*float),
+            _ => None
+        }
+    }
+
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Number(number) => write!(f, "the number {number}"),
+            Operand::Float(float) => write!(f, "the number {float:?}"),
+            Operand::Bool(boolean) => write!(f, "the boolean {}", if *boolean { "TRUE" } else { "FALSE" }),
+            Operand::String(string) => write!(f, "the string '{string}'"),
+            Operand::Temporal(temporal) => write!(f, "the {} {temporal}", temporal.type_name()),
+            Operand::Null => write!(f, "NULL")
+        }
+    }
+}
+
+impl From<Operand> for JsonValue {
+    fn from(operand: Operand) -> JsonValue {
+        match operand {
+            Operand::Number(number) => JsonValue::from(number),
+            Operand::Float(float) => JsonValue::from(float),
+            Operand::Bool(boolean) => JsonValue::Bool(boolean),
+            Operand::String(string) => JsonValue::from(string),
+            Operand::Temporal(temporal) => JsonValue::from(temporal.to_string()),
+            Operand::Null => JsonValue::Null
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ExpTokenType {
+    Operator(String, Operation),
+    Operand(Operand),
+    Field(String),
+    /// IS NULL, or IS NOT NULL when true
+    NullTest(bool),
+    Not,
+    /// The minus sign before a value
+    Negation,
+    /// A call of NOW, DATE_ADD or EXTRACT on the values of its arguments
+    Function(String)
+}
+
+/// The number of arguments of the function
+fn arity(function: &str) -> usize {
+    match function {
+        "NOW" => 0,
+        _ => 2
+    }
+}
+
+/// Compare two temporal values or ISO-8601 strings, None if one of them is not temporal or if they can't be compared
+fn temporal_order(o1: &Operand, o2: &Operand) -> Option<Ordering> {
+    o1.as_temporal()?.compare(&o2.as_temporal()?)
+}
+
+/// NOW(), DATE_ADD(value, days) and EXTRACT(field FROM value), NULL if one of the arguments is NULL
+fn call_function(function: &str, args: &[Operand]) -> Result<Operand, IrisError> {
+    if args.iter().any(Operand::is_null) {
+        return Ok(Operand::Null)
+    }
+    let mismatch = || IrisError::TypeMismatch(format!("The function {function} can't be applied to {}.", args.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(" and ")));
+    // a string may contain a date or not depending on the line, the types of a condition are checked with any string
+    let not_temporal = |value: &Operand| match value {
+        Operand::String(_) => IrisError::Evaluation(format!("The function {function} can't be applied to {value}, it doesn't contain a date or a time.")),
+        _ => mismatch()
+    };
+    match (function, args) {
+        ("NOW", []) => Ok(Operand::Temporal(Temporal::now())),
+        ("DATE_ADD", [value, Operand::Number(days)]) => {
+            let temporal = value.as_temporal().ok_or_else(|| not_temporal(value))?;
+            if temporal.is_time() {
+                return Err(mismatch())
+            }
+            temporal.add_days(*days)
+                .map(Operand::Temporal)
+                .ok_or_else(|| IrisError::Evaluation(format!("The result of DATE_ADD({temporal}, {days}) is not between the years 0 and 9999.")))
+        }
+        ("EXTRACT", [Operand::String(field), value]) => value.as_temporal()
+            .ok_or_else(|| not_temporal(value))?
+            .extract(field)
+            .map(Operand::Number)
+            .ok_or_else(mismatch),
+        _ => Err(mismatch())
+    }
+}
+
+/// The error of an expression whose operators don't have all their operands
+fn missing_operand() -> IrisError {
+    IrisError::Syntax(String::from("An operator of the expression is missing one of its operands."))
+}
+
+fn unbalanced_parenthesis() -> IrisError {
+    IrisError::Syntax(String::from("The parenthesis of the expression are not balanced."))
+}
+
+/// The error of an arithmetic operation without result
+fn arithmetic_error(op: &str, n1: Number, n2: Number) -> IrisError {
+    if n2 == 0 && "/%".contains(op) {
+        IrisError::Evaluation(format!("Division by zero in {n1} {op} {n2}."))
+    } else {
+        IrisError::Evaluation(format!("The result of {n1} {op} {n2} doesn't fit in an INT."))
+    }
+}
+
+/// The arithmetic operations on floats, refused if the result is not a finite float
+fn float_arithmetic(op: &str, f1: Float, f2: Float) -> Result<Operand, IrisError> {
+    let result = match op {
+        "/" | "%" if f2 == 0.0 => return Err(IrisError::Evaluation(format!("Division by zero in {f1:?} {op} {f2:?}."))),
+        "+" => f1 + f2,
+        "-" => f1 - f2,
+        "*" => f1 * f2,
+        "/" => f1 / f2,
+        "%" => f1 % f2,
+        _ => return Err(IrisError::TypeMismatch(format!("The operator {op} can't be applied to {f1:?} and {f2:?}.")))
+    };
+    if !result.is_finite() {
+        return Err(IrisError::Evaluation(format!("The result of {f1:?} {op} {f2:?} doesn't fit in a FLOAT.")))
+    }
+    Ok(Operand::Float(result))
+}
+
+fn is_comparison(op: &str) -> bool {
+    ["<", "<=", ">", ">=", "==", "!="].contains(&op)
+}
+
+fn is_logic(op: &str) -> bool {
+    op == "&&" || op == "||"
+}
+
+/// Three-valued logic: FALSE && NULL is FALSE and TRUE || NULL is TRUE, the other combinations with NULL give NULL.
+fn logic_with_null(op: &str, o1: &Operand, o2: &Operand) -> Result<Operand, IrisError> {
+    let absorbing = op == "||";
+    for operand in [o1, o2] {
+        match operand {
+            Operand::Bool(boolean) if *boolean == absorbing => return Ok(Operand::Bool(absorbing)),
+            Operand::Bool(_) | Operand::Null => (),
+            _ => return Err(IrisError::TypeMismatch(format!("The operator {op} can't be applied to {o1} and {o2}.")))
+        }
+    }
+    Ok(Operand::Null)
 }
 
 impl ExpressionEvaluator {
@@ -27,41 +235,123 @@ impl ExpressionEvaluator {
             pf_exp: Vec::new(),
             operator_priority: ExpressionEvaluator::build_prio_map(),
             op_map: ExpressionEvaluator::build_op_map(),
+            null_test: None
         }
     }
 
     pub fn clear(&mut self) {
         self.pf_exp.clear();
         self.op_stack.clear();
+        self.null_test = None;
     }
 
     pub fn is_empty(&self) -> bool {
         self.pf_exp.is_empty()
     }
 
-    pub fn new_operator(&mut self, content: String) {
-        if "()".contains(&content) {
-            self.new_parenthesis(content)
-        } else {
-            while !self.op_stack.is_empty() && 
-                self.op_stack.val().unwrap() != "(" && 
-                self.get_priority(self.top_op()) >= self.get_priority(&content) {
-                    self.push_op_val();
+    /// The expression in postfix notation, as it is stored in the files of the database
+    pub fn get_datas(&self) -> JsonValue {
+        JsonValue::Array(self.pf_exp.iter().map(|t| match t {
+            ExpTokenType::Operator(op, _) => json!({"operator": op}),
+            ExpTokenType::Operand(operand) => json!({"value": JsonValue::from(operand.clone())}),
+            ExpTokenType::Field(field) => json!({"field": field}),
+            ExpTokenType::NullTest(negated) => json!({"null_test": negated}),
+            ExpTokenType::Not => json!({"operator": "NOT"}),
+            ExpTokenType::Negation => json!({"operator": NEGATION}),
+            ExpTokenType::Function(function) => json!({"function": function})
+        }).collect())
+    }
+
+    pub fn load(json_data: &JsonValue) -> Result<ExpressionEvaluator, IrisError> {
+        let corrupted = || IrisError::Corrupted(format!("An expression was expected in the tables data, found {json_data}"));
+        let mut res = ExpressionEvaluator::new();
+        for t in json_data.as_array().ok_or_else(corrupted)? {
+            let token = match (&t["operator"], &t["value"], &t["field"], &t["null_test"]) {
+                (JsonValue::String(op), ..) if op == "NOT" => ExpTokenType::Not,
+                (JsonValue::String(op), ..) if op == NEGATION => ExpTokenType::Negation,
+                (JsonValue::String(op), ..) => {
+                    let operation = *res.op_map.get(op).ok_or_else(corrupted)?;
+                    ExpTokenType::Operator(op.clone(), operation)
                 }
-            self.op_stack.push(content);
+                (_, JsonValue::Number(_) | JsonValue::String(_) | JsonValue::Bool(_), ..) => ExpTokenType::Operand(Operand::from_json(&t["value"]).map_err(|_| corrupted())?),
+                (_, _, JsonValue::String(field), _) => ExpTokenType::Field(field.clone()),
+                (_, _, _, JsonValue::Bool(negated)) => ExpTokenType::NullTest(*negated),
+                _ if t.get("value").is_some_and(JsonValue::is_null) => ExpTokenType::Operand(Operand::Null),
+                _ if t["function"].as_str().is_some_and(|f| TEMPORAL_FUNCTIONS.contains(&f)) => ExpTokenType::Function(t["function"].as_str().unwrap_or_default().to_string()),
+                _ => return Err(corrupted())
+            };
+            res.pf_exp.push(token);
         }
+        Ok(res)
+    }
+
+    /// The columns used by the expression
+    pub fn fields(&self) -> Vec<&String> {
+        self.pf_exp.iter().filter_map(|t| match t {
+            ExpTokenType::Field(field) => Some(field),
+            _ => None
+        }).collect()
     }
 
-    fn top_op(&self) -> &String {
-        self.op_stack.val().as_ref().unwrap()
+    pub fn new_operator(&mut self, content: String) -> Result<(), IrisError> {
+        if ["(", ")", ","].contains(&content.as_str()) {
+            return self.new_parenthesis(content)
+        }
+        let priority = self.get_priority(&content)?;
+        if content != NEGATION {  // A prefix operator doesn't apply the waiting ones
+            self.apply_operators(priority)?;
+        }
+        self.op_stack.push(content);
+        Ok(())
+    }
+
+    /// Move to the postfix expression the waiting operators, up to the last parenthesis, which have at least the given priority
+    fn apply_operators(&mut self, priority: u8) -> Result<(), IrisError> {
+        while let Some(op) = self.op_stack.val().filter(|op| *op != "(") {
+            if self.get_priority(op)? < priority {
+                break;
+            }
+            self.push_op_val()?;
+        }
+        Ok(())
     }
     
-    pub fn new_number(&mut self, number: String) {
-        self.pf_exp.push(ExpTokenType::Number(str::parse::<Number>(&number).unwrap()));
+    /// A number literal, a float if it has a decimal part
+    pub fn new_number(&mut self, number: String) -> Result<(), IrisError> {
+        let operand = if number.contains('.') {
+            Operand::Float(str::parse::<Float>(&number).map_err(|_| IrisError::Syntax(format!("The number {number} is not a valid FLOAT.")))?)
+        } else {
+            Operand::Number(str::parse::<Number>(&number).map_err(|_| IrisError::Evaluation(format!("The number {number} doesn't fit in an INT.")))?)
+        };
+        self.pf_exp.push(ExpTokenType::Operand(operand));
+        Ok(())
     }
 
-    pub fn new_direct_number(&mut self, number: Number) {
-        self.pf_exp.push(ExpTokenType::Number(number));
+    pub fn new_string(&mut self, string: String) {
+        self.pf_exp.push(ExpTokenType::Operand(Operand::String(string)));
+    }
+
+    /// IS, NOT, NULL, TRUE and FALSE: the NULL and boolean values, the NOT operator,
+    /// or the IS NULL and IS NOT NULL tests of the previous value.
+    /// The temporal functions, called once their closing parenthesis is given, and the fields extracted by EXTRACT.
+    pub fn new_keyword(&mut self, keyword: &str) -> Result<(), IrisError> {
+        match (keyword, self.null_test) {
+            _ if TEMPORAL_FUNCTIONS.contains(&keyword) => self.op_stack.push(keyword.to_string()),
+            _ if TEMPORAL_FIELDS.contains(&keyword) => self.pf_exp.push(ExpTokenType::Operand(Operand::String(keyword.to_string()))),
+            ("IS", _) => self.null_test = Some(false),
+            ("NOT", Some(_)) => self.null_test = Some(true),
+            ("NOT", None) => self.op_stack.push(String::from("NOT")),  // A prefix operator doesn't apply the waiting ones
+            ("TRUE", None) => self.pf_exp.push(ExpTokenType::Operand(Operand::Bool(true))),
+            ("FALSE", None) => self.pf_exp.push(ExpTokenType::Operand(Operand::Bool(false))),
+            ("NULL", Some(negated)) => {
+                self.null_test = None;
+                self.apply_operators(self.get_priority("==")?)?;
+                self.pf_exp.push(ExpTokenType::NullTest(negated));
+            }
+            ("NULL", None) => self.pf_exp.push(ExpTokenType::Operand(Operand::Null)),
+            _ => return Err(IrisError::Syntax(format!("The keyword {keyword} can't be used in an expression.")))
+        }
+        Ok(())
     }
 
 
@@ -70,95 +360,316 @@ impl ExpressionEvaluator {
     }
 
     /// ( -> Push it on the op stack
-    /// ) -> pop the operators til we pop an opening bracket
-    pub fn new_parenthesis(&mut self, par: String) {
+    /// ) -> pop the operators til we pop an opening bracket, then the function called with these parenthesis
+    /// , -> pop the operators of the previous argument of the function
+    pub fn new_parenthesis(&mut self, par: String) -> Result<(), IrisError> {
         match &par as &str {
             "(" => self.op_stack.push(par),
             ")" => {
-                while self.top_op() != "(" {
-                    self.push_op_val();
+                self.apply_operators(0)?;
+                self.op_stack.pop().ok_or_else(unbalanced_parenthesis)?;
+                if self.op_stack.val().is_some_and(|op| TEMPORAL_FUNCTIONS.contains(&op.as_str())) {
+                    let function = self.op_stack.pop().unwrap_or_default();
+                    self.pf_exp.push(ExpTokenType::Function(function));
+                }
+            }
+            "," => {
+                self.apply_operators(0)?;
+                if self.op_stack.is_empty() {
+                    return Err(unbalanced_parenthesis())
                 }
-                self.op_stack.pop();
             }
-            _ => panic!("Unknow parenthesis: {par}")
-        } 
+            _ => return Err(IrisError::Syntax(format!("Unknow parenthesis: {par}")))
+        }
+        Ok(())
     }
 
-    fn get_priority(&self, op: &String) -> u8 {
-        *self.operator_priority.get(op).unwrap_or_else(
-            || panic!("This operator doesn't have priority yet: {op}")
-        )
+    fn get_priority(&self, op: &str) -> Result<u8, IrisError> {
+        self.operator_priority.get(op).copied().ok_or_else(|| IrisError::Syntax(format!("Unknown operator in an expression: {op}")))
     }
 
-    fn push_op_val(&mut self) {
-        let op_string = &self.op_stack.pop().expect("Operator stack empty");
-        let operation: Operation = *self.op_map.get(op_string).expect("Operator doesn't exists");
-        self.pf_exp.push(ExpTokenType::Operator(operation));
+    fn push_op_val(&mut self) -> Result<(), IrisError> {
+        let op_string = self.op_stack.pop().ok_or_else(missing_operand)?;
+        match &op_string as &str {
+            "NOT" => self.pf_exp.push(ExpTokenType::Not),
+            _ if op_string == NEGATION => self.push_negation(),
+            _ if TEMPORAL_FUNCTIONS.contains(&op_string.as_str()) || op_string == "(" => return Err(unbalanced_parenthesis()),
+            _ => {
+                let operation: Operation = *self.op_map.get(&op_string).ok_or_else(|| IrisError::Syntax(format!("Unknown operator in an expression: {op_string}")))?;
+                self.pf_exp.push(ExpTokenType::Operator(op_string, operation));
+            }
+        }
+        Ok(())
     }
 
+    /// A negative number is stored as a constant, so it can be used to look for lines in an index
+    fn push_negation(&mut self) {
+        let constant = match self.pf_exp.last() {
+            Some(ExpTokenType::Operand(Operand::Number(number))) => number.checked_neg().map(Operand::Number),
+            Some(ExpTokenType::Operand(Operand::Float(float))) => Some(Operand::Float(-float)),
+            _ => None
+        };
+        match constant {
+            Some(operand) => {
+                self.pf_exp.pop();
+                self.pf_exp.push(ExpTokenType::Operand(operand))
+            }
+            None => self.pf_exp.push(ExpTokenType::Negation)
+        }
+    }
 
-    pub fn compute(&mut self, fields: &Map<String, JsonValue>, clear: bool) -> Number {
+    /// Move the waiting operators to the postfix expression, once the whole expression is given
+    pub fn flush_operators(&mut self) -> Result<(), IrisError> {
         while !self.op_stack.is_empty() {
-            self.push_op_val();
+            self.push_op_val()?;
+        }
+        Ok(())
+    }
+
+    /// Index of the first token of the sub expression ending at the given index of the postfix expression, None if it misses operands
+    fn sub_expression_start(&self, end: usize) -> Option<usize> {
+        match self.pf_exp.get(end)? {
+            ExpTokenType::Operator(..) => {
+                let right_start = self.sub_expression_start(end.checked_sub(1)?)?;
+                self.sub_expression_start(right_start.checked_sub(1)?)
+            }
+            ExpTokenType::NullTest(_) | ExpTokenType::Not | ExpTokenType::Negation => self.sub_expression_start(end.checked_sub(1)?),
+            ExpTokenType::Function(function) => (0..arity(function)).try_fold(end, |start, _| self.sub_expression_start(start.checked_sub(1)?)),
+            _ => Some(end)
+        }
+    }
+
+    /// Split the sub expression ending at the given index on its top level &&
+    fn conjunctions(&self, end: usize, res: &mut Vec<(usize, usize)>) -> Option<()> {
+        match self.pf_exp.get(end)? {
+            ExpTokenType::Operator(op, _) if op == "&&" => {
+                let right_start = self.sub_expression_start(end.checked_sub(1)?)?;
+                self.conjunctions(right_start.checked_sub(1)?, res)?;
+                self.conjunctions(end - 1, res)
+            }
+            _ => {
+                res.push((self.sub_expression_start(end)?, end));
+                Some(())
+            }
+        }
+    }
+
+    /// The comparisons between the given field and a constant which have to be true for the whole expression to be true,
+    /// as (operator, constant) with the field on the left side of the operator.
+    pub fn field_conditions(&mut self, field: &str) -> Result<Vec<(String, Operand)>, IrisError> {
+        self.flush_operators()?;
+        let mut conjunctions = Vec::new();
+        if self.pf_exp.is_empty() || self.conjunctions(self.pf_exp.len() - 1, &mut conjunctions).is_none() {
+            return Ok(Vec::new())
+        }
+        Ok(conjunctions.iter().filter_map(|(start, end)| {
+            match &self.pf_exp[*start..=*end] {
+                [ExpTokenType::Field(f), ExpTokenType::Operand(o), ExpTokenType::Operator(op, _)] if f == field => Some((op.clone(), o.clone())),
+                [ExpTokenType::Operand(o), ExpTokenType::Field(f), ExpTokenType::Operator(op, _)] if f == field => Some((mirror_comparison(op), o.clone())),
+                _ => None
+            }
+        }).collect())
+    }
+
+
+    /// Compute the condition on the given line, it has to give a boolean. NULL counts as false.
+    pub fn compute(&mut self, fields: &Map<String, JsonValue>, clear: bool) -> Result<bool, IrisError> {
+        match self.evaluate(fields, clear)? {
+            Operand::Bool(boolean) => Ok(boolean),
+            Operand::Null => Ok(false),
+            operand => Err(IrisError::TypeMismatch(format!("The expression gives {operand} where a boolean is expected.")))
+        }
+    }
+
+    /// Check that the condition can be computed on the lines of the given sample, with a value of the type of each column.
+    /// The errors depending on the values, like a division by zero, are left to the computation on each line.
+    pub fn check_condition(&mut self, sample: &Map<String, JsonValue>) -> Result<(), IrisError> {
+        match self.compute(sample, false) {
+            Ok(_) | Err(IrisError::Evaluation(_)) => Ok(()),
+            Err(IrisError::TypeMismatch(msg)) => Err(IrisError::TypeMismatch(format!("The condition doesn't fit the types of the columns, with a value of each type: {msg}"))),
+            Err(e) => Err(e)
         }
-        let mut number_stack = Stack::<Number>::new();
+    }
+
+    pub fn evaluate(&mut self, fields: &Map<String, JsonValue>, clear: bool) -> Result<Operand, IrisError> {
+        self.flush_operators()?;
+        let mut operand_stack = Stack::<Operand>::new();
         for t in self.pf_exp.iter() {
             match t {
-                ExpTokenType::Operator(operation) => self.op_found(&mut number_stack, *operation),
-                ExpTokenType::Number(number) => self.number_found(&mut number_stack, *number),
-                ExpTokenType::Field(field) => self.number_found(&mut number_stack, Value::from_json(&fields[field]).val())
+                ExpTokenType::Operator(op, operation) => self.op_found(&mut operand_stack, op, *operation)?,
+                ExpTokenType::Operand(operand) => operand_stack.push(operand.clone()),
+                ExpTokenType::Field(field) => {
+                    let value = fields.get(field).ok_or_else(|| IrisError::Evaluation(format!("The column {field} has no value in this expression.")))?;
+                    operand_stack.push(Operand::from_json(value)?)
+                }
+                ExpTokenType::NullTest(negated) => {
+                    let operand = operand_stack.pop().ok_or_else(missing_operand)?;
+                    operand_stack.push(Operand::Bool(operand.is_null() != *negated))
+                }
+                ExpTokenType::Not => match operand_stack.pop().ok_or_else(missing_operand)? {
+                    Operand::Bool(boolean) => operand_stack.push(Operand::Bool(!boolean)),
+                    Operand::Null => operand_stack.push(Operand::Null),
+                    operand => return Err(IrisError::TypeMismatch(format!("The operator NOT can't be applied to {operand}.")))
+                }
+                ExpTokenType::Negation => match operand_stack.pop().ok_or_else(missing_operand)? {
+                    Operand::Number(number) => operand_stack.push(Operand::Number(number.checked_neg()
+                        .ok_or_else(|| IrisError::Evaluation(format!("The result of -({number}) doesn't fit in an INT.")))?)),
+                    Operand::Float(float) => operand_stack.push(Operand::Float(-float)),
+                    Operand::Null => operand_stack.push(Operand::Null),
+                    operand => return Err(IrisError::TypeMismatch(format!("The operator - can't be applied to {operand}.")))
+                }
+                ExpTokenType::Function(function) => {
+                    let mut args = (0..arity(function)).map(|_| operand_stack.pop().ok_or_else(missing_operand)).collect::<Result<Vec<_>, _>>()?;
+                    args.reverse();
+                    operand_stack.push(call_function(function, &args)?)
+                }
             }
         }
         if clear {
             self.pf_exp.clear();
         }
-        number_stack.pop().unwrap()
+        operand_stack.pop().ok_or_else(missing_operand)
     }
 
-    fn op_found(&self, number_stack: &mut Stack<Number>, operation: Operation) {
-        let n2 = number_stack.pop().unwrap();
-        let n1 = number_stack.pop().unwrap();
-        self.number_found(number_stack, operation(n1, n2));
-    }
-
-    fn number_found(&self, number_stack: &mut Stack<Number>, number: Number) {
-        number_stack.push(number)
+    /// Apply the operation on the two last operands, strings and booleans can only be compared between them.
+    /// An operation mixing a number and a float is computed on floats.
+    fn op_found(&self, operand_stack: &mut Stack<Operand>, op: &str, operation: Operation) -> Result<(), IrisError> {
+        let o2 = operand_stack.pop().ok_or_else(missing_operand)?;
+        let o1 = operand_stack.pop().ok_or_else(missing_operand)?;
+        if o1.is_null() || o2.is_null() {
+            let result = if is_logic(op) { logic_with_null(op, &o1, &o2)? } else { Operand::Null };
+            operand_stack.push(result);
+            return Ok(())
+        }
+        let truth = |ordering: Ordering| Operand::Bool(operation(ordering as Number, 0) == Some(1));
+        let result = match (&o1, &o2) {
+            (Operand::Number(n1), Operand::Number(n2)) if is_comparison(op) => truth(n1.cmp(n2)),
+            (Operand::Number(n1), Operand::Number(n2)) if !is_logic(op) => Operand::Number(operation(*n1, *n2).ok_or_else(|| arithmetic_error(op, *n1, *n2))?),
+            (Operand::String(s1), Operand::String(s2)) if is_comparison(op) => match (o1.as_temporal(), o2.as_temporal()) {
+                (Some(t1), Some(t2)) => truth(t1.compare(&t2).ok_or_else(|| IrisError::TypeMismatch(format!("The operator {op} can't compare the {} {t1} and the {} {t2}.", t1.type_name(), t2.type_name())))?),
+                _ => truth(s1.cmp(s2))
+            },
+            (Operand::Bool(b1), Operand::Bool(b2)) if is_comparison(op) => truth(b1.cmp(b2)),
+            (Operand::Bool(b1), Operand::Bool(b2)) if is_logic(op) => Operand::Bool(operation(*b1 as Number, *b2 as Number) == Some(1)),
+            _ => match (o1.as_float(), o2.as_float()) {
+                (Some(f1), Some(f2)) if is_comparison(op) => truth(f1.partial_cmp(&f2).unwrap_or(Ordering::Equal)),
+                (Some(f1), Some(f2)) if !is_logic(op) => float_arithmetic(op, f1, f2)?,
+                _ if is_comparison(op) && temporal_order(&o1, &o2).is_some() => truth(temporal_order(&o1, &o2).unwrap_or(Ordering::Equal)),
+                _ => return Err(IrisError::TypeMismatch(format!("The operator {op} can't be applied to {o1} and {o2}.")))
+            }
+        };
+        operand_stack.push(result);
+        Ok(())
     }
 
     
     fn build_prio_map() -> HashMap<String, u8>{
         let mut res = HashMap::<String, u8>::new();
-        for op in vec!["%", "*", "/"].iter() {
-            res.insert(String::from(*op), 4);
+        for op in ["%", "*", "/"].iter() {
+            res.insert(String::from(*op), 7);
         }
-        for op in vec!("<", "<=", ">", ">=", "==", "!=", "||", "&&").iter() {
-            res.insert(String::from(*op), 2);
+        for op in ["<", "<=", ">", ">=", "==", "!="].iter() {
+            res.insert(String::from(*op), 5);
         }
-        res.insert(String::from("+"), 3);
-        res.insert(String::from("-"), 3);
-        res.insert(String::from(")"), 4);
-        res.insert(String::from("("), 5);
+        res.insert(String::from("+"), 6);
+        res.insert(String::from("-"), 6);
+        res.insert(String::from("NOT"), 4);
+        res.insert(String::from(NEGATION), 8);
+        res.insert(String::from("&&"), 3);
+        res.insert(String::from("||"), 2);
+        res.insert(String::from(")"), 7);
+        res.insert(String::from("("), 8);
         res
     }
 
     fn build_op_map() -> HashMap<String, Operation> {
         let mut res = HashMap::<String, Operation>::new();
-        res.insert(String::from("%"), |n1, n2| n1 % n2);
-        res.insert(String::from("*"), |n1, n2| n1 * n2);
-        res.insert(String::from("+"), |n1, n2| n1 + n2);
-        res.insert(String::from("-"), |n1, n2| n1 - n2);
-        res.insert(String::from("<"), |n1, n2| (n1 < n2) as Number);
-        res.insert(String::from("<="), |n1, n2| (n1 <= n2) as Number);
-        res.insert(String::from(">"), |n1, n2| (n1 > n2) as Number);
-        res.insert(String::from(">="), |n1, n2| (n1 >= n2) as Number);
-        res.insert(String::from("=="), |n1, n2| (n1 == n2) as Number);
-        res.insert(String::from("!="), |n1, n2| (n1 != n2) as Number);
-        res.insert(String::from("||"), |n1, n2| ((n1 != 0) || (n2 != 0)) as Number);
-        res.insert(String::from("&&"), |n1, n2| ((n1 != 0) && (n2 != 0)) as Number);
+        res.insert(String::from("%"), |n1, n2| n1.checked_rem(n2));
+        res.insert(String::from("/"), |n1, n2| n1.checked_div(n2));
+        res.insert(String::from("*"), |n1, n2| n1.checked_mul(n2));
+        res.insert(String::from("+"), |n1, n2| n1.checked_add(n2));
+        res.insert(String::from("-"), |n1, n2| n1.checked_sub(n2));
+        res.insert(String::from("<"), |n1, n2| Some((n1 < n2) as Number));
+        res.insert(String::from("<="), |n1, n2| Some((n1 <= n2) as Number));
+        res.insert(String::from(">"), |n1, n2| Some((n1 > n2) as Number));
+        res.insert(String::from(">="), |n1, n2| Some((n1 >= n2) as Number));
+        res.insert(String::from("=="), |n1, n2| Some((n1 == n2) as Number));
+        res.insert(String::from("!="), |n1, n2| Some((n1 != n2) as Number));
+        res.insert(String::from("||"), |n1, n2| Some(((n1 != 0) || (n2 != 0)) as Number));
+        res.insert(String::from("&&"), |n1, n2| Some(((n1 != 0) && (n2 != 0)) as Number));
         res
     }
     
 
 
     
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expression(tokens: &[&str]) -> Result<ExpressionEvaluator, IrisError> {
+        let mut expr = ExpressionEvaluator::new();
+        for token in tokens {
+            match *token {
+                _ if *token == NEGATION => expr.new_operator(token.to_string())?,
+                "NOT" | "IS" | "NULL" | "TRUE" | "FALSE" | "SELECT" => expr.new_keyword(token)?,
+                _ if token.starts_with(char::is_numeric) => expr.new_number(token.to_string())?,
+                _ if token.starts_with(char::is_alphabetic) => expr.new_field(token.to_string()),
+                _ => expr.new_operator(token.to_string())?
+            }
+        }
+        Ok(expr)
+    }
+
+    fn line(values: JsonValue) -> Map<String, JsonValue> {
+        values.as_object().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn operators_follow_their_priority() {
+        let mut expr = expression(&["1", "+", "2", "*", "a", "==", "7", "&&", "NOT", "FALSE"]).unwrap();
+        assert!(expr.compute(&line(json!({"a": 3})), false).unwrap());
+        let mut expr = expression(&[NEGATION, "(", "a", "-", "4", ")", "*", "2"]).unwrap();
+        assert_eq!(expr.evaluate(&line(json!({"a": 1})), false).unwrap(), Operand::Number(6));
+    }
+
+    #[test]
+    fn negative_constants_are_folded() {
+        let mut expr = expression(&["a", "==", NEGATION, "5"]).unwrap();
+        assert_eq!(expr.field_conditions("a").unwrap(), vec![(String::from("=="), Operand::Number(-5))]);
+        assert_eq!(expr.get_datas(), json!([{"field": "a"}, {"value": -5}, {"operator": "=="}]));
+    }
+
+    #[test]
+    fn malformed_expressions_are_errors() {
+        assert!(matches!(expression(&["a", ")"]), Err(IrisError::Syntax(_))));
+        assert!(matches!(expression(&["a", ","]), Err(IrisError::Syntax(_))));
+        assert!(matches!(expression(&["a", "=", "1"]), Err(IrisError::Syntax(_))));
+        assert!(matches!(expression(&["a", "==", "SELECT"]), Err(IrisError::Syntax(_))));
+        let mut expr = expression(&["(", "a", "==", "1"]).unwrap();
+        assert!(matches!(expr.evaluate(&line(json!({"a": 1})), false), Err(IrisError::Syntax(_))));
+        let mut expr = expression(&["a", "+"]).unwrap();
+        assert!(matches!(expr.evaluate(&line(json!({"a": 1})), false), Err(IrisError::Syntax(_))));
+    }
+
+    #[test]
+    fn missing_and_unexpected_values_are_errors() {
+        let mut expr = expression(&["a", "==", "1"]).unwrap();
+        assert!(matches!(expr.evaluate(&line(json!({"b": 1})), false), Err(IrisError::Evaluation(_))));
+        assert!(matches!(expr.evaluate(&line(json!({"a": [1]})), false), Err(IrisError::Evaluation(_))));
+        assert!(matches!(expr.evaluate(&line(json!({"a": "x"})), false), Err(IrisError::TypeMismatch(_))));
+    }
+
+    #[test]
+    fn corrupted_stored_expressions_are_errors() {
+        assert!(matches!(ExpressionEvaluator::load(&json!([{"value": [1]}])), Err(IrisError::Corrupted(_))));
+        assert!(matches!(ExpressionEvaluator::load(&json!([{"operator": "<>"}])), Err(IrisError::Corrupted(_))));
+        let mut expr = ExpressionEvaluator::load(&json!([{"field": "a"}, {"operator": "+"}])).unwrap();
+        assert!(matches!(expr.evaluate(&line(json!({"a": 1})), false), Err(IrisError::Syntax(_))));
+        assert!(expr.field_conditions("a").unwrap().is_empty());
+    }
+
 }
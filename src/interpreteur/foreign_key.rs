@@ -0,0 +1,95 @@
+use super::include::{JsonValue, IrisError, json};
+
+/// What happens to the lines referencing a deleted line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnDelete {
+    Restrict,
+    Cascade,
+    SetNull
+}
+
+impl OnDelete {
+
+    pub fn from_keyword(keyword: &str) -> Option<OnDelete> {
+        match keyword {
+            "RESTRICT" => Some(OnDelete::Restrict),
+            "CASCADE" => Some(OnDelete::Cascade),
+            "SET" => Some(OnDelete::SetNull),
+            _ => None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            OnDelete::Restrict => "RESTRICT",
+            OnDelete::Cascade => "CASCADE",
+            OnDelete::SetNull => "SET NULL"
+        }
+    }
+
+}
+
+/// The reference of a column to the primary key or to a UNIQUE column of a table, declared with REFERENCES table(column).
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    table: String,
+    column: String,
+    on_delete: OnDelete
+}
+
+impl ForeignKey {
+
+    pub fn new() -> ForeignKey {
+        ForeignKey {
+            table: String::new(),
+            column: String::new(),
+            on_delete: OnDelete::Restrict
+        }
+    }
+
+    pub fn load(json_data: &JsonValue) -> Result<ForeignKey, IrisError> {
+        let on_delete = json_data["on_delete"].as_str().and_then(|name| OnDelete::from_keyword(name.split(' ').next().unwrap_or_default()));
+        match (&json_data["table"], &json_data["column"], on_delete) {
+            (JsonValue::String(table), JsonValue::String(column), Some(on_delete)) => Ok(ForeignKey {
+                table: table.clone(),
+                column: column.clone(),
+                on_delete
+            }),
+            _ => Err(IrisError::Corrupted(format!("A foreign key was expected in the tables data, found {json_data}")))
+        }
+    }
+
+    pub fn get_datas(&self) -> JsonValue {
+        json!({
+            "table": self.table,
+            "column": self.column,
+            "on_delete": self.on_delete.name()
+        })
+    }
+
+    pub fn table(&self) -> &String {
+        &self.table
+    }
+
+    pub fn column(&self) -> &String {
+        &self.column
+    }
+
+    pub fn on_delete(&self) -> OnDelete {
+        self.on_delete
+    }
+
+    /// The identifiers following REFERENCES, the referenced table then its column
+    pub fn new_ident(&mut self, name: String) {
+        if self.table.is_empty() {
+            self.table = name
+        } else {
+            self.column = name
+        }
+    }
+
+    pub fn set_on_delete(&mut self, on_delete: OnDelete) {
+        self.on_delete = on_delete
+    }
+
+}
@@ -0,0 +1,743 @@
+use super::include::{IrisError, ConsumeResult};
+use super::buffer_pool::{SharedPool, PAGE_SIZE};
+use super::wal::WalEntry;
+use std::collections::BTreeSet;
+
+/// The first bytes of a data file made of pages, the older data files stored the lines in json
+const MAGIC: &[u8; 8] = b"IRISPAGE";
+/// The version of the data files, the versions 1 and 2 were the json formats and the version 3 didn't number the lines
+const FORMAT_VERSION: u32 = 4;
+
+const FREE_PAGE: u8 = 0;
+const DATA_PAGE: u8 = 1;
+const OVERFLOW_PAGE: u8 = 2;
+
+/// A data page starts with its kind, its number of slots and the start of its cells, followed by the slots.
+/// Each slot gives the offset and the length of a cell, the cells are stored from the end of the page.
+const DATA_HEADER: usize = 5;
+const SLOT_SIZE: usize = 4;
+/// An overflow page starts with its kind, the next page of the chain and the length of its part of the line
+const OVERFLOW_HEADER: usize = 7;
+
+/// A cell starts with its kind and the sequence number of the line, which keeps the order of the insertions.
+/// It continues with the line itself, or the length of the line and the first page of the chain storing it.
+const INLINE_CELL: u8 = 0;
+const OVERFLOW_CELL: u8 = 1;
+const CELL_HEADER: usize = 9;
+const OVERFLOW_CELL_SIZE: usize = CELL_HEADER + 8;
+/// A longer line is stored in overflow pages, so a page always contains several lines
+const MAX_CELL: usize = PAGE_SIZE / 4;
+/// A page with this free space can receive any inline cell, the longest one is a line shorter than MAX_CELL after its header
+const ROOMY: usize = MAX_CELL + CELL_HEADER + SLOT_SIZE;
+
+/// Indicate if the data file starts like a data file made of pages
+pub fn is_heap_file(start: &[u8]) -> bool {
+    start.starts_with(MAGIC)
+}
+
+/// The place of a line in the data file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RowId {
+    page: u32,
+    slot: u16
+}
+
+fn read_u16(data: &[u8], at: usize) -> usize {
+    u16::from_le_bytes([data[at], data[at + 1]]) as usize
+}
+
+fn write_u16(data: &mut [u8], at: usize, value: usize) {
+    data[at..at + 2].copy_from_slice(&(value as u16).to_le_bytes());
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+fn write_u32(data: &mut [u8], at: usize, value: u32) {
+    data[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn read_u64(data: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(data[at..at + 8].try_into().expect("8 bytes were taken"))
+}
+
+fn slot_count(data: &[u8]) -> usize {
+    read_u16(data, 1)
+}
+
+/// The offset and the length of the cell of the slot, a free slot has no length
+fn slot(data: &[u8], slot: usize) -> (usize, usize) {
+    let at = DATA_HEADER + slot * SLOT_SIZE;
+    (read_u16(data, at), read_u16(data, at + 2))
+}
+
+fn set_slot(data: &mut [u8], slot: usize, offset: usize, len: usize) {
+    let at = DATA_HEADER + slot * SLOT_SIZE;
+    write_u16(data, at, offset);
+    write_u16(data, at + 2, len);
+}
+
+fn init_data_page(data: &mut [u8]) {
+    data[0] = DATA_PAGE;
+    write_u16(data, 1, 0);
+    write_u16(data, 3, PAGE_SIZE);
+}
+
+/// The room left by the slots and the cells, including the room of the removed cells
+fn free_space(data: &[u8]) -> usize {
+    let count = slot_count(data);
+    let cells = (0..count).map(|i| slot(data, i).1).sum::<usize>();
+    PAGE_SIZE - DATA_HEADER - count * SLOT_SIZE - cells
+}
+
+/// Move the cells against the end of the page to gather the room of the removed cells
+fn compact_page(data: &mut [u8]) {
+    let count = slot_count(data);
+    let cells = (0..count).map(|i| slot(data, i)).map(|(offset, len)| data[offset..offset + len].to_vec()).collect::<Vec<_>>();
+    let mut start = PAGE_SIZE;
+    for (i, cell) in cells.iter().enumerate().filter(|(_, cell)| !cell.is_empty()) {
+        start -= cell.len();
+        data[start..start + cell.len()].copy_from_slice(cell);
+        set_slot(data, i, start, cell.len());
+    }
+    write_u16(data, 3, start);
+}
+
+/// Store the cell in the given slot, or in the first free one, the page has to have room for it
+fn put_cell(data: &mut [u8], slot_number: Option<usize>, cell: &[u8]) -> usize {
+    let count = slot_count(data);
+    let slot_number = slot_number.or_else(|| (0..count).find(|i| slot(data, *i).1 == 0)).unwrap_or(count);
+    let new_count = count.max(slot_number + 1);
+    for i in count..new_count {
+        set_slot(data, i, 0, 0);
+    }
+    write_u16(data, 1, new_count);
+    if read_u16(data, 3) < DATA_HEADER + new_count * SLOT_SIZE + cell.len() {
+        compact_page(data);
+    }
+    let start = read_u16(data, 3) - cell.len();
+    data[start..start + cell.len()].copy_from_slice(cell);
+    write_u16(data, 3, start);
+    set_slot(data, slot_number, start, cell.len());
+    slot_number
+}
+
+/// Remove the cell of the slot, the free slots at the end of the page are removed
+fn remove_cell(data: &mut [u8], slot_number: usize) {
+    set_slot(data, slot_number, 0, 0);
+    let mut count = slot_count(data);
+    while count > 0 && slot(data, count - 1).1 == 0 {
+        count -= 1;
+    }
+    write_u16(data, 1, count);
+}
+
+/// The lines of a table stored in the pages of its data file. The first page is the header of the file,
+/// the next ones contain the lines, the parts of the longest lines or nothing when they are free.
+#[derive(Debug)]
+pub struct HeapFile {
+    file: String,
+    pool: SharedPool,
+    /// The free space of every page, none for the pages which don't contain lines
+    free: Vec<usize>,
+    /// The free pages, the first ones are reused before the file grows
+    free_pages: BTreeSet<u32>,
+    /// The pages which can receive any line
+    roomy: BTreeSet<u32>,
+    /// The page receiving the new lines while they fit in it
+    last: Option<u32>,
+    /// The number of pages of the data file on the disk
+    written_pages: usize,
+    /// The sequence number of the next inserted line
+    next_sequence: u64
+}
+
+/// The progress of a VACUUM, each step is written before the next one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VacuumStep {
+    /// Move the lines of the last data page to the first pages with room for them
+    Moving,
+    /// Move the overflow pages of the lines of the given data page and of the next ones to the first free pages
+    Chains(u32),
+    Over
+}
+
+impl HeapFile {
+
+    fn new(file: &str, pool: SharedPool) -> HeapFile {
+        HeapFile {
+            file: file.to_string(),
+            pool,
+            free: vec!(0),
+            free_pages: BTreeSet::new(),
+            roomy: BTreeSet::new(),
+            last: None,
+            written_pages: 0,
+            next_sequence: 0
+        }
+    }
+
+    /// A data file without any line, it is written with the other changes of the request or of the transaction
+    pub fn create(file: &str, pool: SharedPool) -> HeapFile {
+        let heap = HeapFile::new(file, pool);
+        let mut pool = heap.pool.lock();
+        let header = pool.create(file, 0);
+        header[..MAGIC.len()].copy_from_slice(MAGIC);
+        write_u32(header, MAGIC.len(), FORMAT_VERSION);
+        write_u32(header, MAGIC.len() + 4, PAGE_SIZE as u32);
+        drop(pool);
+        heap
+    }
+
+    /// Read the pages of the data file of the given size and return the place of its lines, in the order of their insertion.
+    /// The data pages without lines are freed.
+    pub fn open(file: &str, pool: SharedPool, size: u64) -> Result<(HeapFile, Vec<RowId>), IrisError> {
+        let mut heap = HeapFile::new(file, pool);
+        if !size.is_multiple_of(PAGE_SIZE as u64) {
+            return Err(heap.corrupted(format!("its size {size} is not a multiple of the size of the pages")))
+        }
+        let page_count = (size / PAGE_SIZE as u64) as usize;
+        heap.written_pages = page_count;
+        heap.free = vec!(0; page_count);
+        let pool = heap.pool.clone();
+        let mut pool = pool.lock();
+        let header = pool.read(file, 0)?;
+        match (read_u32(header, MAGIC.len()), read_u32(header, MAGIC.len() + 4) as usize) {
+            (FORMAT_VERSION, PAGE_SIZE) => (),
+            (FORMAT_VERSION, page_size) => return Err(heap.corrupted(format!("its pages have {page_size} bytes instead of {PAGE_SIZE}"))),
+            (version, _) => return Err(heap.corrupted(format!("the version {version} of its format is not supported, the last version is {FORMAT_VERSION}")))
+        }
+        let mut rows = Vec::new();
+        for page in 1..page_count as u32 {
+            let data = pool.read(file, page)?;
+            match data[0] {
+                FREE_PAGE => {
+                    heap.free_pages.insert(page);
+                }
+                DATA_PAGE if DATA_HEADER + slot_count(data) * SLOT_SIZE > PAGE_SIZE => return Err(heap.corrupted(format!("its page {page} has too many slots"))),
+                DATA_PAGE if slot_count(data) == 0 => {
+                    heap.free_pages.insert(page);
+                }
+                DATA_PAGE => {
+                    for slot_number in 0..slot_count(data) {
+                        let (offset, len) = slot(data, slot_number);
+                        if len == 0 {
+                            continue
+                        }
+                        if len < CELL_HEADER || offset + len > PAGE_SIZE {
+                            return Err(heap.corrupted(format!("the line {slot_number} of its page {page} is out of the page")))
+                        }
+                        rows.push((read_u64(data, offset + 1), RowId { page, slot: slot_number as u16 }));
+                    }
+                    let free = free_space(data);
+                    heap.set_free(page, free);
+                    heap.last = Some(page);
+                }
+                OVERFLOW_PAGE => (),
+                kind => return Err(heap.corrupted(format!("its page {page} has the unknown kind {kind}")))
+            }
+        }
+        drop(pool);
+        rows.sort_unstable_by_key(|(sequence, _)| *sequence);
+        heap.next_sequence = rows.last().map_or(0, |(sequence, _)| sequence + 1);
+        Ok((heap, rows.into_iter().map(|(_, row)| row).collect()))
+    }
+
+    fn corrupted(&self, msg: String) -> IrisError {
+        IrisError::Corrupted(format!("The data file of the table {} is corrupted, {msg}.", self.file))
+    }
+
+    fn set_free(&mut self, page: u32, free: usize) {
+        self.free[page as usize] = free;
+        if free >= ROOMY {
+            self.roomy.insert(page);
+        } else {
+            self.roomy.remove(&page);
+        }
+    }
+
+    /// The first free page or a new one at the end of the file
+    fn allocate(&mut self) -> u32 {
+        self.free_pages.pop_first().unwrap_or_else(|| {
+            self.free.push(0);
+            (self.free.len() - 1) as u32
+        })
+    }
+
+    /// A page with room for the cell, the last page receiving lines is filled before the others
+    fn page_for(&mut self, len: usize) -> u32 {
+        if let Some(last) = self.last.filter(|last| self.free[*last as usize] >= len + SLOT_SIZE) {
+            return last
+        }
+        if let Some(page) = self.roomy.first() {
+            return *page
+        }
+        let page = self.allocate();
+        init_data_page(self.pool.lock().create(&self.file, page));
+        self.set_free(page, PAGE_SIZE - DATA_HEADER);
+        self.last = Some(page);
+        page
+    }
+
+    /// Free the data page once its last line is removed
+    fn release_if_empty(&mut self, page: u32) -> ConsumeResult {
+        let mut pool = self.pool.lock();
+        if slot_count(pool.read(&self.file, page)?) > 0 {
+            return Ok(())
+        }
+        pool.create(&self.file, page);
+        drop(pool);
+        self.set_free(page, 0);
+        if self.last == Some(page) {
+            self.last = None;
+        }
+        self.free_pages.insert(page);
+        Ok(())
+    }
+
+    /// Free the overflow pages, they are emptied
+    fn release_chain(&mut self, pages: Vec<u32>) {
+        let mut pool = self.pool.lock();
+        for page in pages.iter() {
+            pool.create(&self.file, *page);
+        }
+        drop(pool);
+        self.free_pages.extend(pages);
+    }
+
+    /// Remove the free pages at the end of the file, the file is truncated with the other changes
+    fn trim(&mut self) {
+        let count = self.free.len();
+        while self.free.len() > 1 && self.free_pages.remove(&(self.free.len() as u32 - 1)) {
+            self.free.pop();
+        }
+        if self.free.len() < count {
+            self.pool.lock().truncate(&self.file, self.free.len() as u32);
+        }
+    }
+
+    /// The cell storing the line with its sequence number, the longest lines are written in a chain of overflow pages
+    fn cell(&mut self, sequence: u64, line: &[u8]) -> Vec<u8> {
+        if line.len() < MAX_CELL {
+            return [&[INLINE_CELL], &sequence.to_le_bytes()[..], line].concat()
+        }
+        let parts = line.chunks(PAGE_SIZE - OVERFLOW_HEADER).collect::<Vec<_>>();
+        let pages = parts.iter().map(|_| self.allocate()).collect::<Vec<_>>();
+        let mut pool = self.pool.lock();
+        for (i, part) in parts.iter().enumerate() {
+            let data = pool.create(&self.file, pages[i]);
+            data[0] = OVERFLOW_PAGE;
+            write_u32(data, 1, pages.get(i + 1).copied().unwrap_or(0));
+            write_u16(data, 5, part.len());
+            data[OVERFLOW_HEADER..OVERFLOW_HEADER + part.len()].copy_from_slice(part);
+        }
+        let mut cell = vec!(OVERFLOW_CELL);
+        cell.extend(sequence.to_le_bytes());
+        cell.extend((line.len() as u32).to_le_bytes());
+        cell.extend(pages[0].to_le_bytes());
+        cell
+    }
+
+    /// Read the line stored in the chain of overflow pages starting at the given one, with the pages of the chain
+    fn read_chain(&self, first: u32, len: usize) -> Result<(Vec<u8>, Vec<u32>), IrisError> {
+        let mut line = Vec::with_capacity(len);
+        let mut pages = Vec::new();
+        let mut page = first;
+        let mut pool = self.pool.lock();
+        while line.len() < len {
+            if page == 0 || page as usize >= self.free.len() {
+                drop(pool);
+                return Err(self.corrupted(format!("a line continues in the page {page} which doesn't exist")))
+            }
+            let data = pool.read(&self.file, page)?;
+            let part_len = read_u16(data, 5);
+            if data[0] != OVERFLOW_PAGE || OVERFLOW_HEADER + part_len > PAGE_SIZE {
+                drop(pool);
+                return Err(self.corrupted(format!("the page {page} was expected to continue a line")))
+            }
+            line.extend(&data[OVERFLOW_HEADER..OVERFLOW_HEADER + part_len]);
+            pages.push(page);
+            page = read_u32(data, 1);
+        }
+        Ok((line, pages))
+    }
+
+    /// The line stored in the cell, with the overflow pages storing it
+    fn read_cell(&self, cell: &[u8]) -> Result<(Vec<u8>, Vec<u32>), IrisError> {
+        match cell.first() {
+            Some(&INLINE_CELL) if cell.len() >= CELL_HEADER => Ok((cell[CELL_HEADER..].to_vec(), Vec::new())),
+            Some(&OVERFLOW_CELL) if cell.len() == OVERFLOW_CELL_SIZE => self.read_chain(read_u32(cell, CELL_HEADER + 4), read_u32(cell, CELL_HEADER) as usize),
+            _ => Err(self.corrupted(String::from("a cell has an unknown kind")))
+        }
+    }
+
+    fn get_cell(&self, row: RowId) -> Result<Vec<u8>, IrisError> {
+        let mut pool = self.pool.lock();
+        let data = pool.read(&self.file, row.page)?;
+        let slot_number = row.slot as usize;
+        let (offset, len) = if data[0] == DATA_PAGE && slot_number < slot_count(data) && DATA_HEADER + (slot_number + 1) * SLOT_SIZE <= PAGE_SIZE {
+            slot(data, slot_number)
+        } else {
+            (0, 0)
+        };
+        if len == 0 || offset + len > PAGE_SIZE {
+            drop(pool);
+            return Err(self.corrupted(format!("the line {} of the page {} is missing", row.slot, row.page)))
+        }
+        Ok(data[offset..offset + len].to_vec())
+    }
+
+    /// Read the encoded line
+    pub fn read(&self, row: RowId) -> Result<Vec<u8>, IrisError> {
+        Ok(self.read_cell(&self.get_cell(row)?)?.0)
+    }
+
+    /// Store the encoded line after the other ones and return its place
+    pub fn insert(&mut self, line: &[u8]) -> Result<RowId, IrisError> {
+        let cell = self.cell(self.next_sequence, line);
+        self.next_sequence += 1;
+        let page = self.page_for(cell.len());
+        self.put(page, None, &cell)
+    }
+
+    /// Remove the cell of the line and free its overflow pages, return the sequence number of the line
+    fn remove(&mut self, row: RowId) -> Result<u64, IrisError> {
+        let cell = self.get_cell(row)?;
+        let (_, overflow_pages) = self.read_cell(&cell)?;
+        self.release_chain(overflow_pages);
+        let mut pool = self.pool.lock();
+        let data = pool.write(&self.file, row.page)?;
+        remove_cell(data, row.slot as usize);
+        let free = free_space(data);
+        drop(pool);
+        self.set_free(row.page, free);
+        Ok(read_u64(&cell, 1))
+    }
+
+    /// Remove the line, the pages left without lines are freed
+    pub fn delete(&mut self, row: RowId) -> ConsumeResult {
+        self.remove(row)?;
+        self.release_if_empty(row.page)?;
+        self.trim();
+        Ok(())
+    }
+
+    /// Replace the line by its new version, kept in the same place if it still fits in its page
+    pub fn update(&mut self, row: RowId, line: &[u8]) -> Result<RowId, IrisError> {
+        let sequence = self.remove(row)?;
+        let cell = self.cell(sequence, line);
+        if self.free[row.page as usize] >= cell.len() + SLOT_SIZE {
+            return self.put(row.page, Some(row.slot as usize), &cell)
+        }
+        let page = self.page_for(cell.len());
+        let new_row = self.put(page, None, &cell)?;
+        self.release_if_empty(row.page)?;
+        self.trim();
+        Ok(new_row)
+    }
+
+    fn put(&mut self, page: u32, slot: Option<usize>, cell: &[u8]) -> Result<RowId, IrisError> {
+        let mut pool = self.pool.lock();
+        let data = pool.write(&self.file, page)?;
+        let slot = put_cell(data, slot, cell);
+        let free = free_space(data);
+        drop(pool);
+        self.set_free(page, free);
+        Ok(RowId { page, slot: slot as u16 })
+    }
+
+    fn page_kind(&self, page: u32) -> Result<u8, IrisError> {
+        Ok(self.pool.lock().read(&self.file, page)?[0])
+    }
+
+    /// The first page before the given one with room for the cell, a free page becomes a data page
+    fn page_before(&mut self, before: u32, len: usize) -> Option<u32> {
+        let page = (1..before).find(|page| self.free[*page as usize] >= len + SLOT_SIZE || self.free_pages.contains(page))?;
+        if self.free_pages.remove(&page) {
+            init_data_page(self.pool.lock().create(&self.file, page));
+            self.set_free(page, PAGE_SIZE - DATA_HEADER);
+        }
+        Some(page)
+    }
+
+    /// Do a step of the compaction of the data file, which frees its last pages without reading more than a line at a time.
+    /// The lines of the last data page are moved to the first pages with room for them until none of them can move,
+    /// then the overflow pages are moved to the first free pages, a data page at each step, and the free pages at the end are removed.
+    /// The moved lines are given with their old and their new place. The order of the lines is kept by their sequence numbers.
+    pub fn vacuum_step(&mut self, step: VacuumStep, moved: &mut dyn FnMut(RowId, RowId)) -> Result<VacuumStep, IrisError> {
+        self.trim();
+        match step {
+            VacuumStep::Moving => {
+                let mut source = None;
+                for page in (1..self.free.len() as u32).rev() {
+                    if self.page_kind(page)? == DATA_PAGE {
+                        source = Some(page);
+                        break
+                    }
+                }
+                let Some(source) = source else {
+                    return Ok(VacuumStep::Over)
+                };
+                let mut moved_any = false;
+                let count = slot_count(self.pool.lock().read(&self.file, source)?);
+                for slot_number in 0..count {
+                    let row = RowId { page: source, slot: slot_number as u16 };
+                    if slot(self.pool.lock().read(&self.file, source)?, slot_number).1 == 0 {
+                        continue
+                    }
+                    let cell = self.get_cell(row)?;
+                    let Some(target) = self.page_before(source, cell.len()) else {
+                        continue
+                    };
+                    let new_row = self.put(target, None, &cell)?;
+                    let mut pool = self.pool.lock();
+                    let data = pool.write(&self.file, source)?;
+                    remove_cell(data, slot_number);
+                    let free = free_space(data);
+                    drop(pool);
+                    self.set_free(source, free);
+                    moved(row, new_row);
+                    moved_any = true;
+                }
+                self.release_if_empty(source)?;
+                self.trim();
+                Ok(if moved_any { VacuumStep::Moving } else { VacuumStep::Chains(1) })
+            }
+            VacuumStep::Chains(first) => {
+                let mut page = first;
+                while (page as usize) < self.free.len() && self.page_kind(page)? != DATA_PAGE {
+                    page += 1;
+                }
+                if page as usize >= self.free.len() {
+                    return Ok(VacuumStep::Over)
+                }
+                let count = slot_count(self.pool.lock().read(&self.file, page)?);
+                for slot_number in 0..count {
+                    let row = RowId { page, slot: slot_number as u16 };
+                    let (offset, len) = slot(self.pool.lock().read(&self.file, page)?, slot_number);
+                    if len != OVERFLOW_CELL_SIZE || self.pool.lock().read(&self.file, page)?[offset] != OVERFLOW_CELL {
+                        continue
+                    }
+                    let cell = self.get_cell(row)?;
+                    let (line, chain) = self.read_cell(&cell)?;
+                    if !self.free_pages.first().is_some_and(|first_free| chain.iter().any(|page| page > first_free)) {
+                        continue
+                    }
+                    self.release_chain(chain);
+                    let cell = self.cell(read_u64(&cell, 1), &line);
+                    let mut pool = self.pool.lock();
+                    let data = pool.write(&self.file, page)?;
+                    remove_cell(data, slot_number);
+                    put_cell(data, Some(slot_number), &cell);
+                }
+                self.trim();
+                Ok(VacuumStep::Chains(page + 1))
+            }
+            VacuumStep::Over => Ok(VacuumStep::Over)
+        }
+    }
+
+    /// The pages modified since the last call, to write them with the size of the file
+    pub fn take_changes(&mut self) -> Option<WalEntry> {
+        let pages = self.pool.lock().take_dirty(&self.file);
+        if pages.is_empty() && self.written_pages == self.free.len() {
+            return None
+        }
+        self.written_pages = self.free.len();
+        Some(WalEntry::Pages { file: self.file.clone(), count: self.free.len() as u64, pages })
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.written_pages != self.free.len() || self.pool.lock().has_dirty(&self.file)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_dir::TestDir;
+    use super::super::wal::Wal;
+    use std::collections::HashMap;
+
+    const FILE: &str = "t";
+
+    fn create(dir: &TestDir) -> (HeapFile, Wal) {
+        (HeapFile::create(FILE, SharedPool::new(dir.path(), 4 * PAGE_SIZE)), Wal::open(dir.path()).unwrap())
+    }
+
+    fn write(heap: &mut HeapFile, wal: &mut Wal) {
+        wal.commit(heap.take_changes().into_iter().collect()).unwrap();
+    }
+
+    fn reopen(dir: &TestDir) -> (HeapFile, Vec<RowId>) {
+        let size = std::fs::metadata(dir.path().join(FILE)).unwrap().len();
+        HeapFile::open(FILE, SharedPool::new(dir.path(), 4 * PAGE_SIZE), size).unwrap()
+    }
+
+    fn line(i: usize, len: usize) -> Vec<u8> {
+        format!("{i:>len$}").into_bytes()
+    }
+
+    fn read_all(heap: &HeapFile, rows: &[RowId]) -> Vec<Vec<u8>> {
+        rows.iter().map(|row| heap.read(*row).unwrap()).collect()
+    }
+
+    #[test]
+    fn cells_are_stored_in_slots() {
+        let mut data = vec!(0; PAGE_SIZE);
+        init_data_page(&mut data);
+        assert_eq!(put_cell(&mut data, None, b"aaaa"), 0);
+        assert_eq!(put_cell(&mut data, None, b"bbbbbbbb"), 1);
+        assert_eq!(put_cell(&mut data, None, b"cc"), 2);
+        remove_cell(&mut data, 1);
+        assert_eq!(slot_count(&data), 3);
+        assert_eq!(free_space(&data), PAGE_SIZE - DATA_HEADER - 3 * SLOT_SIZE - 6);
+        assert_eq!(put_cell(&mut data, None, b"dddddd"), 1);
+        remove_cell(&mut data, 2);
+        assert_eq!(slot_count(&data), 2);
+        remove_cell(&mut data, 1);
+        assert_eq!(slot_count(&data), 1);
+        let (offset, len) = slot(&data, 0);
+        assert_eq!(&data[offset..offset + len], b"aaaa");
+    }
+
+    #[test]
+    fn removed_cells_are_compacted() {
+        let mut data = vec!(0; PAGE_SIZE);
+        init_data_page(&mut data);
+        let mut cells = Vec::new();
+        while free_space(&data) >= 100 + SLOT_SIZE {
+            let cell = vec!(cells.len() as u8; 100);
+            put_cell(&mut data, None, &cell);
+            cells.push(Some(cell));
+        }
+        for i in (0..cells.len()).step_by(2) {
+            remove_cell(&mut data, i);
+            cells[i] = None;
+        }
+        let big = vec!(255; 150);
+        let slot_number = put_cell(&mut data, None, &big);
+        assert_eq!(slot_number, 0);
+        cells[0] = Some(big);
+        for (i, cell) in cells.iter().enumerate() {
+            let (offset, len) = slot(&data, i);
+            assert_eq!(cell.as_deref().unwrap_or_default(), &data[offset..offset + len]);
+        }
+    }
+
+    #[test]
+    fn long_lines_are_stored_in_overflow_chains() {
+        let dir = TestDir::new("heap-overflow");
+        let (mut heap, mut wal) = create(&dir);
+        let short = heap.insert(b"short").unwrap();
+        let long = (0..3 * PAGE_SIZE + 10).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let row = heap.insert(&long).unwrap();
+        assert_eq!(heap.read(row).unwrap(), long);
+        assert_eq!(heap.free.len(), 6);
+        write(&mut heap, &mut wal);
+        let (mut heap, rows) = reopen(&dir);
+        assert_eq!(read_all(&heap, &rows), vec!(b"short".to_vec(), long));
+        heap.delete(rows[1]).unwrap();
+        assert_eq!(heap.free.len(), 2);
+        assert_eq!(heap.read(short).unwrap(), b"short");
+    }
+
+    #[test]
+    fn cells_near_the_longest_inline_cell_fit_in_their_page() {
+        let dir = TestDir::new("heap-roomy");
+        let (mut heap, mut wal) = create(&dir);
+        let lens = [MAX_CELL - 17, MAX_CELL - 17, MAX_CELL - 16, MAX_CELL - 1, MAX_CELL - 1, MAX_CELL - 2];
+        let lines = lens.iter().enumerate().map(|(i, len)| line(i, *len)).collect::<Vec<_>>();
+        let rows = lines.iter().map(|line| heap.insert(line).unwrap()).collect::<Vec<_>>();
+        assert_eq!(read_all(&heap, &rows), lines);
+        write(&mut heap, &mut wal);
+        let (heap, reloaded) = reopen(&dir);
+        assert_eq!(read_all(&heap, &reloaded), lines);
+    }
+
+    #[test]
+    fn lines_are_reloaded_in_the_order_of_their_insertion() {
+        let dir = TestDir::new("heap-order");
+        let (mut heap, mut wal) = create(&dir);
+        let mut rows = (0..300).map(|i| heap.insert(&line(i, 40)).unwrap()).collect::<Vec<_>>();
+        let mut expected = (0..300).map(|i| line(i, 40)).collect::<Vec<_>>();
+        for i in (0..300).step_by(3).rev() {
+            heap.delete(rows.remove(i)).unwrap();
+            expected.remove(i);
+        }
+        for i in 300..400 {
+            rows.push(heap.insert(&line(i, 40)).unwrap());
+            expected.push(line(i, 40));
+        }
+        assert!(rows[200..].iter().any(|row| row.page < rows[0].page + 2), "the new lines didn't reuse the room of the deleted ones");
+        rows[0] = heap.update(rows[0], &line(1, 1500)).unwrap();
+        expected[0] = line(1, 1500);
+        write(&mut heap, &mut wal);
+        let (heap, reloaded) = reopen(&dir);
+        assert_eq!(read_all(&heap, &reloaded), expected);
+    }
+
+    #[test]
+    fn emptied_pages_are_recycled() {
+        let dir = TestDir::new("heap-recycle");
+        let (mut heap, mut wal) = create(&dir);
+        let rows = (0..200).map(|i| heap.insert(&line(i, 100)).unwrap()).collect::<Vec<_>>();
+        let page_count = heap.free.len();
+        assert!(page_count > 4);
+        for row in rows.iter().filter(|row| row.page == 1) {
+            heap.delete(*row).unwrap();
+        }
+        assert_eq!(heap.free_pages.first(), Some(&1));
+        let long = heap.insert(&line(0, 2 * PAGE_SIZE)).unwrap();
+        assert!(!heap.free_pages.contains(&1), "the free page wasn't reused");
+        write(&mut heap, &mut wal);
+        assert_eq!(heap.free.len(), page_count + 2);
+        for row in rows.iter().filter(|row| row.page != 1).chain([&long]) {
+            heap.delete(*row).unwrap();
+        }
+        assert_eq!(heap.free.len(), 1);
+        write(&mut heap, &mut wal);
+        assert_eq!(std::fs::metadata(dir.path().join(FILE)).unwrap().len(), PAGE_SIZE as u64);
+        let (_, reloaded) = reopen(&dir);
+        assert!(reloaded.is_empty());
+    }
+
+    #[test]
+    fn vacuum_frees_the_last_pages() {
+        let dir = TestDir::new("heap-vacuum");
+        let (mut heap, mut wal) = create(&dir);
+        let mut rows = Vec::new();
+        let mut expected = Vec::new();
+        for i in 0..400 {
+            let value = if i == 390 { line(i, 3 * PAGE_SIZE) } else { line(i, 60) };
+            rows.push(heap.insert(&value).unwrap());
+            expected.push(value);
+        }
+        for i in (0..400).rev().filter(|i| i % 4 != 0 && *i != 390) {
+            heap.delete(rows.remove(i)).unwrap();
+            expected.remove(i);
+        }
+        write(&mut heap, &mut wal);
+        let page_count = heap.free.len();
+        let mut positions = rows.iter().enumerate().map(|(i, row)| (*row, i)).collect::<HashMap<_, _>>();
+        let mut step = VacuumStep::Moving;
+        while step != VacuumStep::Over {
+            step = heap.vacuum_step(step, &mut |old, new| {
+                let i = positions.remove(&old).unwrap();
+                rows[i] = new;
+                positions.insert(new, i);
+            }).unwrap();
+            write(&mut heap, &mut wal);
+        }
+        // The header, two data pages for the short lines and four overflow pages for the long one
+        assert_eq!(heap.free.len(), 7, "{} pages left out of {page_count}", heap.free.len());
+        assert!(heap.free_pages.is_empty());
+        assert_eq!(read_all(&heap, &rows), expected);
+        let (heap, reloaded) = reopen(&dir);
+        assert_eq!(read_all(&heap, &reloaded), expected);
+    }
+
+}
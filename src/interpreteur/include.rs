@@ -1,26 +1,36 @@
-pub use crate::tokenizer::include::{Token, TokenType, Flag};
-pub use std::process::exit;
-pub use super::expression_evaluator::ExpressionEvaluator;
+pub use crate::tokenizer::include::{Token, TokenType, Flag, AGGREGATE_FUNCTIONS, TEMPORAL_FUNCTIONS, TEMPORAL_FIELDS, NEGATION};
+pub use super::temporal::Temporal;
+pub use crate::error::IrisError;
+pub use super::expression_evaluator::{ExpressionEvaluator, Operand};
 pub use super::string_builder::StringBuilder;
-pub use std::collections::HashMap;
-pub type ConsumeResult = Result<(), String>;
+pub use std::collections::{HashMap, HashSet};
+pub type ConsumeResult = Result<(), IrisError>;
 pub use super::stack::Stack;
+pub use super::query_result::QueryResult;
+pub use super::index::Index;
+pub use super::foreign_key::{ForeignKey, OnDelete};
+pub use super::aggregator::{Aggregate, Aggregator};
+use super::wal::{Wal, WalEntry, replace_file};
+use super::table_file::{self, Layout};
+use super::heap_file::{self, HeapFile, RowId, VacuumStep};
+use super::buffer_pool::SharedPool;
 use std::fs::{
     File,
-    OpenOptions,
-    remove_file
+    OpenOptions
 };
 use std::io::{
-    Write,
     Seek,
     Read
 };
 
 use std::path::{
     Path,
+    PathBuf
 };
 
 use std::fmt;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub use serde_json::{
     json,
@@ -29,16 +39,18 @@ pub use serde_json::{
     Number
 };
 
-use crate::get_iris_path;
-
 pub static ALL_INDICATOR: &str = "*";
 
 #[derive(Debug)]
 #[derive(PartialEq)]
 pub enum Type {
     Int,
+    Float,
     String,
-    Bool
+    Bool,
+    Date,
+    Time,
+    Timestamp
 }
 
 impl Copy for Type {}
@@ -50,6 +62,68 @@ impl Clone for Type {
     }
 }
 
+impl Type {
+
+    pub fn is_numeric(&self) -> bool {
+        *self == Type::Int || *self == Type::Float
+    }
+
+    pub fn is_temporal(&self) -> bool {
+        [Type::Date, Type::Time, Type::Timestamp].contains(self)
+    }
+
+    /// The value stored by a column of this temporal type for the operand, a temporal value or its ISO-8601 string.
+    /// A TIMESTAMP column accepts a date as its midnight.
+    pub fn temporal_of(&self, operand: &Operand) -> Option<Temporal> {
+        let temporal = match operand {
+            Operand::Temporal(temporal) => *temporal,
+            Operand::String(string) => Temporal::parse(string)?,
+            _ => return None
+        };
+        match (self, temporal) {
+            (Type::Date, Temporal::Date(_)) | (Type::Time, Temporal::Time(_)) | (Type::Timestamp, Temporal::Timestamp(_)) => Some(temporal),
+            (Type::Timestamp, Temporal::Date(_)) => Some(temporal.to_timestamp()),
+            _ => None
+        }
+    }
+
+    /// Indicate if a column of this type can contain the value, NULL fits in any column
+    pub fn accepts(&self, operand: &Operand) -> bool {
+        match operand {
+            Operand::Null => true,
+            _ if self.is_temporal() => self.temporal_of(operand).is_some(),
+            Operand::Temporal(_) => false,
+            Operand::String(_) => *self == Type::String,
+            Operand::Float(_) => *self == Type::Float,
+            Operand::Bool(_) => *self == Type::Bool,
+            Operand::Number(_) => self.is_numeric()
+        }
+    }
+
+    /// A value of this type, to check the types of an expression before it is computed on the lines
+    pub fn sample(&self) -> JsonValue {
+        match self {
+            Type::Int => JsonValue::from(1),
+            Type::Float => JsonValue::from(1.0),
+            Type::String => JsonValue::from(""),
+            Type::Bool => JsonValue::Bool(true),
+            Type::Date => JsonValue::from("1970-01-01"),
+            Type::Time => JsonValue::from("00:00:00"),
+            Type::Timestamp => JsonValue::from("1970-01-01 00:00:00")
+        }
+    }
+
+    /// Convert the value accepted by a column of this type to the value stored in it
+    pub fn cast(&self, operand: Operand) -> Operand {
+        match (self, operand) {
+            (Type::Float, Operand::Number(number)) => Operand::Float(number as f64),
+            (_, operand) if self.is_temporal() => self.temporal_of(&operand).map(Operand::Temporal).unwrap_or(operand),
+            (_, operand) => operand
+        }
+    }
+
+}
+
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", format!("{:?}", self).to_uppercase())
@@ -57,13 +131,16 @@ impl fmt::Display for Type {
 }
 
 
-pub fn from_string_to_type(s: String) -> Type {
+pub fn from_string_to_type(s: String) -> Result<Type, IrisError> {
     match &s as &str {
-        "INT" => Type::Int,
-        "STRING" => Type::String,
-        "BOOL" => Type::Bool,
-        _ => panic!("Type {s} doesn't exist")
-            
+        "INT" => Ok(Type::Int),
+        "FLOAT" | "REAL" => Ok(Type::Float),
+        "STRING" => Ok(Type::String),
+        "DATE" => Ok(Type::Date),
+        "TIME" => Ok(Type::Time),
+        "TIMESTAMP" => Ok(Type::Timestamp),
+        "BOOL" => Ok(Type::Bool),
+        _ => Err(IrisError::Syntax(format!("The type {s} doesn't exist.")))
     }
 }
 
@@ -72,7 +149,9 @@ pub fn from_string_to_type(s: String) -> Type {
 #[derive(Clone)]
 pub struct Value {
     number: i64,
-    string: String
+    float: f64,
+    string: String,
+    null: bool
 }
 
 #[allow(dead_code)]
@@ -81,34 +160,76 @@ impl Value {
     pub fn new_by_val(val: i64) -> Value {
         Value {
             number: val,
-            string: format!("{val}")
+            float: val as f64,
+            string: format!("{val}"),
+            null: false
         }
     }
 
-    pub fn new_by_string(s: &mut StringBuilder, hash: bool) -> Value {
-        let val = if hash { s.hash() } else { 0 };
+    pub fn new_by_bool(val: bool) -> Value {
         Value {
-            number: val,
-            string: s.extract()
+            number: val as i64,
+            float: val as i64 as f64,
+            string: String::from(if val { "TRUE" } else { "FALSE" }),
+            null: false
+        }
+    }
+
+    pub fn new_by_float(val: f64) -> Value {
+        Value {
+            number: val as i64,
+            float: val,
+            string: format!("{val:?}"),
+            null: false
+        }
+    }
+
+    /// The absence of value, the one of a column not given on insertion and without default value
+    pub fn new_null() -> Value {
+        Value {
+            number: 0,
+            float: 0.0,
+            string: String::from("NULL"),
+            null: true
         }
     }
 
-    pub fn from_json(data: &JsonValue) -> Value {
-        match data {
-            JsonValue::String(string) => Value::new_by_pure_string(string.clone(), true),
-            JsonValue::Number(number) => Value::new_by_val(number.as_i64().expect("Failed to convert the serde_json Number to i64")),
-            _ => panic!("Unexpected serde_json value for a column: {data:?}")
+    pub fn new_by_string(s: &mut StringBuilder) -> Value {
+        Value {
+            number: 0,
+            float: 0.0,
+            string: s.extract(),
+            null: false
         }
     }
 
-    pub fn new_by_pure_string(s: String, hash: bool) -> Value {
-        Value::new_by_string(&mut StringBuilder::from_string(s), hash)
+    /// The value of a column stored in json, a column never contains an array or an object
+    pub fn from_json(data: &JsonValue) -> Result<Value, IrisError> {
+        let unexpected = || IrisError::Corrupted(format!("The value {data} can't be the value of a column."));
+        Ok(match data {
+            JsonValue::String(string) => Value::new_by_pure_string(string.clone()),
+            JsonValue::Bool(boolean) => Value::new_by_bool(*boolean),
+            JsonValue::Number(number) => match number.as_i64() {
+                Some(val) => Value::new_by_val(val),
+                None => Value::new_by_float(number.as_f64().ok_or_else(unexpected)?)
+            },
+            JsonValue::Null => Value::new_null(),
+            _ => return Err(unexpected())
+        })
+    }
+
+    pub fn new_by_pure_string(s: String) -> Value {
+        Value::new_by_string(&mut StringBuilder::from_string(s))
     }
 
     pub fn val(&self) -> i64 {
         self.number
     }
 
+    pub fn float(&self) -> f64 {
+        self.float
+    }
+
     pub fn string(&self) -> &String {
         &self.string
     }
@@ -116,27 +237,54 @@ impl Value {
     pub fn string_mut(&mut self) -> &mut String {
         &mut self.string
     }
+
+    pub fn is_null(&self) -> bool {
+        self.null
+    }
+
+    /// Convert the value in the json format used by the table files, according to the type of its column
+    pub fn to_json(&self, t: Type) -> JsonValue {
+        if self.is_null() {
+            return JsonValue::Null
+        }
+        match t {
+            Type::String | Type::Date | Type::Time | Type::Timestamp => JsonValue::String(self.string().clone()),
+            Type::Float => JsonValue::from(self.float()),
+            Type::Bool => JsonValue::Bool(self.val() != 0),
+            _ => JsonValue::Number(Number::from(self.val()))
+        }
+    }
     
 }
 
-fn extract_string_from_json(json_value: &JsonValue) -> String {
-     match json_value {
-         JsonValue::String(string) => string.to_string(),
-         _ => panic!("Failed to catch a string for the column name")
-     }
+/// Compare two values of a same column, numbers by value, strings by content and FALSE before TRUE. NULL comes before any value.
+/// The temporal values are stored in ISO-8601, their order is the one of their strings.
+pub fn compare_json_values(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (JsonValue::Number(n1), JsonValue::Number(n2)) => match (n1.as_i64(), n2.as_i64()) {
+            (Some(i1), Some(i2)) => i1.cmp(&i2),
+            _ => n1.as_f64().partial_cmp(&n2.as_f64()).unwrap_or(Ordering::Equal)
+        },
+        (JsonValue::String(s1), JsonValue::String(s2)) => s1.cmp(s2),
+        (JsonValue::Bool(b1), JsonValue::Bool(b2)) => b1.cmp(b2),
+        (JsonValue::Null, JsonValue::Null) => Ordering::Equal,
+        (JsonValue::Null, _) => Ordering::Less,
+        (_, JsonValue::Null) => Ordering::Greater,
+        _ => Ordering::Equal
+    }
 }
 
-fn extract_vec_from_json(json_value: &JsonValue) -> Vec::<JsonValue> {
+fn extract_string_from_json(json_value: &JsonValue) -> Result<String, IrisError> {
      match json_value {
-         JsonValue::Array(arr) => arr.clone(),
-         _ => panic!("Failed to catch a vector for the column name")
+         JsonValue::String(string) => Ok(string.to_string()),
+         _ => Err(IrisError::Corrupted(format!("A string was expected in the tables data, found {json_value}")))
      }
 }
 
-fn extract_map_from_json(json_value: &mut JsonValue) -> &mut Map::<String, JsonValue> {
+fn extract_vec_from_json(json_value: &JsonValue) -> Result<Vec::<JsonValue>, IrisError> {
      match json_value {
-         JsonValue::Object(map) => map,
-         _ => panic!("Failed to catch a vector for the column name")
+         JsonValue::Array(arr) => Ok(arr.clone()),
+         _ => Err(IrisError::Corrupted(format!("An array was expected in the tables data, found {json_value}")))
      }
 }
 
@@ -145,6 +293,10 @@ pub struct Column {
     name: String,
     type_col: Type,
     default_value: Option<Value>,
+    not_null: bool,
+    unique: bool,
+    check: Option<ExpressionEvaluator>,
+    foreign_key: Option<ForeignKey>,
     flag: bool
 }
 
@@ -155,25 +307,50 @@ impl Column {
             name: String::new(),
             type_col: Type::Int,
             default_value: None,
+            not_null: false,
+            unique: false,
+            check: None,
+            foreign_key: None,
             flag: false
         }
     }
 
-    fn load(json_data: &JsonValue) -> Column {
+    fn load(json_data: &JsonValue) -> Result<Column, IrisError> {
         let mut column = Column::new_empty();
-        column.set_name(extract_string_from_json(&json_data["name"]));
-        column.set_type(from_string_to_type(extract_string_from_json(&json_data["type_col"])));
-        column.load_default_value(extract_string_from_json(&json_data["default_value"]));
-        column
+        column.set_name(extract_string_from_json(&json_data["name"])?);
+        column.set_type(from_string_to_type(extract_string_from_json(&json_data["type_col"])?)?);
+        column.load_default_value(extract_string_from_json(&json_data["default_value"])?)?;
+        column.not_null = json_data["not_null"].as_bool().unwrap_or(false);
+        column.unique = json_data["unique"].as_bool().unwrap_or(false);
+        if !json_data["check"].is_null() {
+            column.check = Some(ExpressionEvaluator::load(&json_data["check"])?);
+        }
+        if !json_data["references"].is_null() {
+            column.foreign_key = Some(ForeignKey::load(&json_data["references"])?);
+        }
+        Ok(column)
     }
 
-    fn load_default_value(&mut self, default_value: String) {
+    fn load_default_value(&mut self, default_value: String) -> ConsumeResult {
         if !default_value.is_empty() {
             match self.type_col {
-                Type::String => self.default_value = Some(Value::new_by_pure_string(default_value, true)),
-                _ => self.set_default_value(default_value.parse::<i64>().expect("Failed to parse the default value."))
+                Type::String | Type::Date | Type::Time | Type::Timestamp => self.default_value = Some(Value::new_by_pure_string(default_value)),
+                Type::Bool => match &default_value as &str {
+                    "TRUE" | "1" => self.set_default_value(Value::new_by_bool(true)),
+                    "FALSE" | "0" => self.set_default_value(Value::new_by_bool(false)),
+                    _ => return Err(IrisError::Corrupted(format!("The default value {default_value} of the column {} is not a boolean.", self.name())))
+                },
+                Type::Float => match default_value.parse::<f64>() {
+                    Ok(val) => self.set_default_value(Value::new_by_float(val)),
+                    Err(_) => return Err(IrisError::Corrupted(format!("The default value {default_value} of the column {} is not a float.", self.name())))
+                },
+                _ => match default_value.parse::<i64>() {
+                    Ok(val) => self.set_default_value(Value::new_by_val(val)),
+                    Err(_) => return Err(IrisError::Corrupted(format!("The default value {default_value} of the column {} is not a number.", self.name())))
+                }
             }
         }
+        Ok(())
     }
     
     pub fn set_type(&mut self, t: Type) {
@@ -184,18 +361,14 @@ impl Column {
         self.name = name
     }
 
-    pub fn set_default_value(&mut self, val: i64) {
-        self.default_value = Some(Value::new_by_val(val))
+    pub fn set_default_value(&mut self, val: Value) {
+        self.default_value = Some(val)
     }
 
     pub fn get_type(&self) -> Type {
         self.type_col
     }
     
-    pub fn set_value_by_string(&mut self, s: &mut StringBuilder) {
-        self.default_value = Some(Value::new_by_string(s, false));
-    }
-    
     pub fn name(&self) -> &String {
         &self.name
     }
@@ -216,31 +389,70 @@ impl Column {
         self.flag = false
     }
 
-    pub fn default_value(&self) -> &Value {
-        self.default_value.as_ref().expect(&format!("The column {} doesn't have a default value.", self.name()))
+    pub fn default_value(&self) -> Option<&Value> {
+        self.default_value.as_ref()
     }
-    
-    pub fn has_default_value(&self) -> bool {
-        self.default_value.is_some()
+
+    pub fn set_not_null(&mut self) {
+        self.not_null = true
+    }
+
+    pub fn set_unique(&mut self) {
+        self.unique = true
+    }
+
+    pub fn is_not_null(&self) -> bool {
+        self.not_null
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// The condition every line has to satisfy, the lines for which it gives NULL are accepted.
+    pub fn set_check(&mut self, mut check: ExpressionEvaluator) -> ConsumeResult {
+        check.flush_operators()?;
+        self.check = Some(check);
+        Ok(())
+    }
+
+    pub fn check(&self) -> Option<&ExpressionEvaluator> {
+        self.check.as_ref()
+    }
+
+    pub fn set_foreign_key(&mut self, foreign_key: ForeignKey) {
+        self.foreign_key = Some(foreign_key)
+    }
+
+    pub fn foreign_key(&self) -> Option<&ForeignKey> {
+        self.foreign_key.as_ref()
+    }
+
+    pub fn foreign_key_mut(&mut self) -> Option<&mut ForeignKey> {
+        self.foreign_key.as_mut()
     }
 
     fn get_datas(&self) -> JsonValue {
         json!({
             "name": self.name(),
             "type_col": self.type_col.to_string(),
-            "default_value": if self.default_value.is_some() { self.default_value.as_ref().unwrap().string() } else { "" }
+            "default_value": self.default_value.as_ref().map_or("", |value| value.string()),
+            "not_null": self.not_null,
+            "unique": self.unique,
+            "check": self.check.as_ref().map(|check| check.get_datas()),
+            "references": self.foreign_key.as_ref().map(ForeignKey::get_datas)
         })
     }
 
   
 }
 
-fn open_file(file_path: &str) -> File {
+fn open_file(file_path: &str) -> Result<File, IrisError> {
     OpenOptions::new()
         .append(true)
         .read(true)
         .open(file_path)
-        .expect(&format!("failed to open the file {}", file_path))
+        .map_err(|e| IrisError::io(&format!("Failed to open the file {file_path}"), e))
 }
 
 #[derive(Debug)]
@@ -248,8 +460,16 @@ pub struct Table {
     name: String,
     columns: HashMap<String, Column>,
     p_key: String,
-    table_file: Option<File>,
-    lines: Vec<JsonValue>
+    auto_increment: Option<i64>,
+    /// The place of each line in the data file, the lines are read through the buffer pool when they are needed
+    rows: Vec<RowId>,
+    heap: Option<HeapFile>,
+    layout: Layout,
+    p_key_index: HashMap<String, usize>,
+    indexes: Vec<Index>,
+    /// The indexes of the UNIQUE columns and of the foreign keys, built when the lines are loaded and not stored in the tables data
+    key_indexes: Vec<Index>,
+    dir: PathBuf
 }
 
 impl Table {
@@ -261,66 +481,319 @@ impl Table {
             name: String::new(),
             columns: HashMap::new(),
             p_key: String::new(),
-            table_file: None,
-            lines: Vec::new()
+            auto_increment: None,
+            rows: Vec::new(),
+            heap: None,
+            layout: Layout::new(),
+            p_key_index: HashMap::new(),
+            indexes: Vec::new(),
+            key_indexes: Vec::new(),
+            dir: PathBuf::new()
         }
     }
 
-    fn load(json_data: &JsonValue) -> Table {
+    fn load(json_data: &JsonValue, dir: &Path, pool: &SharedPool) -> Result<Table, IrisError> {
         let mut table = Table::new();
-        table.set_name(extract_string_from_json(&json_data["name"]));
-        table.set_pkey(extract_string_from_json(&json_data["p_key"]));
-        match &json_data["columns"] {
-            JsonValue::Array(columns) =>  {
-                for c in columns {
-                    table.add_column(Column::load(c));
+        table.set_dir(dir);
+        table.set_name(extract_string_from_json(&json_data["name"])?);
+        table.set_pkey(extract_string_from_json(&json_data["p_key"])?);
+        table.auto_increment = json_data["auto_increment"].as_i64();
+        for c in extract_vec_from_json(&json_data["columns"])?.iter() {
+            table.add_column(Column::load(c)?);
+        }
+        if let JsonValue::Array(indexes) = &json_data["indexes"] {
+            for i in indexes.iter() {
+                let index = Index::load(i)?;
+                if !table.column_exists(index.column()) {
+                    return Err(IrisError::Corrupted(format!("The index {} is on the column {} which doesn't exist in the table {}.", index.name(), index.column(), table.name())))
                 }
-            },
-            _ => panic!("Failed to catch the columns as an array")
+                table.indexes.push(index);
+            }
         }
-        table
+        table.load_lines(pool)?;
+        Ok(table)
     }
 
-    pub fn save(&mut self) {
+    /// Open the data file of the table and index its lines, a data file written in json by an older version is stored in pages.
+    fn load_lines(&mut self, pool: &SharedPool) -> ConsumeResult {
         let path = self.get_table_file_path();
-        File::create(Path::new(&path)).expect(&format!("Failed to create the file of the table {}", self.name()));
-        self.table_file = Some(open_file(&path));
-        self.table_file().write_all("[]".as_bytes()).expect("Failed to write");
-        self.lines = Vec::new();
+        if !Path::new(&path).is_file() {
+            return Err(IrisError::Corrupted(format!("The data file of the table {} is missing, expected it at {path}.", self.name())))
+        }
+        let name = self.name.clone();
+        let read_error = |e| IrisError::io(&format!("Failed to read the data file of the table {name}"), e);
+        let mut file = open_file(&path)?;
+        let size = file.metadata().map_err(read_error)?.len();
+        let mut start = [0; 8];
+        self.set_layout();
+        self.set_key_indexes();
+        if file.read_exact(&mut start).is_ok() && heap_file::is_heap_file(&start) {
+            let (heap, rows) = HeapFile::open(&self.name, pool.clone(), size)?;
+            self.heap = Some(heap);
+            self.rows = rows;
+        } else {
+            let mut content = String::new();
+            file.seek(std::io::SeekFrom::Start(0))
+                .and_then(|_| file.read_to_string(&mut content))
+                .map_err(read_error)?;
+            self.heap = Some(HeapFile::create(&self.name, pool.clone()));
+            for line in table_file::read_json_lines(&content, &self.p_key, &self.name)? {
+                let bytes = table_file::encode_line(&self.layout, line.as_object().unwrap_or(&Map::new()), &self.name)?;
+                let row = self.heap_mut().insert(&bytes)?;
+                self.rows.push(row);
+            }
+        }
+        for i in 0..self.rows.len() {
+            let line = self.get_line(i)?;
+            self.p_key_index.insert(self.p_key_of(&line), i);
+            for index in self.all_indexes_mut() {
+                index.add_line(&line, i);
+            }
+            self.bump_auto_increment(&line);
+        }
+        Ok(())
+    }
+
+    /// The values of the lines are encoded in the alphabetical order of the columns
+    fn set_layout(&mut self) {
+        self.layout = self.columns.values().map(|c| (c.name().clone(), c.get_type())).collect();
+        self.layout.sort_by(|(c1, _), (c2, _)| c1.cmp(c2));
+    }
+
+    /// Index the UNIQUE columns and the foreign keys, other than the primary key, to check their constraints without reading every line
+    fn set_key_indexes(&mut self) {
+        self.key_indexes = self.columns.values()
+            .filter(|c| (c.is_unique() || c.foreign_key().is_some()) && *c.name() != self.p_key)
+            .map(|c| Index::new(String::new(), c.name().clone()))
+            .collect();
+    }
+
+    fn all_indexes_mut(&mut self) -> impl Iterator<Item = &mut Index> {
+        self.indexes.iter_mut().chain(self.key_indexes.iter_mut())
+    }
+
+    /// An index on the column, the primary key has its own index
+    fn index_on(&self, column: &String) -> Option<&Index> {
+        self.key_indexes.iter().chain(self.indexes.iter()).find(|index| index.column() == column)
+    }
+
+    fn heap(&self) -> &HeapFile {
+        self.heap.as_ref().expect("The table is not stored in a database")
+    }
+
+    fn heap_mut(&mut self) -> &mut HeapFile {
+        self.heap.as_mut().expect("The table is not stored in a database")
+    }
+
+    /// Read the line from the data file
+    pub fn get_line(&self, line_number: usize) -> Result<Map<String, JsonValue>, IrisError> {
+        let bytes = self.heap().read(self.rows[line_number])?;
+        table_file::decode_line(&self.layout, &bytes, &self.name)
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The key of a line in the primary key index
+    fn p_key_of(&self, line: &Map<String, JsonValue>) -> String {
+        line.get(&self.p_key).map(|key| key.to_string()).unwrap_or_default()
+    }
+
+    /// Keep the AUTOINCREMENT counter above the primary key of the given line
+    fn bump_auto_increment(&mut self, line: &Map<String, JsonValue>) {
+        let key = line.get(&self.p_key).and_then(JsonValue::as_i64);
+        if let (Some(counter), Some(key)) = (self.auto_increment.as_mut(), key) {
+            *counter = (*counter).max(key.saturating_add(1));
+        }
     }
 
-    pub fn drop(&mut self) {
-        remove_file(&self.get_table_file_path()).expect(&format!("Failed to remove the file of the table {}", self.name))
+    fn duplicated_key(&self, key: &str) -> IrisError {
+        IrisError::ConstraintViolation(format!("The primary key {} = {key} already exists in the table {}.", self.p_key, self.name))
     }
 
-    pub fn insert(&mut self, asked_cols: &Vec<String>, values: &Vec<Value>) {
+    /// Refuse the new lines breaking a NOT NULL, UNIQUE or CHECK constraint, the replaced lines are ignored by the UNIQUE constraints.
+    fn check_constraints(&mut self, new_lines: &[&Map<String, JsonValue>], replaced: &HashSet<usize>) -> ConsumeResult {
+        for column in self.columns.values_mut() {
+            for line in new_lines.iter() {
+                let value = line.get(column.name()).unwrap_or(&JsonValue::Null);
+                if column.not_null && value.is_null() {
+                    return Err(IrisError::ConstraintViolation(format!("The column {} of the table {} is NOT NULL, it can't receive NULL.", column.name, self.name)))
+                }
+                if let Some(check) = column.check.as_mut() {
+                    match check.evaluate(line, false)? {
+                        Operand::Bool(true) | Operand::Null => (),
+                        Operand::Bool(false) => return Err(IrisError::ConstraintViolation(format!("The value {} of the column {} doesn't satisfy its CHECK constraint in the table {}.",
+                            Value::from_json(value)?.string(), column.name, self.name))),
+                        operand => return Err(IrisError::TypeMismatch(format!("The CHECK constraint of the column {} of the table {} gives {operand} where a boolean is expected.", column.name, self.name)))
+                    }
+                }
+            }
+        }
+        for column in self.columns.values().filter(|column| column.unique) {
+            for (n, line) in new_lines.iter().enumerate() {
+                let value = line.get(column.name()).unwrap_or(&JsonValue::Null);
+                if !value.is_null() && (new_lines[..n].iter().any(|other| other.get(column.name()) == Some(value)) || self.find_value(column.name(), value, replaced)?.is_some()) {
+                    return Err(IrisError::ConstraintViolation(format!("The column {} of the table {} is UNIQUE, the value {} already exists.", column.name, self.name, Value::from_json(value)?.string())))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The first line, other than the ignored ones, having the given value in the column
+    fn find_value(&self, column: &String, value: &JsonValue, ignored: &HashSet<usize>) -> Result<Option<usize>, IrisError> {
+        Ok(self.lines_with_value(column, value)?.into_iter().find(|i| !ignored.contains(i)))
+    }
+
+    /// The lines having the given value in the column in ascending order, found with an index when the column has one
+    pub fn lines_with_value(&self, column: &String, value: &JsonValue) -> Result<Vec<usize>, IrisError> {
+        if *column == self.p_key {
+            return Ok(self.p_key_index.get(&value.to_string()).copied().into_iter().collect())
+        }
+        if let Some(index) = self.index_on(column) {
+            return Ok(index.lines_with(value))
+        }
+        let mut lines = Vec::new();
+        for i in 0..self.rows.len() {
+            if self.get_line(i)?.get(column) == Some(value) {
+                lines.push(i);
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Refuse a line without primary key
+    fn check_p_key(&self, line: &Map<String, JsonValue>) -> ConsumeResult {
+        if line.get(&self.p_key).is_none_or(|key| key.is_null()) {
+            return Err(IrisError::ConstraintViolation(format!("The primary key {} of the table {} can't be NULL.", self.p_key, self.name)))
+        }
+        Ok(())
+    }
+
+    /// Give an empty data file to a new table, it is created with the other changes of the request
+    pub fn save(&mut self, pool: &SharedPool) {
+        self.set_layout();
+        self.set_key_indexes();
+        self.rows = Vec::new();
+        self.heap = Some(HeapFile::create(&self.name, pool.clone()));
+    }
+
+    fn encode(&self, line: &Map<String, JsonValue>) -> Result<Vec<u8>, IrisError> {
+        table_file::encode_line(&self.layout, line, &self.name)
+    }
+
+    /// Build the line made of the given values of the columns
+    pub fn new_line(&self, asked_cols: &[String], values: &[Value]) -> Result<Map<String, JsonValue>, IrisError> {
         let mut map = Map::<String, JsonValue>::new();
         for (col, val) in asked_cols.iter().zip(values.iter()) {
-            map.insert(col.clone(), if self.get_column(col).get_type() == Type::String { JsonValue::String(val.string().clone()) } else { JsonValue::Number(Number::from(val.val())) });
+            map.insert(col.clone(), val.to_json(self.get_column(col)?.get_type()));
         }
-        self.lines.push(JsonValue::Object(map));
-        self.actualise_table_file();
+        Ok(map)
     }
 
-    pub fn actualise_table_file(&mut self) {
-        let lines = self.lines.clone();
-        self.table_file().set_len(0).expect("Failed to reset the data file len");
-        self.table_file()
-            .write_all(JsonValue::Array(lines).to_string().trim().as_bytes())
-            .expect("write failed");
+    pub fn insert(&mut self, map: Map<String, JsonValue>) -> ConsumeResult {
+        self.check_p_key(&map)?;
+        self.check_constraints(&[&map], &HashSet::new())?;
+        let key = self.p_key_of(&map);
+        if self.p_key_index.contains_key(&key) {
+            return Err(self.duplicated_key(&key))
+        }
+        let bytes = self.encode(&map)?;
+        let row = self.heap_mut().insert(&bytes)?;
+        self.p_key_index.insert(key, self.rows.len());
+        let line_number = self.rows.len();
+        for index in self.all_indexes_mut() {
+            index.add_line(&map, line_number);
+        }
+        self.rows.push(row);
+        self.bump_auto_increment(&map);
+        Ok(())
+    }
+
+    /// Replace the given lines by their new version, the whole update is refused if it duplicates a primary key.
+    pub fn update_lines(&mut self, updates: Vec<(usize, Map<String, JsonValue>)>) -> ConsumeResult {
+        let old_lines = updates.iter().map(|(i, _)| self.get_line(*i)).collect::<Result<Vec<_>, _>>()?;
+        let old_keys = old_lines.iter().map(|line| self.p_key_of(line)).collect::<HashSet<_>>();
+        let mut new_keys = HashSet::new();
+        for (_, line) in updates.iter() {
+            self.check_p_key(line)?;
+            let key = self.p_key_of(line);
+            if self.p_key_index.contains_key(&key) && !old_keys.contains(&key) || !new_keys.insert(key.clone()) {
+                return Err(self.duplicated_key(&key))
+            }
+        }
+        let new_lines = updates.iter().map(|(_, line)| line).collect::<Vec<_>>();
+        self.check_constraints(&new_lines, &updates.iter().map(|(i, _)| *i).collect())?;
+        let encoded = updates.iter().map(|(_, line)| self.encode(line)).collect::<Result<Vec<_>, _>>()?;
+        for key in old_keys.iter() {
+            self.p_key_index.remove(key);
+        }
+        for (((i, line), old_line), bytes) in updates.into_iter().zip(old_lines).zip(encoded) {
+            let row = self.rows[i];
+            self.rows[i] = self.heap_mut().update(row, &bytes)?;
+            self.p_key_index.insert(self.p_key_of(&line), i);
+            for index in self.all_indexes_mut() {
+                index.remove_line(&old_line, i);
+                index.add_line(&line, i);
+            }
+            self.bump_auto_increment(&line);
+        }
+        Ok(())
+    }
+
+    /// Move the lines to free the last pages of the data file, the changes of each step are given to be written at once
+    pub fn vacuum(&mut self, write: &mut dyn FnMut(Option<WalEntry>) -> ConsumeResult) -> ConsumeResult {
+        let mut positions = self.rows.iter().enumerate().map(|(i, row)| (*row, i)).collect::<HashMap<_, _>>();
+        let mut step = VacuumStep::Moving;
+        while step != VacuumStep::Over {
+            let rows = &mut self.rows;
+            step = self.heap.as_mut().expect("The table is not stored in a database").vacuum_step(step, &mut |old, new| {
+                if let Some(i) = positions.remove(&old) {
+                    rows[i] = new;
+                    positions.insert(new, i);
+                }
+            })?;
+            write(self.take_changes())?;
+        }
+        Ok(())
+    }
+
+    /// The pages of the data file modified since the last call
+    fn take_changes(&mut self) -> Option<WalEntry> {
+        self.heap.as_mut().and_then(HeapFile::take_changes)
+    }
+
+    fn has_changes(&self) -> bool {
+        self.heap.as_ref().is_some_and(HeapFile::has_changes)
     }
     
     pub fn get_table_file_path(&self) -> String {
-        get_iris_path() + self.name()
+        self.dir.join(self.name()).to_string_lossy().into_owned()
     }
 
-    pub fn table_file(&mut self) -> &mut File {
-        self.table_file.as_mut().expect(&format!("Failed to unwrap the file of the table {}", self.name))
+    /// Set the directory of the database owning the table, where its data file is stored
+    pub fn set_dir(&mut self, dir: &Path) {
+        self.dir = dir.to_path_buf()
     }
-    
+
     pub fn set_pkey(&mut self, p_key: String) {
         self.p_key = p_key;
     }
+
+    /// The primary key is filled with the AUTOINCREMENT counter when an insertion doesn't give it
+    pub fn set_auto_increment(&mut self) {
+        self.auto_increment = Some(1);
+    }
+
+    pub fn is_auto_increment(&self) -> bool {
+        self.auto_increment.is_some()
+    }
+
+    /// The primary key of the next line inserted without it, None if the table is not AUTOINCREMENT
+    pub fn next_auto_increment(&self) -> Option<i64> {
+        self.auto_increment
+    }
     
     /// Set the name of the table
     pub fn set_name(&mut self, name: String) {
@@ -341,20 +814,42 @@ impl Table {
         self.columns.contains_key(name)
     }
 
-    pub fn drop_lines(&mut self, stack_line_number: &mut Stack<usize>) {
-        while !stack_line_number.is_empty() {
-            self.lines.remove(stack_line_number.pop().unwrap());
+    /// Delete the lines, the following lines are renumbered once in the rows and in the indexes
+    pub fn drop_lines(&mut self, stack_line_number: &mut Stack<usize>) -> ConsumeResult {
+        let mut deleted = Vec::new();
+        while let Some(line_number) = stack_line_number.pop() {
+            let row = self.rows[line_number];
+            self.heap_mut().delete(row)?;
+            deleted.push(line_number);
+        }
+        deleted.sort_unstable();
+        let mut line_number = 0;
+        self.rows.retain(|_| {
+            line_number += 1;
+            deleted.binary_search(&(line_number - 1)).is_err()
+        });
+        self.p_key_index.retain(|_, i| deleted.binary_search(i).is_err());
+        for i in self.p_key_index.values_mut() {
+            *i -= deleted.partition_point(|d| d < i);
         }
-        self.actualise_table_file();
+        for index in self.all_indexes_mut() {
+            index.remove_line_numbers(&deleted);
+        }
+        Ok(())
+    }
+
+    fn unknown_column(&self, name: &str) -> IrisError {
+        IrisError::UnknownColumn { table: self.name.clone(), column: name.to_string() }
     }
     
-    pub fn get_column(&self, name: &String) -> &Column {
-        self.columns.get(name).expect(&format!("ERROR: Column {name} doesn't exists in the table {}", self.name))
+    pub fn get_column(&self, name: &String) -> Result<&Column, IrisError> {
+        self.columns.get(name).ok_or_else(|| self.unknown_column(name))
     }
 
     
-    pub fn get_column_mut(&mut self, name: &String) -> &mut Column {
-        self.columns.get_mut(name).expect(&format!("ERROR: Column {name} doesn't exists in the table {}", self.name))
+    pub fn get_column_mut(&mut self, name: &String) -> Result<&mut Column, IrisError> {
+        let err = self.unknown_column(name);
+        self.columns.get_mut(name).ok_or(err)
     }
 
     pub fn column_exists_without_flag(&self, name: &String) -> bool {
@@ -365,8 +860,9 @@ impl Table {
         }
     }
 
-    pub fn active_column_flag(&mut self, name: &String) {
-        self.get_column_mut(name).active_flag()
+    pub fn active_column_flag(&mut self, name: &String) -> ConsumeResult {
+        self.get_column_mut(name)?.active_flag();
+        Ok(())
     }
     
     pub fn reset_all_flags(&mut self) {
@@ -384,6 +880,11 @@ impl Table {
         &self.columns
     }
 
+    /// Indicate if a line has the given value in the column
+    pub fn contains_value(&self, column: &String, value: &JsonValue) -> Result<bool, IrisError> {
+        Ok(!self.lines_with_value(column, value)?.is_empty())
+    }
+
     pub fn p_key(&self) -> &String {
         &self.p_key
     }
@@ -392,97 +893,190 @@ impl Table {
         json!({
             "name": self.name(),
             "p_key": self.p_key(),
+            "auto_increment": self.auto_increment,
             "columns": self.columns.iter().map(|(_, c)| {
                 c.get_datas()
-            }).collect::<Vec<_>>()
+            }).collect::<Vec<_>>(),
+            "indexes": self.indexes.iter().map(Index::get_datas).collect::<Vec<_>>()
         })
     }
 
-    pub fn browse(&mut self, browser: &mut dyn BrowserReq) {
-        for (i, line) in self.lines.iter_mut().enumerate() {
-            let map = extract_map_from_json(line);
-            if browser.get_expr().compute(map, false) != 0 {
-                browser.browse_action(map, i);
+    pub fn has_index(&self, name: &str) -> bool {
+        self.indexes.iter().any(|index| index.name() == name)
+    }
+
+    /// Build a new index on the given column with the current lines
+    pub fn add_index(&mut self, name: String, column: String) -> ConsumeResult {
+        self.get_column(&column)?;
+        let mut index = Index::new(name, column);
+        for i in 0..self.rows.len() {
+            index.add_line(&self.get_line(i)?, i);
+        }
+        self.indexes.push(index);
+        Ok(())
+    }
+
+    pub fn remove_index(&mut self, name: &str) {
+        self.indexes.retain(|index| index.name() != name)
+    }
+
+    /// Call the browse action of the browser on each line satisfying its where expression, every line if there is no where.
+    /// The first offset matching lines are skipped and the browsing stops once the limit of the browser is reached.
+    pub fn browse(&mut self, browser: &mut dyn BrowserReq) -> ConsumeResult {
+        let no_where = browser.get_expr().is_empty();
+        if !no_where {
+            browser.get_expr().check_condition(&self.sample_line())?;
+        }
+        let lines: Box<dyn Iterator<Item = usize>> = match self.candidate_lines(browser.get_expr())? {
+            Some(candidates) => Box::new(candidates.into_iter()),
+            None => Box::new(0..self.rows.len())
+        };
+        let mut to_skip = browser.get_offset();
+        let mut remaining = browser.get_limit();
+        for i in lines {
+            if remaining == Some(0) {
+                break;
+            }
+            let mut map = self.get_line(i)?;
+            if no_where || browser.get_expr().compute(&map, false)? {
+                if to_skip > 0 {
+                    to_skip -= 1;
+                } else {
+                    browser.browse_action(&mut map, i)?;
+                    remaining = remaining.map(|r| r - 1);
+                }
             }
         }
+        Ok(())
+    }
+
+    /// A line with a value of the type of each column
+    fn sample_line(&self) -> Map<String, JsonValue> {
+        self.columns.values().map(|column| (column.name.clone(), column.get_type().sample())).collect()
+    }
+
+    /// Use the indexes to find the only lines which can satisfy the expression, None if every line has to be checked.
+    fn candidate_lines(&self, expr: &mut ExpressionEvaluator) -> Result<Option<Vec<usize>>, IrisError> {
+        if let Some((_, key)) = self.typed_conditions(expr, &self.p_key)?.into_iter().find(|(op, _)| op == "==") {
+            return Ok(Some(self.p_key_index.get(&JsonValue::from(key).to_string()).copied().into_iter().collect()))
+        }
+        for index in self.indexes.iter().chain(self.key_indexes.iter()) {
+            if let Some(lines) = index.candidate_lines(&self.typed_conditions(expr, index.column())?) {
+                return Ok(Some(lines))
+            }
+        }
+        Ok(None)
+    }
+
+    /// The conditions of the expression on the column whose constant fits in the column, converted to the type of the column.
+    /// The other ones are left to the evaluator.
+    fn typed_conditions(&self, expr: &mut ExpressionEvaluator, column: &String) -> Result<Vec<(String, Operand)>, IrisError> {
+        let column_type = self.get_column(column).map(Column::get_type).unwrap_or(Type::Int);
+        Ok(expr.field_conditions(column)?.into_iter()
+            .filter(|(_, constant)| column_type.accepts(constant))
+            .map(|(op, constant)| (op, column_type.cast(constant)))
+            .collect())
     }
 }
 
 
+/// The file storing the data of every table
+static DATA_FILE: &str = "tables.json";
+
 pub struct Database {
+    wal: Wal,
+    pool: SharedPool,
     tables: HashMap<String, Table>,
     data_file: File,
-    json_table_data: Vec<JsonValue>
+    json_table_data: Vec<JsonValue>,
+    dir: PathBuf,
+    data_modified: bool,
+    transaction: bool,
+    aborted: bool,
+    dropped_tables: Vec<String>,
+    /// The tables created or whose data changed since the last write, they are loaded again if the changes are discarded
+    changed_tables: HashSet<String>,
+    /// A write of the changes failed, the files may not match the memory until the database is loaded again
+    failed_write: bool
 }
 
 
 impl Database {
 
-    pub fn new_empty() -> Database {
-        Database {
+    /// The committed changes of the write-ahead log are applied before the data file is opened
+    pub fn new_empty(dir: &Path, memory_limit: usize) -> Result<Database, IrisError> {
+        Ok(Database {
+            wal: Wal::open(dir)?,
+            pool: SharedPool::new(dir, memory_limit),
             tables: HashMap::new(),
-            data_file: Database::load_data_file(),
-            json_table_data: Vec::new()
-        }
+            data_file: Database::load_data_file(dir)?,
+            json_table_data: Vec::new(),
+            dir: dir.to_path_buf(),
+            data_modified: false,
+            transaction: false,
+            aborted: false,
+            dropped_tables: Vec::new(),
+            changed_tables: HashSet::new(),
+            failed_write: false
+        })
     }
 
-    fn load_data_file() -> File {
-        let iris_path = get_iris_path();
-        let mut create = false;
-        let data_path = iris_path + "/tables.json";
+    fn load_data_file(dir: &Path) -> Result<File, IrisError> {
+        let data_path = dir.join(DATA_FILE).to_string_lossy().into_owned();
         let path = Path::new(&data_path);
         if !path.exists() {
-            create = true;
-            File::create(path).expect("Failed to create the data file");
-        }
-        let mut file = open_file(&data_path);
-        if create {
-            file.write_all("[]".as_bytes()).expect("write failed");
+            replace_file(path, "[]".as_bytes()).map_err(|e| IrisError::io("Failed to create the data file", e))?;
         }
-        file
+        open_file(&data_path)
     }
 
     
-    /// Load the database (only the tables data not each lines..) and return it
-    pub fn load() -> Database {
-        let mut res = Database::new_empty();
-        res.load_table_vec_from_file();
+    /// Load the database stored in the given directory, the tables data and the data file of each table, and return it.
+    /// The pages of the data files kept in memory use at most the given memory, except the pages modified by the current request or transaction.
+    pub fn load(dir: &Path, memory_limit: usize) -> Result<Database, IrisError> {
+        let mut res = Database::new_empty(dir, memory_limit)?;
+        res.load_table_vec_from_file()?;
         let mut map = HashMap::new();
         for table in res.json_table_data.iter() {
-            let table = Table::load(table); 
+            let table = Table::load(table, &res.dir, &res.pool)?; 
             map.insert(table.name().clone(), table);
         }
         res.tables = map;
-        res
+        Ok(res)
     }
 
 
-    fn load_table_vec_from_file(&mut self)  {
-        let data_file_content = self.get_data_file_content();
-        let datas: JsonValue = serde_json::from_str(if data_file_content.is_empty() { "[]" } else  { &data_file_content }).expect("Failed to extract json data file.");
-        self.json_table_data = extract_vec_from_json(&datas);
+    fn load_table_vec_from_file(&mut self) -> ConsumeResult {
+        let data_file_content = self.get_data_file_content()?;
+        let datas: JsonValue = serde_json::from_str(if data_file_content.is_empty() { "[]" } else  { &data_file_content })
+            .map_err(|e| IrisError::Corrupted(format!("The tables data file is not valid json: {e}")))?;
+        self.json_table_data = extract_vec_from_json(&datas)?;
+        Ok(())
     }
     
-    fn get_data_file_content(&mut self) -> String {
-        self.data_file.seek(std::io::SeekFrom::Start(0)).expect("Failed to seek during the reading of data file");
+    fn get_data_file_content(&mut self) -> Result<String, IrisError> {
         let mut result = String::new();
-        self.data_file.read_to_string(&mut result).expect("Failed to read the data file");
-        result
+        self.data_file.seek(std::io::SeekFrom::Start(0))
+            .and_then(|_| self.data_file.read_to_string(&mut result))
+            .map_err(|e| IrisError::io("Failed to read the tables data file", e))?;
+        Ok(result)
     }
     
     /// Add a table in the database, in the database of the program and in the one of the system
-    pub fn add_table(&mut self, mut table: Table) {
-        table.save();
+    pub fn add_table(&mut self, mut table: Table) -> ConsumeResult {
+        table.set_dir(&self.dir);
+        table.save(&self.pool);
         self.json_table_data.push(table.get_datas());
-        self.actualise_data_file();
+        self.changed_tables.insert(table.name().clone());
+        self.actualise_data_file()?;
         self.insert_table(table);
+        Ok(())
     }
 
-    fn actualise_data_file(&mut self) {
-        self.data_file.set_len(0).expect("Failed to reset the data file len");
-        self.data_file
-            .write_all(JsonValue::Array(self.json_table_data.clone()).to_string().trim().as_bytes())
-            .expect("write failed");
+    /// Mark the tables data as modified, the data file is written with the other changes of the request or of the transaction
+    fn actualise_data_file(&mut self) -> ConsumeResult {
+        self.data_modified = true;
+        Ok(())
     }
 
     fn insert_table(&mut self, table: Table) {
@@ -495,69 +1089,375 @@ impl Database {
     }
 
 
-    /// Delete the table in the database of the program and in the one of the system
-    pub fn delete_table(&mut self, name: &String) {
-        self.tables.get_mut(name).expect(&format!("Drop error: The table {} doesn't exists", name)).drop();
+    /// Delete the table in the database of the program and in the one of the system, refused if another table references it
+    pub fn delete_table(&mut self, name: &String) -> ConsumeResult {
+        if let Some((child, column, _)) = self.referencing_columns(name).into_iter().find(|(child, _, _)| child != name) {
+            return Err(IrisError::InvalidRequest(format!("The table {name} can't be dropped, the column {column} of the table {child} references it.")))
+        }
+        self.get_table(name)?;
+        self.dropped_tables.push(name.clone());
+        self.pool.lock().forget(name);
         self.tables.remove(name);
-        let mut i = 0;
-        for t in self.json_table_data.iter() {
-            if extract_string_from_json(&t["name"]) == *name {
-                break;
+        self.json_table_data.retain(|t| t["name"] != JsonValue::String(name.clone()));
+        self.actualise_data_file()
+    }
+
+    /// Apply the given change on the lines of the table, then save its AUTOINCREMENT counter if the change moved it
+    pub fn modify_table(&mut self, name: &String, change: impl FnOnce(&mut Table) -> ConsumeResult) -> ConsumeResult {
+        let table = self.get_table_mut(name)?;
+        let counter = table.next_auto_increment();
+        change(table)?;
+        if table.next_auto_increment() != counter {
+            self.actualise_table_datas(name)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the data of the given table in the tables data file by its current version
+    fn actualise_table_datas(&mut self, name: &String) -> ConsumeResult {
+        let datas = self.get_table(name)?.get_datas();
+        self.changed_tables.insert(name.clone());
+        for t in self.json_table_data.iter_mut() {
+            if t["name"] == JsonValue::String(name.clone()) {
+                *t = datas.clone();
             }
-            i += 1;
         }
-        self.json_table_data.remove(i);
-        self.actualise_data_file();
+        self.actualise_data_file()
+    }
+
+    fn index_owner(&self, index_name: &str) -> Option<String> {
+        self.tables.values().find(|t| t.has_index(index_name)).map(|t| t.name().clone())
+    }
+
+    pub fn create_index(&mut self, name: String, table_name: &String, column: String) -> ConsumeResult {
+        if self.index_owner(&name).is_some() {
+            return Err(IrisError::InvalidRequest(format!("The index {name} already exists.")))
+        }
+        self.get_table_mut(table_name)?.add_index(name, column)?;
+        self.actualise_table_datas(table_name)
+    }
+
+    pub fn drop_index(&mut self, name: &str) -> ConsumeResult {
+        let table_name = match self.index_owner(name) {
+            Some(table_name) => table_name,
+            None => return Err(IrisError::InvalidRequest(format!("The index {name} doesn't exist.")))
+        };
+        self.get_table_mut(&table_name)?.remove_index(name);
+        self.actualise_table_datas(&table_name)
+    }
+
+    pub fn reset_all_flags(&mut self) {
+        for table in self.tables.values_mut() {
+            table.reset_all_flags()
+        }
+    }
+
+    /// Start a transaction, the changes are kept in memory until the COMMIT
+    pub fn begin(&mut self) -> ConsumeResult {
+        if self.transaction {
+            return Err(IrisError::InvalidRequest(String::from("A transaction is already in progress, it has to be committed or rolled back before starting a new one.")))
+        }
+        self.transaction = true;
+        Ok(())
+    }
+
+    /// Write every change of the transaction, nothing is written if the transaction was aborted by an error
+    pub fn commit(&mut self) -> ConsumeResult {
+        if !self.transaction {
+            return Err(IrisError::InvalidRequest(String::from("There is no transaction to commit.")))
+        }
+        self.transaction = false;
+        if std::mem::take(&mut self.aborted) {
+            return Err(IrisError::InvalidRequest(String::from("The transaction was aborted by an error, it was rolled back and nothing was committed.")))
+        }
+        self.flush()
+    }
+
+    /// Forget every change of the transaction
+    pub fn rollback(&mut self) -> ConsumeResult {
+        if !self.transaction {
+            return Err(IrisError::InvalidRequest(String::from("There is no transaction to roll back.")))
+        }
+        self.discard_changes()
+    }
+
+    /// Write the changes of the request which just ended, unless they belong to a transaction
+    pub fn end_request(&mut self) -> ConsumeResult {
+        if self.transaction {
+            return Ok(())
+        }
+        self.flush()
+    }
+
+    /// Write the changes in the write-ahead log, then in the files: the files of the dropped tables are removed,
+    /// then the modified tables and the tables data are written. The changes are forgotten once they are written,
+    /// a failed write leaves them to the next load of the database.
+    fn flush(&mut self) -> ConsumeResult {
+        let mut entries = self.dropped_tables.iter().map(|file| WalEntry::Remove { file: file.clone() }).collect::<Vec<_>>();
+        entries.extend(self.tables.values_mut().filter_map(Table::take_changes));
+        if self.data_modified {
+            entries.push(WalEntry::Write {
+                file: DATA_FILE.to_string(),
+                content: JsonValue::Array(self.json_table_data.clone()).to_string()
+            });
+        }
+        if let Err(e) = self.wal.commit(entries) {
+            self.failed_write = true;
+            return Err(e)
+        }
+        self.dropped_tables.clear();
+        self.changed_tables.clear();
+        self.data_modified = false;
+        Ok(())
+    }
+
+    /// Forget the changes of a request interrupted by an error. A transaction in progress loses all its changes
+    /// and stays open but aborted: the next requests are refused until the COMMIT or the ROLLBACK which ends it.
+    pub fn abort(&mut self) -> ConsumeResult {
+        let transaction = self.transaction;
+        self.discard_changes()?;
+        self.transaction = transaction;
+        self.aborted = transaction;
+        Ok(())
+    }
+
+    /// Refuse the requests of a transaction aborted by an error
+    pub fn check_not_aborted(&self) -> ConsumeResult {
+        if self.aborted {
+            return Err(IrisError::InvalidRequest(String::from("The transaction was aborted by an error and its changes were rolled back, it has to be ended by a COMMIT or a ROLLBACK.")))
+        }
+        Ok(())
+    }
+
+    /// Forget the changes which aren't written yet and the transaction in progress: the tables data and the tables
+    /// which were created, dropped or modified are loaded again from their files, which weren't modified since.
+    /// After a failed write, the whole database is loaded again and the committed changes of the log are applied.
+    pub fn discard_changes(&mut self) -> ConsumeResult {
+        self.transaction = false;
+        self.aborted = false;
+        if std::mem::take(&mut self.failed_write) {
+            let dir = self.dir.clone();
+            let memory_limit = self.pool.lock().memory_limit();
+            *self = Database::load(&dir, memory_limit)?;
+            return Ok(())
+        }
+        let mut stale = std::mem::take(&mut self.changed_tables);
+        stale.extend(self.dropped_tables.drain(..));
+        stale.extend(self.tables.values().filter(|table| table.has_changes()).map(|table| table.name().clone()));
+        if std::mem::take(&mut self.data_modified) {
+            // each write replaces the tables data file, the one opened with the database is outdated
+            self.data_file = Database::load_data_file(&self.dir)?;
+            self.load_table_vec_from_file()?;
+        }
+        for name in stale.iter() {
+            self.pool.lock().forget(name);
+            self.tables.remove(name);
+        }
+        for table in self.json_table_data.iter().filter(|table| table["name"].as_str().is_some_and(|name| stale.contains(name))) {
+            let table = Table::load(table, &self.dir, &self.pool)?;
+            self.tables.insert(table.name().clone(), table);
+        }
+        Ok(())
     }
 
-    pub fn reset_database(&mut self) {
-        self.tables.clear();
-        self.data_file.set_len(0).expect("failed to reset the data file");
-        self.load_table_vec_from_file();
+    /// Limit the memory used by the pages of the data files kept in memory
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.pool.lock().set_memory_limit(memory_limit)
     }
 
-    pub fn get_table(&self, name: &String) -> &Table {
-        self.tables.get(name).expect(&format!("ERROR: The table {name} doesn't exists."))
+    #[cfg(test)]
+    pub fn fail_writes(&mut self) {
+        self.wal.fail_writes()
+    }
+
+    /// Free the last pages of the data file of the given table or of every table, each step is written in the log at once.
+    /// It can't be done in a transaction, whose changes would be written with the first step.
+    pub fn vacuum(&mut self, name: Option<&String>) -> ConsumeResult {
+        if self.transaction {
+            return Err(IrisError::InvalidRequest(String::from("VACUUM can't be executed in a transaction, its steps are written as it goes.")))
+        }
+        let wal = &mut self.wal;
+        let mut write = |entry: Option<WalEntry>| wal.commit(entry.into_iter().collect());
+        let result = match name {
+            Some(name) => self.tables.get_mut(name).ok_or_else(|| IrisError::UnknownTable(name.clone()))?.vacuum(&mut write),
+            None => self.tables.values_mut().try_for_each(|table| table.vacuum(&mut write))
+        };
+        self.failed_write |= result.is_err();
+        result
+    }
+
+    /// Remove every table with its data file
+    pub fn reset_database(&mut self) -> ConsumeResult {
+        for name in self.tables.keys() {
+            self.pool.lock().forget(name);
+        }
+        self.dropped_tables.extend(self.tables.drain().map(|(name, _)| name));
+        self.json_table_data.clear();
+        self.actualise_data_file()
     }
 
-    pub fn get_table_mut(&mut self, name: &String) -> &mut Table {
-        self.tables.get_mut(name).expect(&format!("ERROR: The table {name} doesn't exists."))
+    pub fn get_table(&self, name: &String) -> Result<&Table, IrisError> {
+        self.tables.get(name).ok_or_else(|| IrisError::UnknownTable(name.clone()))
+    }
+
+    pub fn get_table_mut(&mut self, name: &String) -> Result<&mut Table, IrisError> {
+        self.tables.get_mut(name).ok_or_else(|| IrisError::UnknownTable(name.clone()))
     }
 
     
-    pub fn test_column_existance(&self, table_name: &String, cols: &Vec<String>) -> ConsumeResult {
-        let table = self.get_table(&table_name);
+    pub fn test_column_existance(&self, table_name: &String, cols: &[String]) -> ConsumeResult {
+        let table = self.get_table(table_name)?;
         for c in cols.iter() {
-            if !table.column_exists(c) {
-                return Err(format!("Error during the selection on {}: the column {} doesn't exists.", table_name, c))
+            table.get_column(c)?;
+        }
+        Ok(())
+    }
+
+    /// The columns of every table referencing the given table, as (table, column, foreign key)
+    fn referencing_columns(&self, table_name: &String) -> Vec<(String, String, ForeignKey)> {
+        let mut res = Vec::new();
+        for table in self.tables.values() {
+            for column in table.get_cols().values() {
+                if let Some(foreign_key) = column.foreign_key().filter(|fk| fk.table() == table_name) {
+                    res.push((table.name().clone(), column.name().clone(), foreign_key.clone()));
+                }
+            }
+        }
+        res
+    }
+
+    /// Refuse the new lines of the table whose foreign keys reference a line which doesn't exist,
+    /// a line of a table referencing itself can reference one of the new lines.
+    pub fn check_foreign_keys(&self, table_name: &String, new_lines: &[&Map<String, JsonValue>]) -> ConsumeResult {
+        let table = self.get_table(table_name)?;
+        for column in table.get_cols().values() {
+            let Some(foreign_key) = column.foreign_key() else {
+                continue
+            };
+            let referenced = self.get_table(foreign_key.table())?;
+            for line in new_lines.iter() {
+                let value = line.get(column.name()).unwrap_or(&JsonValue::Null);
+                let in_new_lines = || foreign_key.table() == table_name && new_lines.iter().any(|other| other.get(foreign_key.column()) == Some(value));
+                if !value.is_null() && !referenced.contains_value(foreign_key.column(), value)? && !in_new_lines() {
+                    return Err(IrisError::ConstraintViolation(format!("The value {} of the column {} of the table {table_name} doesn't exist in the column {} of the table {}.",
+                        Value::from_json(value)?.string(), column.name(), foreign_key.column(), foreign_key.table())))
+                }
             }
         }
         Ok(())
     }
+
+    /// Refuse the updates modifying a value of the table which is referenced by a foreign key
+    pub fn check_referenced_keys(&self, table_name: &String, updates: &[(usize, Map<String, JsonValue>)]) -> ConsumeResult {
+        let table = self.get_table(table_name)?;
+        for (child, column, foreign_key) in self.referencing_columns(table_name) {
+            let child_table = self.get_table(&child)?;
+            for (i, line) in updates.iter() {
+                let old_line = table.get_line(*i)?;
+                let old_value = old_line.get(foreign_key.column()).unwrap_or(&JsonValue::Null);
+                if !old_value.is_null() && line.get(foreign_key.column()) != Some(old_value) && child_table.contains_value(&column, old_value)? {
+                    return Err(IrisError::ConstraintViolation(format!("The value {} of the column {} of the table {table_name} can't be modified, the column {column} of the table {child} references it.",
+                        Value::from_json(old_value)?.string(), foreign_key.column())))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the given lines of the table and apply the ON DELETE action of the foreign keys referencing them,
+    /// nothing is modified if a RESTRICT foreign key references one of the deleted lines.
+    pub fn delete_lines(&mut self, table_name: &str, lines: Vec<usize>) -> ConsumeResult {
+        let mut deleted = HashMap::<String, BTreeSet<usize>>::new();
+        let mut set_null = HashMap::<String, BTreeMap<usize, Vec<String>>>::new();
+        let mut to_delete = vec!((table_name.to_string(), lines));
+        while let Some((parent, lines)) = to_delete.pop() {
+            let parent_deleted = deleted.entry(parent.clone()).or_default();
+            let lines = lines.into_iter().filter(|i| parent_deleted.insert(*i)).collect::<Vec<_>>();
+            let parent_table = self.get_table(&parent)?;
+            let parent_lines = lines.iter().map(|i| parent_table.get_line(*i)).collect::<Result<Vec<_>, _>>()?;
+            for (child, column, foreign_key) in self.referencing_columns(&parent) {
+                let keys = parent_lines.iter().filter_map(|line| line.get(foreign_key.column())).filter(|key| !key.is_null()).collect::<Vec<_>>();
+                let child_deleted = deleted.get(&child);
+                let child_table = self.get_table(&child)?;
+                let mut referencing = BTreeSet::new();
+                for key in keys {
+                    referencing.extend(child_table.lines_with_value(&column, key)?.into_iter().filter(|i| !child_deleted.is_some_and(|d| d.contains(i))));
+                }
+                let referencing = referencing.into_iter().collect::<Vec<_>>();
+                let Some(first) = referencing.first() else {
+                    continue
+                };
+                match foreign_key.on_delete() {
+                    OnDelete::Restrict => return Err(IrisError::ConstraintViolation(format!("The line of the table {parent} with {} = {} can't be deleted, the column {column} of the table {child} references it.",
+                        foreign_key.column(), Value::from_json(&child_table.get_line(*first)?[&column])?.string()))),
+                    OnDelete::Cascade => to_delete.push((child, referencing)),
+                    OnDelete::SetNull => for i in referencing {
+                        set_null.entry(child.clone()).or_default().entry(i).or_default().push(column.clone());
+                    }
+                }
+            }
+        }
+        for (name, lines) in set_null {
+            let table = self.get_table_mut(&name)?;
+            let updates = lines.into_iter()
+                .filter(|(i, _)| !deleted.get(&name).is_some_and(|d| d.contains(i)))
+                .map(|(i, columns)| {
+                    let mut line = table.get_line(i)?;
+                    for column in columns {
+                        line.insert(column, JsonValue::Null);
+                    }
+                    Ok((i, line))
+                })
+                .collect::<Result<_, IrisError>>()?;
+            table.update_lines(updates)?;
+        }
+        for (name, lines) in deleted {
+            let mut stack = Stack::new();
+            for i in lines {
+                stack.push(i);
+            }
+            self.get_table_mut(&name)?.drop_lines(&mut stack)?;
+        }
+        Ok(())
+    }
     
 }
 
 pub type BoxedReq = Box<dyn Request>;
 
-pub  trait Request {
+pub  trait Request: Send {
     
     fn consume(&mut self, database: &mut Database, token: Token) -> ConsumeResult;
 
     fn new() -> BoxedReq where Self: Sized;
 
     fn end(&mut self, database: &mut Database) -> ConsumeResult;
+
+    /// Give the rows produced by the last request, if the request produces some.
+    fn take_result(&mut self) -> Option<QueryResult> {
+        None
+    }
     
-    fn panic_bad_token(&self, token: Token, name: &str) {
-        eprintln!("Tried to conusme an unexpected token in {name}: {type_token:?}: {content}", type_token=token.token_type, content=token.content);
-        exit(1);
+    fn bad_token(&self, token: Token, name: &str) -> ConsumeResult {
+        Err(IrisError::Syntax(format!("Unexpected token in the {name} request: {type_token:?}: {content}", type_token=token.token_type, content=token.content)))
     }
     
 }
 
 pub trait BrowserReq {
 
-    fn browse_action(&mut self, line: &mut Map::<String, JsonValue>, i: usize);
+    fn browse_action(&mut self, line: &mut Map::<String, JsonValue>, i: usize) -> ConsumeResult;
 
     fn get_expr(&mut self) -> &mut ExpressionEvaluator;
+
+    /// The maximum number of lines given to the browse action, None to browse every matching line.
+    fn get_limit(&self) -> Option<usize> {
+        None
+    }
+
+    /// The number of matching lines skipped before the first browse action.
+    fn get_offset(&self) -> usize {
+        0
+    }
     
 }
@@ -0,0 +1,218 @@
+use super::include::{JsonValue, Map, IrisError, Operand, json};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::cmp::Ordering;
+
+/// The value of a column as stored in an index, numbers and floats are ordered by value, strings by content and FALSE before TRUE.
+#[derive(Debug, Clone)]
+pub enum IndexKey {
+    Bool(bool),
+    Number(i64),
+    Float(f64),
+    String(String)
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &IndexKey) -> Ordering {
+        match (self, other) {
+            (IndexKey::Bool(b1), IndexKey::Bool(b2)) => b1.cmp(b2),
+            (IndexKey::Number(n1), IndexKey::Number(n2)) => n1.cmp(n2),
+            (IndexKey::String(s1), IndexKey::String(s2)) => s1.cmp(s2),
+            _ if self.rank() != other.rank() => self.rank().cmp(&other.rank()),
+            _ => self.as_float().partial_cmp(&other.as_float()).unwrap_or(Ordering::Equal)
+        }
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &IndexKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &IndexKey) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+impl IndexKey {
+
+    fn from_json(data: &JsonValue) -> Option<IndexKey> {
+        match data {
+            JsonValue::Number(number) => number.as_i64().map(IndexKey::Number).or_else(|| number.as_f64().map(IndexKey::Float)),
+            JsonValue::String(string) => Some(IndexKey::String(string.clone())),
+            JsonValue::Bool(boolean) => Some(IndexKey::Bool(*boolean)),
+            _ => None
+        }
+    }
+
+    fn from_operand(operand: &Operand) -> Option<IndexKey> {
+        match operand {
+            Operand::Number(number) => Some(IndexKey::Number(*number)),
+            Operand::Float(float) => Some(IndexKey::Float(*float)),
+            Operand::String(string) => Some(IndexKey::String(string.clone())),
+            Operand::Bool(boolean) => Some(IndexKey::Bool(*boolean)),
+            Operand::Temporal(temporal) => Some(IndexKey::String(temporal.to_string())),
+            Operand::Null => None
+        }
+    }
+
+    /// Keys of different kinds are ordered by kind, numbers and floats being the same kind
+    fn rank(&self) -> u8 {
+        match self {
+            IndexKey::Bool(_) => 0,
+            IndexKey::Number(_) | IndexKey::Float(_) => 1,
+            IndexKey::String(_) => 2
+        }
+    }
+
+    fn as_float(&self) -> f64 {
+        match self {
+            IndexKey::Number(number) => *number as f64,
+            IndexKey::Float(float) => *float,
+            _ => f64::NAN
+        }
+    }
+
+}
+
+/// A secondary index, associate each value of a column to the lines containing it.
+#[derive(Debug)]
+pub struct Index {
+    name: String,
+    column: String,
+    entries: BTreeMap<IndexKey, Vec<usize>>
+}
+
+impl Index {
+
+    pub fn new(name: String, column: String) -> Index {
+        Index {
+            name,
+            column,
+            entries: BTreeMap::new()
+        }
+    }
+
+    pub fn load(json_data: &JsonValue) -> Result<Index, IrisError> {
+        match (&json_data["name"], &json_data["column"]) {
+            (JsonValue::String(name), JsonValue::String(column)) => Ok(Index::new(name.clone(), column.clone())),
+            _ => Err(IrisError::Corrupted(format!("An index was expected in the tables data, found {json_data}")))
+        }
+    }
+
+    pub fn get_datas(&self) -> JsonValue {
+        json!({
+            "name": self.name,
+            "column": self.column
+        })
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn column(&self) -> &String {
+        &self.column
+    }
+
+    /// Forget the deleted lines, given in ascending order, and renumber the following ones
+    pub fn remove_line_numbers(&mut self, deleted: &[usize]) {
+        for lines in self.entries.values_mut() {
+            lines.retain(|i| deleted.binary_search(i).is_err());
+            for i in lines.iter_mut() {
+                *i -= deleted.partition_point(|d| d < i);
+            }
+        }
+        self.entries.retain(|_, lines| !lines.is_empty());
+    }
+
+    pub fn add_line(&mut self, line: &Map<String, JsonValue>, line_number: usize) {
+        if let Some(key) = line.get(&self.column).and_then(IndexKey::from_json) {
+            self.entries.entry(key).or_default().push(line_number);
+        }
+    }
+
+    pub fn remove_line(&mut self, line: &Map<String, JsonValue>, line_number: usize) {
+        if let Some(key) = line.get(&self.column).and_then(IndexKey::from_json) {
+            if let Some(lines) = self.entries.get_mut(&key) {
+                lines.retain(|i| *i != line_number);
+                if lines.is_empty() {
+                    self.entries.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// The lines having the given value in the column, in ascending order
+    pub fn lines_with(&self, value: &JsonValue) -> Vec<usize> {
+        let mut lines = IndexKey::from_json(value).and_then(|key| self.entries.get(&key)).cloned().unwrap_or_default();
+        lines.sort_unstable();
+        lines
+    }
+
+    /// Returns in ascending order the lines satisfying every given comparison (operator, constant) on the column,
+    /// None if none of them can be answered by the index.
+    pub fn candidate_lines(&self, conditions: &[(String, Operand)]) -> Option<Vec<usize>> {
+        let mut lower = Bound::Unbounded;
+        let mut upper = Bound::Unbounded;
+        let mut used = false;
+        for (op, constant) in conditions.iter() {
+            if !["==", ">", ">=", "<", "<="].contains(&op.as_str()) {
+                continue
+            }
+            let Some(key) = IndexKey::from_operand(constant) else {
+                return Some(Vec::new())  // A comparison with NULL is never true
+            };
+            match op as &str {
+                "==" => {
+                    lower = tighter_bound(lower, Bound::Included(key.clone()), true);
+                    upper = tighter_bound(upper, Bound::Included(key), false);
+                }
+                ">" => lower = tighter_bound(lower, Bound::Excluded(key), true),
+                ">=" => lower = tighter_bound(lower, Bound::Included(key), true),
+                "<" => upper = tighter_bound(upper, Bound::Excluded(key), false),
+                "<=" => upper = tighter_bound(upper, Bound::Included(key), false),
+                _ => continue
+            }
+            used = true;
+        }
+        if !used {
+            return None
+        }
+        if is_empty_range(&lower, &upper) {
+            return Some(Vec::new())
+        }
+        let mut lines = self.entries.range((lower, upper)).flat_map(|(_, lines)| lines.iter().copied()).collect::<Vec<_>>();
+        lines.sort_unstable();
+        Some(lines)
+    }
+
+}
+
+fn bound_key(bound: &Bound<IndexKey>) -> Option<&IndexKey> {
+    match bound {
+        Bound::Included(key) | Bound::Excluded(key) => Some(key),
+        Bound::Unbounded => None
+    }
+}
+
+/// Keep the most restrictive of the two bounds, the greatest one for a lower bound and the smallest one for an upper bound.
+fn tighter_bound(current: Bound<IndexKey>, new: Bound<IndexKey>, lower: bool) -> Bound<IndexKey> {
+    match (bound_key(&current), bound_key(&new)) {
+        (None, _) => new,
+        (_, None) => current,
+        (Some(c), Some(n)) if c == n => if matches!(current, Bound::Excluded(_)) { current } else { new },
+        (Some(c), Some(n)) => if (n > c) == lower { new } else { current }
+    }
+}
+
+fn is_empty_range(lower: &Bound<IndexKey>, upper: &Bound<IndexKey>) -> bool {
+    match (bound_key(lower), bound_key(upper)) {
+        (Some(l), Some(u)) => l > u || l == u && (matches!(lower, Bound::Excluded(_)) || matches!(upper, Bound::Excluded(_))),
+        _ => false
+    }
+}
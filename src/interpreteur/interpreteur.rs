@@ -6,8 +6,12 @@ use super::{
     requests::insert_req::InsertReq,
     requests::select_req::SelectReq,
     requests::set_req::SetReq,
-    requests::delete_req::DeleteReq
+    requests::delete_req::DeleteReq,
+    requests::transaction_req::{BeginReq, CommitReq, RollbackReq},
+    requests::vacuum_req::VacuumReq,
+    buffer_pool::DEFAULT_MEMORY_LIMIT
 };
+use std::path::Path;
 
 
 pub struct Interpreteur {
@@ -15,19 +19,22 @@ pub struct Interpreteur {
     keyword_link: HashMap<String, usize>,
     current_treater: usize,
     database: Database,
-    request_in_treatment: bool
+    request_in_treatment: bool,
+    results: Vec<QueryResult>
 }
 
 impl Interpreteur {
 
-    pub fn new() -> Interpreteur{
-        Interpreteur {
+    /// Build an interpreteur working on the database stored in the given directory
+    pub fn new(dir: &Path) -> Result<Interpreteur, IrisError> {
+        Ok(Interpreteur {
             request_treaters: Interpreteur::build_treaters(),
             keyword_link: Interpreteur::build_keyword_link(),
             current_treater: 0,
-            database: Database::load(),
-            request_in_treatment: false
-        }
+            database: Database::load(dir, DEFAULT_MEMORY_LIMIT)?,
+            request_in_treatment: false,
+            results: Vec::new()
+        })
     }
 
     pub fn new_token(&mut self, token: Token) -> ConsumeResult {
@@ -43,25 +50,64 @@ impl Interpreteur {
         if self.request_in_treatment {
             self.request_treaters[self.current_treater].consume(&mut self.database, token)?;
         } else {
-            self.current_treater = *self.keyword_link.get(&token.content).expect(&format!("Interpreteur: Unknow main keyword: {}", token.content));
+            self.current_treater = match self.keyword_link.get(&token.content) {
+                Some(treater) => *treater,
+                None => return Err(IrisError::Syntax(format!("Unknow main keyword: {}", token.content)))
+            };
+            if !matches!(token.content.as_str(), "COMMIT" | "ROLLBACK") {
+                self.database.check_not_aborted()?;
+            }
             self.request_in_treatment = true;
         }
         Ok(())
     }
     
     fn end_request(&mut self) -> ConsumeResult {
-        self.request_treaters[self.current_treater].end(&mut self.database)?;
+        let treater = &mut self.request_treaters[self.current_treater];
+        treater.end(&mut self.database)?;
+        self.database.end_request()?;
+        self.results.push(treater.take_result().unwrap_or_else(QueryResult::empty));
         self.request_in_treatment = false;
         Ok(())
     }
+
+    /// Forget the request in treatment, used when an error interrupted it.
+    /// Its changes and the ones of the transaction in progress are not kept, the transaction is aborted.
+    pub fn abort_request(&mut self) -> ConsumeResult {
+        self.request_treaters = Interpreteur::build_treaters();
+        self.request_in_treatment = false;
+        self.database.reset_all_flags();
+        self.results.clear();
+        self.database.abort()
+    }
+
+    /// Limit the memory used by the pages of the data files kept in memory
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.database.set_memory_limit(memory_limit)
+    }
+
+    /// Make the next writes of the database fail, they succeed again once it is loaded again
+    #[cfg(test)]
+    pub fn fail_writes(&mut self) {
+        self.database.fail_writes()
+    }
+
+    pub fn request_in_treatment(&self) -> bool {
+        self.request_in_treatment
+    }
+
+    /// Give the results of the requests executed since the last call, one for each request.
+    pub fn take_results(&mut self) -> Vec<QueryResult> {
+        std::mem::take(&mut self.results)
+    }
     
     fn build_treaters() -> Vec<Box<dyn Request>> {
-        vec!(CreateReq::new(), DropReq::new(), ResetReq::new(), InsertReq::new(), SelectReq::new(), SetReq::new(), DeleteReq::new())
+        vec!(CreateReq::new(), DropReq::new(), ResetReq::new(), InsertReq::new(), SelectReq::new(), SetReq::new(), DeleteReq::new(), BeginReq::new(), CommitReq::new(), RollbackReq::new(), VacuumReq::new())
     }
 
     fn build_keyword_link() -> HashMap::<String, usize> {
         let mut res = HashMap::<String, usize>::new();
-        for (i, kw) in Vec::from(["CREATE", "DROP", "RESET", "INSERT", "SELECT", "SET", "DELETE"]).iter().enumerate() {
+        for (i, kw) in Vec::from(["CREATE", "DROP", "RESET", "INSERT", "SELECT", "SET", "DELETE", "BEGIN", "COMMIT", "ROLLBACK", "VACUUM"]).iter().enumerate() {
             res.insert(String::from(*kw), i);
         }
         res
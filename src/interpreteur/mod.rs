@@ -2,5 +2,16 @@ mod include;
 mod expression_evaluator;
 mod string_builder;
 mod stack;
+mod index;
+mod foreign_key;
+mod temporal;
+mod wal;
+mod table_file;
+mod heap_file;
+mod buffer_pool;
+#[cfg(test)]
+pub(crate) mod test_dir;
+mod aggregator;
 pub mod interpreteur;
 mod requests;
+pub mod query_result;
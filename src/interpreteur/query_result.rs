@@ -0,0 +1,115 @@
+use super::include::{JsonValue, json};
+pub use super::include::{Type, Value};
+use std::fmt;
+
+/// The rows returned by a request, with the name and the type of each column.
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    columns: Vec<String>,
+    types: Vec<Type>,
+    rows: Vec<Vec<Value>>
+}
+
+impl QueryResult {
+
+    /// Result of a request which doesn't return any row.
+    pub fn empty() -> QueryResult {
+        QueryResult::new(Vec::new(), Vec::new())
+    }
+
+    pub fn new(columns: Vec<String>, types: Vec<Type>) -> QueryResult {
+        QueryResult {
+            columns,
+            types,
+            rows: Vec::new()
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<Value>) {
+        self.rows.push(row)
+    }
+
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    pub fn types(&self) -> &Vec<Type> {
+        &self.types
+    }
+
+    pub fn rows(&self) -> &Vec<Vec<Value>> {
+        &self.rows
+    }
+
+    /// Indicate if the request had columns to return, in other words if it was a selection
+    pub fn has_columns(&self) -> bool {
+        !self.columns.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "columns": self.columns,
+            "types": self.types.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+            "rows": self.rows.iter().map(|row| {
+                row.iter().zip(self.types.iter()).map(|(v, t)| v.to_json(*t)).collect::<Vec<_>>()
+            }).collect::<Vec<_>>()
+        })
+    }
+    
+}
+
+impl fmt::Display for QueryResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.columns.join(" | "))?;
+        for row in self.rows.iter() {
+            writeln!(f, "{}", row.iter().map(|v| v.string().clone()).collect::<Vec<_>>().join(" | "))?;
+        }
+        write!(f, "({} rows)", self.len())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pets() -> QueryResult {
+        let mut result = QueryResult::new(vec![String::from("id"), String::from("name")], vec![Type::Int, Type::String]);
+        result.push_row(vec![Value::new_by_val(1), Value::new_by_pure_string(String::from("Rex"))]);
+        result.push_row(vec![Value::new_by_val(2), Value::new_by_pure_string(String::from("Felix"))]);
+        result
+    }
+
+    #[test]
+    fn rows_are_kept_with_the_columns_and_their_types() {
+        let result = pets();
+        assert!(result.has_columns());
+        assert_eq!(result.columns(), &vec![String::from("id"), String::from("name")]);
+        assert_eq!(result.types(), &vec![Type::Int, Type::String]);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.rows()[1][1].string(), "Felix");
+        assert_eq!(result.to_json(), json!({
+            "columns": ["id", "name"],
+            "types": ["INT", "STRING"],
+            "rows": [[1, "Rex"], [2, "Felix"]]
+        }));
+        assert_eq!(result.to_string(), "id | name\n1 | Rex\n2 | Felix\n(2 rows)");
+    }
+
+    #[test]
+    fn requests_without_selection_have_an_empty_result() {
+        let result = QueryResult::empty();
+        assert!(!result.has_columns());
+        assert!(result.is_empty());
+        assert_eq!(result.to_json(), json!({"columns": [], "types": [], "rows": []}));
+    }
+
+}
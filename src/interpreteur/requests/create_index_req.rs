@@ -0,0 +1,53 @@
+use crate::interpreteur::include::*;
+
+/// Handle the CREATE INDEX request, the create request redirects its tokens here after the INDEX keyword
+pub struct CreateIndexReq {
+    index_name: String,
+    table_name: String,
+    column: String
+}
+
+impl Request for CreateIndexReq {
+
+    fn new() -> BoxedReq {
+        Box::from(CreateIndexReq::pure_new())
+    }
+
+    fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        let index_name = std::mem::take(&mut self.index_name);
+        let table_name = std::mem::take(&mut self.table_name);
+        let column = std::mem::take(&mut self.column);
+        database.create_index(index_name, &table_name, column)
+    }
+
+    fn consume(&mut self, _database: &mut Database, token: Token) -> ConsumeResult {
+        match token.token_type {
+            TokenType::Ident => self.new_ident(token.content),
+            _ => self.bad_token(token, "create index")?
+        }
+        Ok(())
+    }
+
+}
+
+impl CreateIndexReq {
+
+    pub fn pure_new() -> CreateIndexReq {
+        CreateIndexReq {
+            index_name: String::new(),
+            table_name: String::new(),
+            column: String::new()
+        }
+    }
+
+    fn new_ident(&mut self, name: String) {
+        if self.index_name.is_empty() {
+            self.index_name = name;
+        } else if self.table_name.is_empty() {
+            self.table_name = name;
+        } else {
+            self.column = name;
+        }
+    }
+
+}
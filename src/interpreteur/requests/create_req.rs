@@ -1,12 +1,17 @@
 use crate::interpreteur::include::*;
+use super::create_index_req::CreateIndexReq;
 
-/// Handle the CREATE TABLE request
+/// Handle the CREATE TABLE request, and redirect the CREATE INDEX request to its own treater
 pub struct CreateReq {
     table: Option<Table>,
     current_col: Option<Column>,
     pkey_exists: bool,
+    clause: String,
     expr: ExpressionEvaluator,
-    string_builder: StringBuilder
+    check: ExpressionEvaluator,
+    string_builder: StringBuilder,
+    redirect: bool,
+    index_req: CreateIndexReq
 }
 
 
@@ -17,31 +22,53 @@ impl Request for CreateReq {
             table: Some(Table::new()),
             current_col: Some(Column::new_empty()),
             pkey_exists: false,
+            clause: String::new(),
             expr: ExpressionEvaluator::new(),
-            string_builder: StringBuilder::new()
+            check: ExpressionEvaluator::new(),
+            string_builder: StringBuilder::new(),
+            redirect: false,
+            index_req: CreateIndexReq::pure_new()
         })
     }
 
     fn end(&mut self, database: &mut Database) -> ConsumeResult{
+        if self.redirect {
+            self.redirect = false;
+            return self.index_req.end(database)
+        }
         if !self.pkey_exists {
-            return Err(format!("Error during the creation of the table {}, you didn' indicate a primary key", self.table().name()))
+            return Err(IrisError::InvalidRequest(format!("Error during the creation of the table {}, you didn't indicate a primary key", self.table().name())))
         }
-        self.push_col();
-        database.add_table(self.table.take().expect("Create: Failed to unwrap the final table during the end method"));
+        self.push_col()?;
+        self.check_constraint_columns()?;
+        self.check_foreign_keys(database)?;
+        self.check_auto_increment()?;
+        database.add_table(self.table.take().expect("Create: Failed to unwrap the final table during the end method"))?;
         self.table = Some(Table::new());
         self.pkey_exists = false;
         Ok(())
     }
     
     fn consume(&mut self, database: &mut Database, token: Token) -> ConsumeResult {
+        if self.redirect {
+            return self.index_req.consume(database, token)
+        }
+        if token.token_type != TokenType::Symbol || token.flag != Flag::NoFlag {
+            self.push_string();
+        }
         match token.token_type {
+            TokenType::Ident if self.clause == "CHECK" => self.check.new_field(token.content),
+            TokenType::Ident if self.clause == "REFERENCES" => self.new_reference(token.content),
             TokenType::Ident => self.new_ident(database, token.content)?,
+            TokenType::Keyword if token.flag == Flag::NoFlag && token.content != "INDEX" => self.current_expr().new_keyword(&token.content)?,
             TokenType::Keyword => self.new_keyword(token.content)?,
-            TokenType::Type => self.new_type(token.content),
-            TokenType::Operator => self.expr.new_operator(token.content),
-            TokenType::Number => self.expr.new_number(token.content),
+            TokenType::Type => self.new_type(token.content)?,
+            TokenType::Operator => self.current_expr().new_operator(token.content)?,
+            TokenType::Number => self.current_expr().new_number(token.content)?,
+            TokenType::Symbol if token.flag == Flag::Parenthesis => self.current_expr().new_operator(token.content)?,
+            TokenType::Symbol if token.flag == Flag::Comma => self.push_col()?,
             TokenType::Symbol => self.new_char(token.content),
-            _ => self.panic_bad_token(token, "create")
+            _ => self.bad_token(token, "create")?
         }
         Ok(())
     }
@@ -67,38 +94,76 @@ impl CreateReq {
         self.current_col.as_mut().expect("Create: Failed to unwrap the column when calling col_mut method")
     }
 
-    fn extract_col(&mut self) -> Column {
-        let mut result = self.current_col.take().unwrap();
-        if !self.expr.is_empty() {
-            result.set_default_value(self.expr.compute(&Map::new(), true)); 
-        } else if !self.string_builder.is_empty() {
-            result.set_value_by_string(&mut self.string_builder)
+    /// The expression in construction, the one of the CHECK constraint once it started
+    fn current_expr(&mut self) -> &mut ExpressionEvaluator {
+        if self.clause == "CHECK" {
+            &mut self.check
+        } else {
+            &mut self.expr
         }
+    }
+
+    /// Give the string being read to the expression of the DEFAULT value or of the CHECK constraint
+    fn push_string(&mut self) {
+        if ["DEFAULT", "CHECK"].contains(&self.clause.as_str()) && !self.string_builder.is_empty() {
+            let string = self.string_builder.extract();
+            self.current_expr().new_string(string);
+        }
+    }
+
+    /// Give to the current column the DEFAULT value or the CHECK constraint which was being declared
+    fn close_clause(&mut self) -> ConsumeResult {
+        self.push_string();
+        match &self.clause as &str {
+            "DEFAULT" => {
+                let default_value = self.expr.evaluate(&Map::new(), true)?;
+                let column_type = self.col().get_type();
+                if !column_type.accepts(&default_value) {
+                    return Err(IrisError::TypeMismatch(format!("The column {} of type {column_type} can't have {default_value} as default value.", self.col().name())))
+                }
+                if !default_value.is_null() {
+                    self.col_mut().set_default_value(Value::from_json(&column_type.cast(default_value).into())?);
+                }
+            },
+            "CHECK" => {
+                let check = std::mem::replace(&mut self.check, ExpressionEvaluator::new());
+                self.col_mut().set_check(check)?;
+            }
+            _ => ()
+        }
+        self.clause.clear();
+        Ok(())
+    }
+
+    fn extract_col(&mut self) -> Result<Column, IrisError> {
+        self.close_clause()?;
+        let result = self.current_col.take().unwrap();
         self.current_col = Some(Column::new_empty());
-        result
+        Ok(result)
     }
     
     fn new_ident(&mut self, database: &Database, name: String) -> ConsumeResult {
         if !self.table().has_name() {
             if database.table_exists(&name) {
-                return Err(format!("The table {name} already exists."))
+                return Err(IrisError::InvalidRequest(format!("The table {name} already exists.")))
             }
             self.table_mut().set_name(name);
         } else {
-            self.push_col();
+            self.push_col()?;
             if self.table().column_exists(&name) {
-                return Err(format!("You declared the column {name} twice for the table {}", self.table().name()))
+                return Err(IrisError::InvalidRequest(format!("You declared the column {name} twice for the table {}", self.table().name())))
             }
             self.col_mut().set_name(name)
         }
         Ok(())
     }
 
-    fn push_col(&mut self) {
+    fn push_col(&mut self) -> ConsumeResult {
          if !self.col().is_empty() {
-             let col = self.extract_col();
+             let col = self.extract_col()?;
              self.table_mut().add_column(col);
          }
+         Ok(())
     }
     
     fn pkey_exists(&self) -> bool {
@@ -107,7 +172,7 @@ impl CreateReq {
 
     fn def_pkey(&mut self, p_key: String) -> ConsumeResult {
         if self.pkey_exists() {
-            Err(format!("You defined a primary key twice for the table {}", self.table().name()))
+            Err(IrisError::InvalidRequest(format!("You defined a primary key twice for the table {}", self.table().name())))
         } else {
             self.table_mut().set_pkey(p_key);
             self.pkey_exists = true;
@@ -116,15 +181,82 @@ impl CreateReq {
     }
     
     fn new_keyword(&mut self, kw: String) -> ConsumeResult {
+        self.close_clause()?;
         match &kw as &str {
             "PRIMARY" => self.def_pkey(self.col().name().clone())?,
-            _ => panic!("Unknow keyword: {kw}")
+            "AUTOINCREMENT" => self.table_mut().set_auto_increment(),
+            "INDEX" => self.redirect = true,
+            "NOT" => self.col_mut().set_not_null(),
+            "UNIQUE" => self.col_mut().set_unique(),
+            "DEFAULT" | "CHECK" => self.clause = kw,
+            "REFERENCES" => {
+                self.col_mut().set_foreign_key(ForeignKey::new());
+                self.clause = kw
+            },
+            "RESTRICT" | "CASCADE" | "SET" => if let (Some(foreign_key), Some(on_delete)) = (self.col_mut().foreign_key_mut(), OnDelete::from_keyword(&kw)) {
+                foreign_key.set_on_delete(on_delete)
+            },
+            _ => return Err(IrisError::Syntax(format!("Unexpected keyword in the create request: {kw}")))
+        }
+        Ok(())
+    }
+
+    /// Refuse the CHECK constraints using a column which doesn't exist in the table
+    fn check_constraint_columns(&self) -> ConsumeResult {
+        let table = self.table();
+        for column in table.get_cols().values() {
+            for field in column.check().map(|check| check.fields()).unwrap_or_default() {
+                if !table.column_exists(field) {
+                    return Err(IrisError::UnknownColumn { table: table.name().clone(), column: field.clone() })
+                }
+            }
         }
         Ok(())
     }
 
-    fn new_type(&mut self, type_string: String) {
-        self.col_mut().set_type(from_string_to_type(type_string))
+    /// Refuse an AUTOINCREMENT primary key which is not an INT
+    fn check_auto_increment(&self) -> ConsumeResult {
+        let table = self.table();
+        let p_key = table.get_column(table.p_key())?;
+        if table.is_auto_increment() && p_key.get_type() != Type::Int {
+            return Err(IrisError::TypeMismatch(format!("The primary key {} of type {} can't be AUTOINCREMENT, only an INT can.", p_key.name(), p_key.get_type())))
+        }
+        Ok(())
+    }
+
+    /// The referenced table, then the referenced column of the foreign key being declared
+    fn new_reference(&mut self, name: String) {
+        if let Some(foreign_key) = self.col_mut().foreign_key_mut() {
+            foreign_key.new_ident(name)
+        }
+    }
+
+    /// Refuse the foreign keys which don't reference the primary key or a UNIQUE column of the same type,
+    /// the table can reference itself.
+    fn check_foreign_keys(&self, database: &Database) -> ConsumeResult {
+        let table = self.table();
+        for column in table.get_cols().values() {
+            let Some(foreign_key) = column.foreign_key() else {
+                continue
+            };
+            let referenced_table = if foreign_key.table() == table.name() { table } else { database.get_table(foreign_key.table())? };
+            let referenced = referenced_table.get_column(foreign_key.column())?;
+            if referenced.name() != referenced_table.p_key() && !referenced.is_unique() {
+                return Err(IrisError::InvalidRequest(format!("The column {} of the table {} can't be referenced, it is neither its primary key nor UNIQUE.", referenced.name(), referenced_table.name())))
+            }
+            if referenced.get_type() != column.get_type() {
+                return Err(IrisError::TypeMismatch(format!("The column {} of type {} can't reference the column {} of type {}.", column.name(), column.get_type(), referenced.name(), referenced.get_type())))
+            }
+            if foreign_key.on_delete() == OnDelete::SetNull && (column.is_not_null() || column.name() == table.p_key()) {
+                return Err(IrisError::InvalidRequest(format!("The column {} of the table {} can't be NULL, it can't be SET NULL on delete.", column.name(), table.name())))
+            }
+        }
+        Ok(())
+    }
+
+    fn new_type(&mut self, type_string: String) -> ConsumeResult {
+        self.col_mut().set_type(from_string_to_type(type_string)?);
+        Ok(())
     }
 
     fn new_char(&mut self, c: String) {
@@ -3,18 +3,27 @@ use super::from_where_req::FromWhereReq;
 
 pub struct DeleteReq {
     from_where: FromWhereReq,
-    delete_stack: Stack<usize>
+    deleted_lines: Vec<usize>
 }
 
 impl BrowserReq for DeleteReq {
 
-    fn browse_action(&mut self, _line: &mut Map::<String, JsonValue>, line_number: usize) {
-        self.delete_stack.push(line_number)
+    fn browse_action(&mut self, _line: &mut Map::<String, JsonValue>, line_number: usize) -> ConsumeResult {
+        self.deleted_lines.push(line_number);
+        Ok(())
     }
     
     fn get_expr(&mut self) -> &mut ExpressionEvaluator {
         self.from_where.get_where_expr()
     }
+
+    fn get_limit(&self) -> Option<usize> {
+        self.from_where.limit()
+    }
+
+    fn get_offset(&self) -> usize {
+        self.from_where.offset()
+    }
 }
 
 impl Request for DeleteReq {
@@ -22,15 +31,16 @@ impl Request for DeleteReq {
     fn new() -> BoxedReq {
         Box::from(DeleteReq {
             from_where: FromWhereReq::pure_new(),
-            delete_stack: Stack::new()
+            deleted_lines: Vec::new()
         })
     }
 
     fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        self.from_where.refuse_select_clauses("delete")?;
         self.from_where.push_last_string();
-        let table = database.get_table_mut(self.from_where.table_name());
-        table.browse(self);
-        table.drop_lines(&mut self.delete_stack);
+        let table_name = self.from_where.table_name().clone();
+        database.get_table_mut(&table_name)?.browse(self)?;
+        database.delete_lines(&table_name, std::mem::take(&mut self.deleted_lines))?;
         self.from_where.end(database)
     }
     
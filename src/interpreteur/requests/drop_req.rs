@@ -1,21 +1,28 @@
 use crate::interpreteur::include::*;
 
-pub struct DropReq;
+/// Handle the DROP TABLE and DROP INDEX requests
+pub struct DropReq {
+    index: bool
+}
 
 impl Request for DropReq {
 
     fn new() -> BoxedReq {
-        Box::from(DropReq)
+        Box::from(DropReq {
+            index: false
+        })
     }
 
     fn end(&mut self, _database: &mut Database) -> ConsumeResult {
+        self.index = false;
         Ok(())
     }
     
     fn consume(&mut self, database: &mut Database, token: Token) -> ConsumeResult {
         match token.token_type {
             TokenType::Ident => self.new_ident(database, token.content)?,
-            _ => self.panic_bad_token(token, "drop")
+            TokenType::Keyword if token.content == "INDEX" => self.index = true,
+            _ => self.bad_token(token, "drop")?
         }
         Ok(())
     }
@@ -26,11 +33,11 @@ impl Request for DropReq {
 impl DropReq {
 
     fn new_ident(&self, database: &mut Database, name: String) -> ConsumeResult {
-        if !database.table_exists(&name) {
-            return Err(format!("Error during drop request: The table {name} doesn't exists"))
+        if self.index {
+            database.drop_index(&name)
+        } else {
+            database.delete_table(&name)
         }
-        database.delete_table(&name);
-        Ok(())
     }
     
 }
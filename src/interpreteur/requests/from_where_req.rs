@@ -1,11 +1,30 @@
 use crate::interpreteur::include::*;
 
+/// A table joined to the previous ones in the FROM clause
+#[derive(Clone)]
+struct JoinedTable {
+    table: String,
+    alias: String,
+    left: bool,
+    on: ExpressionEvaluator
+}
 
 pub struct FromWhereReq {
     table_name: String,
+    alias: String,
+    joins: Vec<JoinedTable>,
+    left: bool,
     expr: ExpressionEvaluator,
     string_builder: StringBuilder,
-    where_passed: bool,
+    clause: String,
+    order_by: Vec<(String, bool)>,
+    limit: Option<usize>,
+    offset: usize,
+    group_by: Vec<String>,
+    having: ExpressionEvaluator,
+    having_fields: Vec<String>,
+    having_aggregates: Vec<Aggregate>,
+    function: Option<String>
 }
 
 impl Request for FromWhereReq {
@@ -16,23 +35,36 @@ impl Request for FromWhereReq {
 
     fn end(&mut self, _database: &mut Database) -> ConsumeResult {
         self.table_name.clear();
-        self.where_passed = false;
+        self.alias.clear();
+        self.joins.clear();
+        self.left = false;
+        self.clause.clear();
+        self.order_by.clear();
+        self.limit = None;
+        self.offset = 0;
+        self.group_by.clear();
+        self.having.clear();
+        self.having_fields.clear();
+        self.having_aggregates.clear();
+        self.function = None;
         self.expr.clear();
         Ok(())
     }
-    
+
     fn consume(&mut self, database: &mut Database, token: Token) -> ConsumeResult {
         match token.token_type {
             TokenType::Ident => self.new_ident(token.content, database)?,
-            TokenType::Operator => self.new_operator(token.content),
-            TokenType::Number => self.expr.new_number(token.content),
+            TokenType::Operator => self.new_operator(token.content)?,
+            TokenType::Symbol if self.function.is_some() => self.new_aggregate(token.content)?,
+            TokenType::Number => self.new_number(token.content)?,
+            TokenType::Symbol if token.flag == Flag::Parenthesis => self.new_operator(token.content)?,
             TokenType::Symbol => self.new_char(token.content),
-            TokenType::Keyword => self.new_keyword(token.content),
-            _ => self.panic_bad_token(token, "from where")
+            TokenType::Keyword => self.new_keyword(token.content)?,
+            _ => self.bad_token(token, "from where")?
         }
         Ok(())
     }
-    
+
 }
 
 
@@ -41,48 +73,263 @@ impl FromWhereReq {
     pub fn pure_new() -> FromWhereReq {
         FromWhereReq {
             table_name: String::new(),
+            alias: String::new(),
+            joins: Vec::new(),
+            left: false,
             expr: ExpressionEvaluator::new(),
-            where_passed: false,
+            clause: String::new(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: 0,
+            group_by: Vec::new(),
+            having: ExpressionEvaluator::new(),
+            having_fields: Vec::new(),
+            having_aggregates: Vec::new(),
+            function: None,
             string_builder: StringBuilder::new()
         }
     }
 
     fn new_ident(&mut self, name: String, database: &Database) -> ConsumeResult {
-       if !self.where_passed {
-           self.table_name = name;
-           self.where_passed = true;
-           if !database.table_exists(&self.table_name) {
-               return Err(format!("Error: table {} don't exists.", self.table_name))
-           }
-       } else {
-           if !database.get_table(self.table_name()).column_exists(&name) {
-               return Err(format!("The column {} doesn't exists for the table {}", name, self.table_name()))
-           }
-           self.expr.new_field(name)
-       }
+        if self.function.is_some() {
+            return self.new_aggregate(name)
+        }
+        match &self.clause as &str {
+            "" => if self.table_name.is_empty() {
+                database.get_table(&name)?;
+                self.table_name = name;
+            } else {
+                self.alias = name;
+            },
+            "JOIN" => {
+                let join = self.joins.last_mut().expect("FromWhere: JOIN clause without joined table");
+                if join.table.is_empty() {
+                    database.get_table(&name)?;
+                    join.table = name.clone();
+                }
+                join.alias = name;
+            }
+            _ => {
+                let key = match self.resolve_column(database, &name) {
+                    // the column may belong to a table joined later, refused by check_join_conditions with a clearer error
+                    Err(_) if self.clause == "ON" => name,
+                    key => key?
+                };
+                match &self.clause as &str {
+                    "ORDER" => self.order_by.push((key, false)),
+                    "GROUP" => self.group_by.push(key),
+                    "HAVING" => {
+                        self.having.new_field(key.clone());
+                        self.having_fields.push(key)
+                    }
+                    _ => self.current_expr().new_field(key)
+                }
+            }
+        }
         Ok(())
     }
 
-    fn new_operator(&mut self, op: String) {
+    /// The argument of the aggregate function given just before, a column or *
+    fn new_aggregate(&mut self, argument: String) -> ConsumeResult {
+        let function = self.function.take().expect("FromWhere: new aggregate without function");
+        let aggregate = Aggregate::new(&function, &argument)?;
+        self.having.new_field(aggregate.name());
+        self.having_aggregates.push(aggregate);
+        Ok(())
+    }
+
+    /// The expression in construction, depending on the current clause
+    fn current_expr(&mut self) -> &mut ExpressionEvaluator {
+        match &self.clause as &str {
+            "HAVING" => &mut self.having,
+            "ON" => &mut self.joins.last_mut().expect("FromWhere: ON clause without joined table").on,
+            _ => &mut self.expr
+        }
+    }
+
+    fn new_operator(&mut self, op: String) -> ConsumeResult {
         if !self.string_builder.is_empty() {
             self.push_string();
         }
-        self.expr.new_operator(op);
+        self.current_expr().new_operator(op)
     }
-    
+
     fn new_char(&mut self, c: String) {
         self.string_builder.new_char(c);
     }
 
-    fn new_keyword(&mut self, keyword: String) {
-        if keyword == "WHERE" {
-             self.where_passed = true
+    fn new_keyword(&mut self, keyword: String) -> ConsumeResult {
+        self.push_last_string();
+        match &keyword as &str {
+            "LEFT" => self.left = true,
+            "JOIN" => {
+                self.joins.push(JoinedTable {
+                    table: String::new(),
+                    alias: String::new(),
+                    left: std::mem::take(&mut self.left),
+                    on: ExpressionEvaluator::new()
+                });
+                self.clause = keyword
+            }
+            "ON" => {
+                self.check_aliases()?;
+                self.clause = keyword
+            }
+            "WHERE" | "ORDER" | "LIMIT" | "OFFSET" | "GROUP" | "HAVING" => self.clause = keyword,
+            "IS" | "NOT" | "NULL" | "TRUE" | "FALSE" => self.current_expr().new_keyword(&keyword)?,
+            _ if TEMPORAL_FUNCTIONS.contains(&keyword.as_str()) || TEMPORAL_FIELDS.contains(&keyword.as_str()) => self.current_expr().new_keyword(&keyword)?,
+            "DESC" => if let Some(order) = self.order_by.last_mut() {
+                order.1 = true
+            },
+            _ if AGGREGATE_FUNCTIONS.contains(&keyword.as_str()) => {
+                if self.clause != "HAVING" {
+                    return Err(IrisError::InvalidRequest(format!("The aggregate function {keyword} can only be used in the selected columns and in the HAVING clause.")))
+                }
+                self.function = Some(keyword)
+            }
+            _ => ()
+        }
+        Ok(())
+    }
+
+    fn new_number(&mut self, number: String) -> ConsumeResult {
+        match &self.clause as &str {
+            "LIMIT" => self.limit = Some(parse_count(&number)?),
+            "OFFSET" => self.offset = parse_count(&number)?,
+            _ => self.current_expr().new_number(number)?
         }
+        Ok(())
     }
 
     fn push_string(&mut self) {
-        self.expr.new_direct_number(self.string_builder.hash());
-        self.string_builder.extract();
+        let string = self.string_builder.extract();
+        self.current_expr().new_string(string);
+    }
+
+    /// The name of the main table in the lines, its alias if it has one
+    fn alias(&self) -> &String {
+        if self.alias.is_empty() { &self.table_name } else { &self.alias }
+    }
+
+    /// The tables named in the FROM clause so far, as (alias, table)
+    fn scope(&self) -> Vec<(&String, &String)> {
+        let mut scope = vec!((self.alias(), &self.table_name));
+        scope.extend(self.joins.iter().map(|join| (&join.alias, &join.table)));
+        scope
+    }
+
+    /// Refuse a joined table whose name is already given to a previous table of the FROM clause
+    fn check_aliases(&self) -> ConsumeResult {
+        let scope = self.scope();
+        let (alias, _) = scope[scope.len() - 1];
+        if scope[..scope.len() - 1].iter().any(|(a, _)| *a == alias) {
+            return Err(IrisError::InvalidRequest(format!("The name {alias} is given to two tables of the FROM clause, use an alias to distinguish them.")))
+        }
+        Ok(())
+    }
+
+    /// Find the column designated by the name, qualified by its table or not, and returns its name in the browsed lines:
+    /// table.column when there are joins, the column alone otherwise.
+    pub fn resolve_column(&self, database: &Database, name: &String) -> Result<String, IrisError> {
+        let scope = self.scope();
+        match name.split_once('.') {
+            Some((qualifier, column)) => {
+                let (_, table) = scope.iter().find(|(alias, _)| *alias == qualifier).ok_or_else(|| IrisError::UnknownTable(qualifier.to_string()))?;
+                database.get_table(table)?.get_column(&column.to_string())?;
+                Ok(if self.joins.is_empty() { column.to_string() } else { name.clone() })
+            }
+            None if self.joins.is_empty() => {
+                database.get_table(&self.table_name)?.get_column(name)?;
+                Ok(name.clone())
+            }
+            None => {
+                let owners = scope.iter()
+                    .filter(|(_, table)| database.get_table(table).is_ok_and(|t| t.column_exists(name)))
+                    .collect::<Vec<_>>();
+                match owners.as_slice() {
+                    [(alias, _)] => Ok(format!("{alias}.{name}")),
+                    [] => Err(IrisError::UnknownColumn { table: self.table_name.clone(), column: name.clone() }),
+                    _ => Err(IrisError::InvalidRequest(format!("The column {name} is ambiguous, qualify it with the name of its table.")))
+                }
+            }
+        }
+    }
+
+    /// The type of a column given by its name in the browsed lines
+    pub fn column_type(&self, database: &Database, key: &String) -> Result<Type, IrisError> {
+        if self.joins.is_empty() {
+            return Ok(database.get_table(&self.table_name)?.get_column(key)?.get_type())
+        }
+        let (qualifier, column) = key.split_once('.').expect("FromWhere: unqualified column in a join");
+        let (_, table) = self.scope().into_iter().find(|(alias, _)| *alias == qualifier).ok_or_else(|| IrisError::UnknownTable(qualifier.to_string()))?;
+        Ok(database.get_table(table)?.get_column(&column.to_string())?.get_type())
+    }
+
+    /// Every column of the tables of the FROM clause, named as in the browsed lines
+    pub fn all_columns(&self, database: &Database) -> Result<Vec<String>, IrisError> {
+        if self.joins.is_empty() {
+            return Ok(database.get_table(&self.table_name)?.get_cols().keys().cloned().collect())
+        }
+        let mut columns = Vec::new();
+        for (alias, table) in self.scope() {
+            columns.extend(database.get_table(table)?.get_cols().keys().map(|c| format!("{alias}.{c}")));
+        }
+        Ok(columns)
+    }
+
+    /// Check that the ON clause of each join only uses the tables joined before and the joined table,
+    /// the next tables are not joined yet when it is evaluated
+    fn check_join_scopes(&self, database: &Database) -> ConsumeResult {
+        let scope = self.scope();
+        for (i, join) in self.joins.iter().enumerate() {
+            for field in join.on.fields() {
+                let key = self.resolve_column(database, field)?;
+                let alias = key.split_once('.').map_or(key.as_str(), |(alias, _)| alias);
+                if !scope[..i + 2].iter().any(|(a, _)| *a == alias) {
+                    return Err(IrisError::InvalidRequest(format!("The ON clause of the table {} can't use {field}, the table {alias} is joined after it.", join.alias)))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the types of the WHERE and ON clauses of the joins on a line with a value of the type of each column
+    pub fn check_join_conditions(&mut self, database: &Database) -> ConsumeResult {
+        self.check_join_scopes(database)?;
+        let mut sample = Map::new();
+        for column in self.all_columns(database)? {
+            let column_type = self.column_type(database, &column)?;
+            sample.insert(column, column_type.sample());
+        }
+        for join in self.joins.iter_mut() {
+            join.on.check_condition(&sample)?;
+        }
+        if !self.expr.is_empty() {
+            self.expr.check_condition(&sample)?;
+        }
+        Ok(())
+    }
+
+    pub fn has_joins(&self) -> bool {
+        !self.joins.is_empty()
+    }
+
+    /// The lines produced by the joins which satisfy the WHERE clause, with every column named table.column.
+    /// They are built one at a time by a nested loop reading the lines of the tables when they are needed.
+    pub fn joined_lines<'a>(&self, database: &'a Database) -> Result<JoinedLines<'a>, IrisError> {
+        let mut tables = vec!(database.get_table(&self.table_name)?);
+        for join in self.joins.iter() {
+            tables.push(database.get_table(&join.table)?);
+        }
+        Ok(JoinedLines {
+            tables,
+            aliases: self.scope().into_iter().map(|(alias, _)| alias.clone()).collect(),
+            joins: self.joins.clone(),
+            expr: self.expr.clone(),
+            positions: vec!(0; self.joins.len() + 1),
+            matched: vec!(false; self.joins.len()),
+            lines: Vec::new()
+        })
     }
 
     pub fn table_name(&self) -> &String {
@@ -93,10 +340,143 @@ impl FromWhereReq {
         &mut self.expr
     }
 
+    /// The columns of the ORDER BY clause, with true for a descending order
+    pub fn order_by(&self) -> &[(String, bool)] {
+        &self.order_by
+    }
+
+    /// The maximum number of lines the request works on, None without LIMIT clause
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// The number of matching lines ignored before the request starts working
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn group_by(&self) -> &[String] {
+        &self.group_by
+    }
+
+    /// Indicate if the lines are gathered in groups by a GROUP BY or a HAVING clause
+    pub fn is_grouped(&self) -> bool {
+        !self.group_by.is_empty() || !self.having.is_empty()
+    }
+
+    pub fn get_having_expr(&mut self) -> &mut ExpressionEvaluator {
+        &mut self.having
+    }
+
+    /// The columns used outside of an aggregate function in the HAVING clause
+    pub fn having_fields(&self) -> &[String] {
+        &self.having_fields
+    }
+
+    pub fn having_aggregates(&self) -> &[Aggregate] {
+        &self.having_aggregates
+    }
+
+    /// Refuse the clauses which only make sense for a select
+    pub fn refuse_select_clauses(&self, request: &str) -> ConsumeResult {
+        let clause = if !self.joins.is_empty() {
+            "JOIN"
+        } else if !self.order_by.is_empty() {
+            "ORDER BY"
+        } else if !self.group_by.is_empty() {
+            "GROUP BY"
+        } else if !self.having.is_empty() {
+            "HAVING"
+        } else {
+            return Ok(())
+        };
+        Err(IrisError::InvalidRequest(format!("The {request} request doesn't support {clause}.")))
+    }
+
     pub fn push_last_string(&mut self) {
         if !self.string_builder.is_empty() {
             self.push_string()
         }
     }
-    
+
+}
+
+/// The position of a joined table whose lines were all tried with the current line of the previous tables
+const EXHAUSTED: usize = usize::MAX;
+
+/// The lines produced by the joins of a FROM clause and satisfying its WHERE clause.
+/// Each joined table is browsed again for each line made of the previous tables, a line without match in a LEFT JOIN
+/// is kept with NULL in each column of the joined table.
+pub struct JoinedLines<'a> {
+    tables: Vec<&'a Table>,
+    aliases: Vec<String>,
+    joins: Vec<JoinedTable>,
+    expr: ExpressionEvaluator,
+    /// The next line to read in each table
+    positions: Vec<usize>,
+    /// Indicate if the current line of the previous tables matched a line of each joined table
+    matched: Vec<bool>,
+    /// The line made of the current lines of the first tables, for each table already read
+    lines: Vec<Map<String, JsonValue>>
+}
+
+impl JoinedLines<'_> {
+
+    /// The line of the table with each column renamed alias.column, added to the line made of the previous tables
+    fn combined(&self, depth: usize, line_number: usize) -> Result<Map<String, JsonValue>, IrisError> {
+        let mut combined = self.lines.get(depth.wrapping_sub(1)).cloned().unwrap_or_default();
+        let alias = &self.aliases[depth];
+        combined.extend(self.tables[depth].get_line(line_number)?.into_iter().map(|(c, v)| (format!("{alias}.{c}"), v)));
+        Ok(combined)
+    }
+
+    fn next_line(&mut self) -> Result<Option<Map<String, JsonValue>>, IrisError> {
+        loop {
+            let depth = self.lines.len();
+            if depth == self.tables.len() {
+                let line = self.lines.pop().expect("JoinedLines: no line at the last depth");
+                if self.expr.is_empty() || self.expr.compute(&line, false)? {
+                    return Ok(Some(line))
+                }
+                continue
+            }
+            let position = self.positions[depth];
+            if position == EXHAUSTED {
+                self.positions[depth] = 0;
+                self.lines.pop();
+            } else if position < self.tables[depth].line_count() {
+                self.positions[depth] += 1;
+                let combined = self.combined(depth, position)?;
+                if depth == 0 {
+                    self.lines.push(combined);
+                } else if self.joins[depth - 1].on.compute(&combined, false)? {
+                    self.matched[depth - 1] = true;
+                    self.lines.push(combined);
+                }
+            } else if depth == 0 {
+                return Ok(None)
+            } else {
+                self.positions[depth] = EXHAUSTED;
+                if !std::mem::take(&mut self.matched[depth - 1]) && self.joins[depth - 1].left {
+                    let mut combined = self.lines[depth - 1].clone();
+                    let alias = &self.aliases[depth];
+                    combined.extend(self.tables[depth].get_cols().keys().map(|c| (format!("{alias}.{c}"), JsonValue::Null)));
+                    self.lines.push(combined);
+                }
+            }
+        }
+    }
+
+}
+
+impl Iterator for JoinedLines<'_> {
+    type Item = Result<Map<String, JsonValue>, IrisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_line().transpose()
+    }
+}
+
+fn parse_count(number: &str) -> Result<usize, IrisError> {
+    number.parse::<usize>().map_err(|_| IrisError::InvalidRequest(format!("A LIMIT or an OFFSET is a number of lines, {number} is not one.")))
 }
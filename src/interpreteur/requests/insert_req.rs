@@ -23,19 +23,22 @@ impl Request for InsertReq {
     fn end(&mut self, database: &mut Database) -> ConsumeResult {
         self.save_value(database)?;
         if self.values.len() != self.asked_cols.len() {
-            return Err(format!("Error during the insertion of the table {}, the number of value is less than the number of columns.", self.table_name))
+            return Err(IrisError::InvalidRequest(format!("Error during the insertion of the table {}, the number of value is less than the number of columns.", self.table_name)))
         }
-        let table = database.get_table_mut(&self.table_name);
-        for (_, c) in table.get_cols().iter() {
-            if !c.has_default_value() && !c.flag() {
-                return Err(format!("Error during the insertion of the table {}, the column {} doesn't have a default value and you didn't indicate his value.", self.table_name, c.name()))
-            } else if !c.flag() {
+        let table = database.get_table_mut(&self.table_name)?;
+        for c in table.get_cols().values() {
+            if !c.flag() {
                 self.asked_cols.push(c.name().clone());
-                self.values.push(c.default_value().clone())
+                self.values.push(match table.next_auto_increment() {
+                    Some(key) if c.name() == table.p_key() => Value::new_by_val(key),
+                    _ => c.default_value().cloned().unwrap_or_else(Value::new_null)
+                })
             }
         }
-        table.insert(&self.asked_cols, &self.values);
-        database.get_table_mut(&self.table_name).reset_all_flags();
+        table.reset_all_flags();
+        let line = table.new_line(&self.asked_cols, &self.values)?;
+        database.check_foreign_keys(&self.table_name, &[&line])?;
+        database.modify_table(&self.table_name, |table| table.insert(line))?;
         self.table_name.clear();
         self.asked_cols.clear();
         self.values.clear();
@@ -45,10 +48,17 @@ impl Request for InsertReq {
     fn consume(&mut self, database: &mut Database, token: Token) -> ConsumeResult {
         match token.token_type {
             TokenType::Ident => self.new_ident(token.content, database)?,
-            TokenType::Operator => self.expr.new_operator(token.content),
-            TokenType::Number => self.expr.new_number(token.content),
+            TokenType::Operator => {
+                self.push_string();
+                self.expr.new_operator(token.content)?
+            }
+            TokenType::Number => self.expr.new_number(token.content)?,
+            TokenType::Keyword => {
+                self.push_string();
+                self.expr.new_keyword(&token.content)?
+            }
             TokenType::Symbol => self.new_char(token.content, token.flag, database)?,
-            _ => self.panic_bad_token(token, "drop")
+            _ => self.bad_token(token, "insert")?
         }
         Ok(())
     }
@@ -67,23 +77,18 @@ impl InsertReq {
     }
 
     fn new_col(&mut self, col_name: String, database: &mut Database) -> ConsumeResult {
-        let table = database.get_table_mut(&self.table_name);
+        let table = database.get_table_mut(&self.table_name)?;
         if !table.column_exists_without_flag(&col_name) {
-            return if !table.column_exists(&col_name) {
-                Err(format!("Error during an insertion, the column {col_name} doesn't exists in the table {}.", self.table_name))            
-            } else {
-                Err(format!("Error during an insertion, you asked for the column {col_name} twice."))            
-            }
+            table.get_column(&col_name)?;
+            return Err(IrisError::InvalidRequest(format!("Error during an insertion, you asked for the column {col_name} twice.")))
         } 
-        table.active_column_flag(&col_name);
+        table.active_column_flag(&col_name)?;
         self.asked_cols.push(col_name);
         Ok(())
     }
 
     fn set_table_name(&mut self, table_name: String, database: &Database) -> ConsumeResult {
-        if !database.table_exists(&table_name) {
-            return Err(format!("Error during an insertion, the table {table_name} doesn't exists"))
-        }
+        database.get_table(&table_name)?;
         self.table_name = table_name;
         Ok(())
     }
@@ -91,28 +96,33 @@ impl InsertReq {
     fn new_char(&mut self, c: String, flag: Flag, database: &Database) -> ConsumeResult {
         if flag == Flag::Comma {
             self.save_value(database)?;
+        } else if flag == Flag::Parenthesis {
+            self.push_string();
+            self.expr.new_operator(c)?;
         } else {
             self.string_builder.new_char(c);
         }
         Ok(())
     }
 
+    /// Give the string being read to the expression of the value
+    fn push_string(&mut self) {
+        if !self.string_builder.is_empty() {
+            self.expr.new_string(self.string_builder.extract());
+        }
+    }
+
     fn save_value(&mut self, database: &Database) -> ConsumeResult {
-        if self.asked_cols.len() < self.values.len() {
-            return Err(format!("Error during insert request in the table {}, you put more values than column.", self.table_name))
+        if self.values.len() >= self.asked_cols.len() {
+            return Err(IrisError::InvalidRequest(format!("Error during insert request in the table {}, you put more values than column.", self.table_name)))
         }
-        let column = database.get_table(&self.table_name).get_column(&self.asked_cols[self.values.len()]);
-        if self.string_builder.is_empty() {
-            if column.get_type() == Type::String {
-                return Err(format!("Error during insert request in the table {}, a string was expected for the column {}.", self.table_name, column.name()))
-            }
-            self.values.push(Value::new_by_val(self.expr.compute(&Map::new(), true)));
-        } else {
-            if column.get_type() != Type::String {
-                return Err(format!("Error during insert request in the table {}, the column {} doesn't have the String type.", self.table_name, column.name()))
-            }
-            self.values.push(Value::new_by_string(&mut self.string_builder, false))
+        let column = database.get_table(&self.table_name)?.get_column(&self.asked_cols[self.values.len()])?;
+        self.push_string();
+        let value = self.expr.evaluate(&Map::new(), true)?;
+        if !column.get_type().accepts(&value) {
+            return Err(IrisError::TypeMismatch(format!("Error during insert request in the table {}, the column {} of type {} can't receive {value}.", self.table_name, column.name(), column.get_type())))
         }
+        self.values.push(Value::from_json(&column.get_type().cast(value).into())?);
         Ok(())
     }
     
@@ -1,4 +1,5 @@
 pub mod create_req;
+mod create_index_req;
 pub mod drop_req;
 pub mod reset_req;
 pub mod insert_req;
@@ -6,3 +7,5 @@ pub mod select_req;
 pub mod set_req;
 pub mod delete_req;
 mod from_where_req;
+pub mod transaction_req;
+pub mod vacuum_req;
@@ -9,15 +9,11 @@ impl Request for ResetReq {
     }
 
     fn end(&mut self, database: &mut Database) -> ConsumeResult {
-        database.reset_database();
-        Ok(())
+        database.reset_database()
     }
     
     fn consume(&mut self, _database: &mut Database, token: Token) -> ConsumeResult {
-        match token.token_type {
-            _ => self.panic_bad_token(token, "drop")
-        }
-        Ok(())
+        self.bad_token(token, "reset")
     }
     
 }
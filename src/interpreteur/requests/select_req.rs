@@ -1,12 +1,119 @@
 use crate::interpreteur::include::*;
 use super::from_where_req::FromWhereReq;
 
+/// A call of NOW, DATE_ADD or EXTRACT in the selected columns, computed on each selected line.
+/// Its tokens are kept until the FROM clause is known to find the columns it uses.
+struct ScalarFunction {
+    name: String,
+    tokens: Vec<Token>,
+    depth: usize,
+    in_string: bool,
+    expr: ExpressionEvaluator,
+    result_type: Type
+}
+
+impl ScalarFunction {
+
+    fn new(function: Token) -> ScalarFunction {
+        ScalarFunction {
+            name: function.content.clone(),
+            tokens: vec!(function),
+            depth: 0,
+            in_string: false,
+            expr: ExpressionEvaluator::new(),
+            result_type: Type::Timestamp
+        }
+    }
+
+    /// Indicate if the closing parenthesis of the call was given
+    fn is_complete(&self) -> bool {
+        self.depth == 0 && self.tokens.len() > 1
+    }
+
+    /// Add a token of the call and write it in the name of the column, the one it has in the request
+    fn push(&mut self, token: Token) {
+        let is_char = token.token_type == TokenType::Symbol && token.flag == Flag::NoFlag;
+        if is_char != self.in_string {
+            self.name.push('\'');
+            self.in_string = is_char;
+        }
+        let spaced = !self.name.ends_with(['(', ' ', '-']);
+        match (token.token_type, token.content.as_str()) {
+            _ if is_char => self.name.push_str(&token.content),
+            (TokenType::Symbol, "(") => {
+                self.depth += 1;
+                self.name.push('(')
+            }
+            (TokenType::Symbol, ")") => {
+                self.depth = self.depth.saturating_sub(1);
+                self.name.push(')')
+            }
+            (TokenType::Symbol, ",") => self.name.push_str(", "),
+            (TokenType::Operator, op) if op == NEGATION => self.name.push('-'),
+            (TokenType::Operator, op) => self.name.push_str(&format!(" {op} ")),
+            (TokenType::Keyword, field) if TEMPORAL_FIELDS.contains(&field) => self.name.push_str(&format!("{field} FROM ")),
+            (_, content) if spaced && !TEMPORAL_FUNCTIONS.contains(&content) => self.name.push_str(&format!(" {content}")),
+            (_, content) => self.name.push_str(content)
+        }
+        self.tokens.push(token);
+    }
+
+    /// Build the expression of the call once the columns of the FROM clause are known, and the type of its result
+    fn prepare(&mut self, from_where: &FromWhereReq, database: &Database) -> ConsumeResult {
+        let mut string_builder = StringBuilder::new();
+        self.expr.clear();
+        for token in self.tokens.iter() {
+            let is_char = token.token_type == TokenType::Symbol && token.flag == Flag::NoFlag;
+            if !is_char && !string_builder.is_empty() {
+                self.expr.new_string(string_builder.extract());
+            }
+            match token.token_type {
+                _ if is_char => string_builder.new_char(token.content.clone()),
+                TokenType::Symbol | TokenType::Operator => self.expr.new_operator(token.content.clone())?,
+                TokenType::Keyword => self.expr.new_keyword(&token.content)?,
+                TokenType::Number => self.expr.new_number(token.content.clone())?,
+                TokenType::Ident => self.expr.new_field(from_where.resolve_column(database, &token.content)?),
+                _ => return Err(IrisError::Syntax(format!("Unexpected token {} in the call of {}.", token.content, self.name)))
+            }
+        }
+        self.expr.flush_operators()?;
+        self.result_type = match self.tokens[0].content.as_str() {
+            "EXTRACT" => Type::Int,
+            "DATE_ADD" => match self.expr.fields().first() {
+                Some(field) => from_where.column_type(database, field)?,
+                None => Type::Date
+            },
+            _ => Type::Timestamp
+        };
+        Ok(())
+    }
+
+    /// The result of the call on the line, a temporal value gives its type to the column
+    fn compute(&mut self, line: &Map<String, JsonValue>) -> Result<JsonValue, IrisError> {
+        let result = self.expr.evaluate(line, false)?;
+        self.result_type = match result {
+            Operand::Temporal(Temporal::Date(_)) => Type::Date,
+            Operand::Temporal(Temporal::Time(_)) => Type::Time,
+            Operand::Temporal(Temporal::Timestamp(_)) => Type::Timestamp,
+            _ => self.result_type
+        };
+        Ok(result.into())
+    }
+
+}
+
 pub struct SelectReq {
     table_name: String,
     asked_cols: Vec<String>,
+    row_keys: Vec<String>,
+    aggregates: Vec<Aggregate>,
+    scalars: Vec<ScalarFunction>,
+    function: Option<String>,
     redirect: bool,
     from_where: FromWhereReq,
-    result: Vec<Vec<JsonValue>>
+    aggregator: Aggregator,
+    result: Vec<(Vec<JsonValue>, Vec<JsonValue>)>,
+    query_result: Option<QueryResult>
 }
 
 impl Request for SelectReq {
@@ -15,36 +122,61 @@ impl Request for SelectReq {
         Box::from(SelectReq {
             table_name: String::new(),
             asked_cols: Vec::new(),
+            row_keys: Vec::new(),
+            aggregates: Vec::new(),
+            scalars: Vec::new(),
+            function: None,
             redirect: false,
             from_where: FromWhereReq::pure_new(),
-            result: Vec::new()
+            aggregator: Aggregator::new(),
+            result: Vec::new(),
+            query_result: None
         })
     }
 
     fn end(&mut self, database: &mut Database) -> ConsumeResult {
         self.from_where.push_last_string();
         if self.asked_cols.contains(&ALL_INDICATOR.to_string()) {
-            self.fill_asked_cols(database);
+            self.fill_asked_cols(database)?;
+        }
+        self.check_columns(database)?;
+        if self.from_where.has_joins() {
+            self.browse_joins(database)?;
         } else {
-            database.test_column_existance(self.from_where.table_name(), &self.asked_cols)?;
+            database.get_table_mut(self.from_where.table_name())?.browse(self)?;
         }
-        database.get_table_mut(self.from_where.table_name()).browse(self);
+        if self.is_aggregation() {
+            self.aggregate()?;
+        }
+        self.sort_result();
+        self.query_result = Some(self.build_result(database)?);
         self.from_where.end(database)?;
         self.table_name.clear();
         self.asked_cols.clear();
+        self.row_keys.clear();
+        self.aggregates.clear();
+        self.scalars.clear();
         self.redirect = false;
         self.result.clear();
         Ok(())
     }
+
+    fn take_result(&mut self) -> Option<QueryResult> {
+        self.query_result.take()
+    }
     
     fn consume(&mut self, database: &mut Database, token: Token) -> ConsumeResult {
         if self.redirect {
             self.from_where.consume(database, token)?;
         } else {
             match token.token_type {
+                _ if self.scalars.last().is_some_and(|scalar| !scalar.is_complete()) => self.new_scalar_token(token),
+                TokenType::Keyword if TEMPORAL_FUNCTIONS.contains(&token.content.as_str()) => self.scalars.push(ScalarFunction::new(token)),
+                TokenType::Keyword if AGGREGATE_FUNCTIONS.contains(&token.content.as_str()) => self.function = Some(token.content),
+                TokenType::Ident | TokenType::Symbol if self.function.is_some() => self.new_aggregate(token.content)?,
                 TokenType::Ident | TokenType::Symbol => self.asked_cols.push(token.content),
                 TokenType::Keyword => self.redirect = true,
-                _ => self.panic_bad_token(token, "select")
+                _ => self.bad_token(token, "select")?
             }
         }
         Ok(())
@@ -54,24 +186,191 @@ impl Request for SelectReq {
 
 impl SelectReq {
 
-    fn fill_asked_cols(&mut self, database: &Database) {
-        self.asked_cols = database.get_table(self.from_where.table_name()).get_cols().keys().map(|c| c.clone()).collect::<_>();
+    fn fill_asked_cols(&mut self, database: &Database) -> ConsumeResult {
+        self.asked_cols = self.from_where.all_columns(database)?;
+        Ok(())
+    }
+
+    /// Browse the lines produced by the joins which satisfy the WHERE clause
+    fn browse_joins(&mut self, database: &Database) -> ConsumeResult {
+        self.from_where.check_join_conditions(database)?;
+        for (i, line) in self.from_where.joined_lines(database)?.enumerate() {
+            self.browse_action(&mut line?, i)?;
+        }
+        Ok(())
+    }
+
+    /// The argument of the aggregate function given just before, a column or *
+    fn new_aggregate(&mut self, argument: String) -> ConsumeResult {
+        let function = self.function.take().expect("Select: new aggregate without function");
+        let aggregate = Aggregate::new(&function, &argument)?;
+        self.asked_cols.push(aggregate.name());
+        self.aggregates.push(aggregate);
+        Ok(())
+    }
+
+    /// A token of the call of a temporal function, the call becomes a selected column once it is complete
+    fn new_scalar_token(&mut self, token: Token) {
+        if let Some(scalar) = self.scalars.last_mut() {
+            scalar.push(token);
+            if scalar.is_complete() {
+                self.asked_cols.push(scalar.name.clone());
+            }
+        }
+    }
+
+    fn get_scalar(&self, name: &String) -> Option<&ScalarFunction> {
+        self.scalars.iter().find(|scalar| scalar.name == *name)
+    }
+
+    /// Indicate if the lines are replaced by the groups computed by the aggregator
+    fn is_aggregation(&self) -> bool {
+        !self.aggregates.is_empty() || !self.from_where.having_aggregates().is_empty() || self.from_where.is_grouped()
+    }
+
+    fn get_aggregate(&self, name: &String) -> Option<&Aggregate> {
+        self.aggregates.iter().find(|a| a.name() == *name)
+    }
+
+    /// Check that every column used by the request exists, and when the lines are grouped, that they are grouped on it.
+    fn check_columns(&mut self, database: &Database) -> ConsumeResult {
+        for aggregate in self.aggregates.iter_mut() {
+            resolve_aggregate(aggregate, &self.from_where, database)?;
+        }
+        let mut aggregates = self.aggregates.clone();
+        for aggregate in self.from_where.having_aggregates() {
+            let mut aggregate = aggregate.clone();
+            resolve_aggregate(&mut aggregate, &self.from_where, database)?;
+            aggregates.push(aggregate);
+        }
+        for scalar in self.scalars.iter_mut() {
+            scalar.prepare(&self.from_where, database)?;
+        }
+        self.row_keys.clear();
+        for col in self.asked_cols.iter() {
+            self.row_keys.push(match self.get_aggregate(col) {
+                Some(aggregate) => aggregate.name(),
+                None if self.get_scalar(col).is_some() => col.clone(),
+                None => self.from_where.resolve_column(database, col)?
+            });
+        }
+        if !self.is_aggregation() {
+            return Ok(())
+        }
+        let group_by = self.from_where.group_by();
+        let used_cols = self.asked_cols.iter().zip(self.row_keys.iter()).filter(|(c, _)| self.get_aggregate(c).is_none() && self.get_scalar(c).is_none())
+            .map(|(_, key)| key)
+            .chain(self.scalars.iter().flat_map(|scalar| scalar.expr.fields()))
+            .chain(self.from_where.order_by().iter().map(|(c, _)| c))
+            .chain(self.from_where.having_fields().iter());
+        for col in used_cols {
+            if !group_by.contains(col) {
+                return Err(IrisError::InvalidRequest(format!("The column {col} must appear in the GROUP BY clause or be used in an aggregate function.")))
+            }
+        }
+        self.aggregator.init(group_by.to_vec(), aggregates);
+        Ok(())
+    }
+
+    /// Replace the browsed lines by one line for each group satisfying the HAVING clause.
+    fn aggregate(&mut self) -> ConsumeResult {
+        for line in self.aggregator.take_groups()? {
+            let having = self.from_where.get_having_expr();
+            if having.is_empty() || having.compute(&line, false)? {
+                self.push_line(&line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push_line(&mut self, line: &Map<String, JsonValue>) -> ConsumeResult {
+        let mut extraction = Vec::<JsonValue>::new();
+        for key in self.row_keys.iter() {
+            extraction.push(match self.scalars.iter_mut().find(|scalar| scalar.name == *key) {
+                Some(scalar) => scalar.compute(line)?,
+                None => line.get(key).cloned().unwrap_or_default()
+            });
+        }
+        let keys = self.from_where.order_by().iter().map(|(col, _)| line.get(col).cloned().unwrap_or_default()).collect();
+        self.result.push((extraction, keys));
+        Ok(())
+    }
+
+    fn build_result(&mut self, database: &Database) -> Result<QueryResult, IrisError> {
+        let mut types = Vec::new();
+        for (c, key) in self.asked_cols.iter().zip(self.row_keys.iter()) {
+            types.push(match (self.get_aggregate(c), self.get_scalar(c)) {
+                (Some(aggregate), _) => aggregate.result_type(),
+                (_, Some(scalar)) => scalar.result_type,
+                _ => self.from_where.column_type(database, key)?
+            });
+        }
+        let mut result = QueryResult::new(self.asked_cols.clone(), types);
+        for (line, _) in self.result.drain(..) {
+            result.push_row(line.iter().map(Value::from_json).collect::<Result<_, _>>()?);
+        }
+        Ok(result)
+    }
+
+    /// Indicate if the LIMIT and OFFSET clauses can only be applied once every line is browsed, because they are sorted, grouped or joined.
+    fn limit_after_browse(&self) -> bool {
+        !self.from_where.order_by().is_empty() || self.is_aggregation() || self.from_where.has_joins()
+    }
+
+    /// Sort the lines according to the ORDER BY clause, the lines equal on every column keep their order,
+    /// then keep only the lines asked by the LIMIT and OFFSET clauses if the browse couldn't do it.
+    fn sort_result(&mut self) {
+        let order_by = self.from_where.order_by();
+        if !order_by.is_empty() {
+            self.result.sort_by(|(_, keys1), (_, keys2)| {
+                order_by.iter().zip(keys1.iter().zip(keys2.iter()))
+                    .map(|((_, desc), (k1, k2))| {
+                        let ordering = compare_json_values(k1, k2);
+                        if *desc { ordering.reverse() } else { ordering }
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        if self.limit_after_browse() {
+            let limit = self.from_where.limit().unwrap_or(usize::MAX);
+            self.result = self.result.drain(..).skip(self.from_where.offset()).take(limit).collect();
+        }
     }
     
 }
 
 impl BrowserReq for SelectReq {
 
-    fn browse_action(&mut self, line: &mut Map::<String, JsonValue>, _line_number: usize) {
-        let mut extraction = Vec::<JsonValue>::new();
-        for col in self.asked_cols.iter() {
-            extraction.push(line[col].clone());
+    fn browse_action(&mut self, line: &mut Map::<String, JsonValue>, _line_number: usize) -> ConsumeResult {
+        if self.is_aggregation() {
+            self.aggregator.add_line(line);
+        } else {
+            self.push_line(line)?;
         }
-        self.result.push(extraction);
+        Ok(())
     }
 
     fn get_expr(&mut self) -> &mut ExpressionEvaluator {
         self.from_where.get_where_expr()
     }
+
+    fn get_limit(&self) -> Option<usize> {
+        if self.limit_after_browse() { None } else { self.from_where.limit() }
+    }
+
+    fn get_offset(&self) -> usize {
+        if self.limit_after_browse() { 0 } else { self.from_where.offset() }
+    }
     
 }
+
+/// Give to the aggregate the name and the type of its column in the browsed lines
+fn resolve_aggregate(aggregate: &mut Aggregate, from_where: &FromWhereReq, database: &Database) -> ConsumeResult {
+    if let Some(column) = aggregate.column() {
+        let key = from_where.resolve_column(database, column)?;
+        let column_type = from_where.column_type(database, &key)?;
+        aggregate.set_column_key(key, column_type)?;
+    }
+    Ok(())
+}
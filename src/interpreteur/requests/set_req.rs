@@ -4,6 +4,7 @@ use super::from_where_req::FromWhereReq;
 struct Update {
     column: String,
     operator: String,
+    column_type: Type,
     expr: ExpressionEvaluator,
     string_builder: StringBuilder
 }
@@ -14,6 +15,7 @@ impl Update {
         Update {
             column: String::new(),
             operator: String::new(),
+            column_type: Type::Int,
             expr: ExpressionEvaluator::new(),
             string_builder: StringBuilder::new()
         }
@@ -23,48 +25,67 @@ impl Update {
         !self.operator.is_empty()
     }
 
-    fn test_string_exp_compability(&self) -> ConsumeResult {
-        if !self.string_builder.is_empty() && !self.expr.is_empty() {
-            return Err("Error during a set request: Iris doesn't support yet the string operations".to_string())
+    /// Give the string being read to the expression of the new value
+    fn push_string(&mut self) {
+        if !self.string_builder.is_empty() {
+            self.expr.new_string(self.string_builder.extract());
         }
-        Ok(())
     }
     
-    fn new_operator(&mut self, op: String)  {
+    /// Keep the type of the updated column and give the last string to the expression
+    fn prepare(&mut self, column_type: Type) {
+        self.column_type = column_type;
+        self.push_string();
+    }
+
+    /// The new value of the column for the given line
+    fn new_value(&mut self, line: &Map<String, JsonValue>) -> Result<JsonValue, IrisError> {
+        let value = self.expr.evaluate(line, false)?;
+        if !self.column_type.accepts(&value) {
+            return Err(IrisError::TypeMismatch(format!("Error during a set request, the column {} of type {} can't receive {value}.", self.column, self.column_type)))
+        }
+        Ok(self.column_type.cast(value).into())
+    }
+
+    fn new_operator(&mut self, op: String) -> ConsumeResult {
         if self.has_operator() {
-            self.expr.new_operator(op);
+            self.push_string();
+            self.expr.new_operator(op)?;
         } else {
             self.operator = op;
         }
-        
+        Ok(())
+    }
+
+    fn new_keyword(&mut self, keyword: &str) -> ConsumeResult {
+        self.push_string();
+        self.expr.new_keyword(keyword)
     }
 
-    fn new_name(&mut self, name: String) -> ConsumeResult {
+    fn new_name(&mut self, name: String) {
         if self.has_operator() {
+            self.push_string();
             self.expr.new_field(name);
-            self.test_string_exp_compability()?;
         } else {
             self.column = name;
         }
-        Ok(())
     }
 
     fn new_number(&mut self, n: String) -> ConsumeResult {
-        self.test_string_exp_compability()?;        
-        self.expr.new_number(n);
-        Ok(())
+        self.push_string();
+        self.expr.new_number(n)
     }
 
-    fn new_char(&mut self, c: String) -> ConsumeResult {
+    fn new_char(&mut self, c: String) {
         self.string_builder.new_char(c);
-        self.test_string_exp_compability()
     }
 }
 
 pub struct SetReq {
     redirect: bool,
     aff_vec: Vec<Update>,
-    from_where: FromWhereReq
+    from_where: FromWhereReq,
+    updates: Vec<(usize, Map<String, JsonValue>)>
 }
 
 impl Request for SetReq {
@@ -73,18 +94,29 @@ impl Request for SetReq {
         Box::from(SetReq {
             redirect: false,
             aff_vec: vec!(Update::new()),
-            from_where: FromWhereReq::pure_new()
+            from_where: FromWhereReq::pure_new(),
+            updates: Vec::new()
         })
     }
 
     fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        self.from_where.refuse_select_clauses("set")?;
         self.from_where.push_last_string();
-        let table = database.get_table_mut(self.from_where.table_name());
-        table.browse(self);
-        table.actualise_table_file();
+        let columns = self.aff_vec.iter().map(|aff| aff.column.clone()).collect::<Vec<_>>();
+        database.test_column_existance(self.from_where.table_name(), &columns)?;
+        let table_name = self.from_where.table_name().clone();
+        let table = database.get_table_mut(&table_name)?;
+        for aff in self.aff_vec.iter_mut() {
+            aff.prepare(table.get_column(&aff.column)?.get_type());
+        }
+        table.browse(self)?;
+        let updates = std::mem::take(&mut self.updates);
+        database.check_foreign_keys(&table_name, &updates.iter().map(|(_, line)| line).collect::<Vec<_>>())?;
+        database.check_referenced_keys(&table_name, &updates)?;
+        database.modify_table(&table_name, |table| table.update_lines(updates))?;
         self.from_where.end(database)?;
         self.redirect = false;
-        self.aff_vec.clear();
+        self.aff_vec = vec!(Update::new());
         Ok(())
     }
     
@@ -94,12 +126,13 @@ impl Request for SetReq {
         } else {
             let last = self.get_last_update();
             match token.token_type {
-                TokenType::Keyword => self.new_keyword(),
+                TokenType::Keyword if token.content == "FROM" => self.new_keyword(),
+                TokenType::Keyword => last.new_keyword(&token.content)?,
                 TokenType::Symbol => self.new_char(token.content, token.flag)?,
-                TokenType::Operator => last.new_operator(token.content),
+                TokenType::Operator => last.new_operator(token.content)?,
                 TokenType::Number => last.new_number(token.content)?,
-                TokenType::Ident => last.new_name(token.content)?,
-                _ => self.panic_bad_token(token, "set")
+                TokenType::Ident => last.new_name(token.content),
+                _ => self.bad_token(token, "set")?
             }
         }
         Ok(())
@@ -116,8 +149,10 @@ impl SetReq {
     fn new_char(&mut self, c: String, flag: Flag) -> ConsumeResult {
         if flag == Flag::Comma {
             self.aff_vec.push(Update::new())
+        } else if flag == Flag::Parenthesis {
+            self.get_last_update().new_operator(c)?;
         } else {
-            self.get_last_update().new_char(c)?;
+            self.get_last_update().new_char(c);
         }
         Ok(())
     }
@@ -129,14 +164,25 @@ impl SetReq {
 
 impl BrowserReq for SetReq {
 
-    fn browse_action(&mut self, line: &mut Map::<String, JsonValue>, _line_number: usize) {
+    fn browse_action(&mut self, line: &mut Map::<String, JsonValue>, line_number: usize) -> ConsumeResult {
+        let mut new_line = line.clone();
         for aff in self.aff_vec.iter_mut() {
-            line[&aff.column] = aff.expr.compute(line, false).into();
+            new_line[&aff.column] = aff.new_value(line)?;
         }
+        self.updates.push((line_number, new_line));
+        Ok(())
     }
 
     fn get_expr(&mut self) -> &mut ExpressionEvaluator {
         self.from_where.get_where_expr()
     }
+
+    fn get_limit(&self) -> Option<usize> {
+        self.from_where.limit()
+    }
+
+    fn get_offset(&self) -> usize {
+        self.from_where.offset()
+    }
     
 }
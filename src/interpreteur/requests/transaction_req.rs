@@ -0,0 +1,58 @@
+use crate::interpreteur::include::*;
+
+/// Handle the BEGIN request, the next changes are kept in memory until the COMMIT or the ROLLBACK
+pub struct BeginReq;
+
+impl Request for BeginReq {
+
+    fn new() -> BoxedReq {
+        Box::from(BeginReq)
+    }
+
+    fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        database.begin()
+    }
+    
+    fn consume(&mut self, _database: &mut Database, token: Token) -> ConsumeResult {
+        self.bad_token(token, "begin")
+    }
+    
+}
+
+/// Handle the COMMIT request, the changes of the transaction are written on the disk
+pub struct CommitReq;
+
+impl Request for CommitReq {
+
+    fn new() -> BoxedReq {
+        Box::from(CommitReq)
+    }
+
+    fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        database.commit()
+    }
+    
+    fn consume(&mut self, _database: &mut Database, token: Token) -> ConsumeResult {
+        self.bad_token(token, "commit")
+    }
+    
+}
+
+/// Handle the ROLLBACK request, the changes of the transaction are forgotten
+pub struct RollbackReq;
+
+impl Request for RollbackReq {
+
+    fn new() -> BoxedReq {
+        Box::from(RollbackReq)
+    }
+
+    fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        database.rollback()
+    }
+    
+    fn consume(&mut self, _database: &mut Database, token: Token) -> ConsumeResult {
+        self.bad_token(token, "rollback")
+    }
+    
+}
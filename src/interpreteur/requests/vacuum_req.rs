@@ -0,0 +1,28 @@
+use crate::interpreteur::include::*;
+
+/// Handle the VACUUM request, which compacts the data file of the given table or of every table
+pub struct VacuumReq {
+    table_name: Option<String>
+}
+
+impl Request for VacuumReq {
+
+    fn new() -> BoxedReq {
+        Box::from(VacuumReq {
+            table_name: None
+        })
+    }
+
+    fn end(&mut self, database: &mut Database) -> ConsumeResult {
+        database.vacuum(self.table_name.take().as_ref())
+    }
+    
+    fn consume(&mut self, _database: &mut Database, token: Token) -> ConsumeResult {
+        match token.token_type {
+            TokenType::Ident => self.table_name = Some(token.content),
+            _ => self.bad_token(token, "vacuum")?
+        }
+        Ok(())
+    }
+    
+}
@@ -1,6 +1,3 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-
 pub struct StringBuilder {
     string: Option<String>
 }
@@ -37,12 +34,6 @@ impl StringBuilder {
         self.string_mut().push_str(&c);
     }
 
-    pub fn hash(&self) -> i64 {
-        let mut s = DefaultHasher::new();
-        self.string().hash(&mut s);
-        (s.finish()/2) as i64
-    }
-
     pub fn is_empty(&self) -> bool {
         return self.string().is_empty()
     }
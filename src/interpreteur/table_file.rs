@@ -0,0 +1,125 @@
+use super::include::{JsonValue, Map, HashMap, IrisError, Type, Temporal, Operand};
+
+/// The columns of a table in the order their values are encoded in a line
+pub type Layout = Vec<(String, Type)>;
+
+/// Encode the line with the types of the columns: a bitmap of the NULL values, then the other values in the order of the layout.
+/// INT, FLOAT and TIMESTAMP take 8 bytes, DATE and TIME 4 bytes, BOOL 1 byte and STRING its length on 4 bytes followed by its content.
+pub fn encode_line(layout: &Layout, line: &Map<String, JsonValue>, table: &str) -> Result<Vec<u8>, IrisError> {
+    let mut bytes = vec!(0; layout.len().div_ceil(8));
+    for (n, (column, column_type)) in layout.iter().enumerate() {
+        let value = line.get(column).unwrap_or(&JsonValue::Null);
+        let mismatch = || IrisError::TypeMismatch(format!("The value {value} can't be stored in the column {column} of type {column_type} of the table {table}."));
+        match (column_type, value) {
+            (_, JsonValue::Null) => bytes[n / 8] |= 1 << (n % 8),
+            (Type::Int, JsonValue::Number(number)) => bytes.extend(number.as_i64().ok_or_else(mismatch)?.to_le_bytes()),
+            (Type::Float, JsonValue::Number(number)) => bytes.extend(number.as_f64().ok_or_else(mismatch)?.to_le_bytes()),
+            (Type::Bool, JsonValue::Bool(boolean)) => bytes.push(*boolean as u8),
+            // The BOOL columns used to be stored as numbers
+            (Type::Bool, JsonValue::Number(number)) => bytes.push((number.as_i64() != Some(0)) as u8),
+            (Type::String, JsonValue::String(string)) => {
+                bytes.extend(u32::try_from(string.len()).map_err(|_| mismatch())?.to_le_bytes());
+                bytes.extend(string.as_bytes());
+            }
+            (_, JsonValue::String(string)) if column_type.is_temporal() => match column_type.temporal_of(&Operand::String(string.clone())) {
+                Some(Temporal::Date(value) | Temporal::Time(value)) => bytes.extend(i32::try_from(value).map_err(|_| mismatch())?.to_le_bytes()),
+                Some(Temporal::Timestamp(seconds)) => bytes.extend(seconds.to_le_bytes()),
+                None => return Err(mismatch())
+            },
+            _ => return Err(mismatch())
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decode a line encoded with the same layout
+pub fn decode_line(layout: &Layout, bytes: &[u8], table: &str) -> Result<Map<String, JsonValue>, IrisError> {
+    let truncated = || IrisError::Corrupted(format!("In the data file of the table {table}, a line is shorter than its columns."));
+    let nulls = bytes.get(..layout.len().div_ceil(8)).ok_or_else(truncated)?;
+    let mut at = nulls.len();
+    let mut take = |len: usize| {
+        let value = bytes.get(at..at + len).ok_or_else(truncated);
+        at += len;
+        value
+    };
+    let mut line = Map::new();
+    for (n, (column, column_type)) in layout.iter().enumerate() {
+        let value = if nulls[n / 8] & (1 << (n % 8)) != 0 {
+            JsonValue::Null
+        } else {
+            match column_type {
+                Type::Int => JsonValue::from(i64::from_le_bytes(take(8)?.try_into().map_err(|_| truncated())?)),
+                Type::Float => JsonValue::from(f64::from_le_bytes(take(8)?.try_into().map_err(|_| truncated())?)),
+                Type::Bool => JsonValue::Bool(take(1)?[0] != 0),
+                Type::String => {
+                    let len = u32::from_le_bytes(take(4)?.try_into().map_err(|_| truncated())?) as usize;
+                    let string = String::from_utf8(take(len)?.to_vec())
+                        .map_err(|_| IrisError::Corrupted(format!("In the data file of the table {table}, a value of the column {column} is not valid UTF-8.")))?;
+                    JsonValue::String(string)
+                }
+                Type::Date | Type::Time => {
+                    let value = i32::from_le_bytes(take(4)?.try_into().map_err(|_| truncated())?) as i64;
+                    let temporal = if *column_type == Type::Date { Temporal::Date(value) } else { Temporal::Time(value) };
+                    JsonValue::String(temporal.to_string())
+                }
+                Type::Timestamp => JsonValue::String(Temporal::Timestamp(i64::from_le_bytes(take(8)?.try_into().map_err(|_| truncated())?)).to_string())
+            }
+        };
+        line.insert(column.clone(), value);
+    }
+    Ok(line)
+}
+
+/// The version 2 of the data files: a header followed by the records of the changes of the lines
+const RECORDS_FORMAT_NAME: &str = "iris table";
+const RECORDS_FORMAT_VERSION: i64 = 2;
+
+/// Read the lines of a data file written in json by an older version, to store them in pages.
+/// The version 1 was the whole table in one json array, the version 2 the records of the insertions, the updates and the deletions.
+pub fn read_json_lines(content: &str, p_key: &str, table: &str) -> Result<Vec<JsonValue>, IrisError> {
+    let corrupted = |msg: String| IrisError::Corrupted(format!("In the data file of the table {table}, {msg}"));
+    if content.trim().is_empty() || content.trim_start().starts_with('[') {
+        return read_json_array(content, table)
+    }
+    let mut records = content.lines();
+    let header = records.next().and_then(|line| serde_json::from_str::<JsonValue>(line).ok()).unwrap_or_default();
+    if header["format"] != RECORDS_FORMAT_NAME || header["version"].as_i64() != Some(RECORDS_FORMAT_VERSION) {
+        return Err(corrupted(String::from("the header is missing.")))
+    }
+    let mut lines: Vec<Option<JsonValue>> = Vec::new();
+    let mut positions = HashMap::<String, usize>::new();
+    for record in records {
+        let record: JsonValue = serde_json::from_str(record).map_err(|e| corrupted(format!("the record {record} is not valid json: {e}")))?;
+        let mut position = |key: &JsonValue| positions.remove(&key.to_string()).ok_or_else(|| corrupted(format!("the record {record} changes a line which doesn't exist.")));
+        let (position, line) = match (&record["insert"], &record["update"], &record["row"], &record["delete"]) {
+            (JsonValue::Object(line), _, _, _) => (None, Some(line)),
+            (_, key, JsonValue::Object(line), _) if !key.is_null() => (Some(position(key)?), Some(line)),
+            (_, _, _, key) if !key.is_null() => (Some(position(key)?), None),
+            _ => return Err(corrupted(format!("the record {record} is unknown.")))
+        };
+        let position = position.unwrap_or_else(|| {
+            lines.push(None);
+            lines.len() - 1
+        });
+        if let Some(line) = line {
+            positions.insert(line.get(p_key).map(|key| key.to_string()).unwrap_or_default(), position);
+        }
+        lines[position] = line.cloned().map(JsonValue::Object);
+    }
+    Ok(lines.into_iter().flatten().collect())
+}
+
+fn read_json_array(content: &str, table: &str) -> Result<Vec<JsonValue>, IrisError> {
+    let datas: JsonValue = match serde_json::from_str(if content.trim().is_empty() { "[]" } else { content }) {
+        Ok(datas) => datas,
+        Err(e) => return Err(IrisError::Corrupted(format!("The data file of the table {table} is not valid json: {e}")))
+    };
+    let lines = match datas {
+        JsonValue::Array(lines) => lines,
+        _ => return Err(IrisError::Corrupted(format!("The data file of the table {table} doesn't contain a json array.")))
+    };
+    if let Some(line) = lines.iter().find(|line| !line.is_object()) {
+        return Err(IrisError::Corrupted(format!("In the data file of the table {table}, the line {line} is not a json object.")))
+    }
+    Ok(lines)
+}
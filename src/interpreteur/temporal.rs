@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// A date, a time of the day or a timestamp, written in ISO-8601: 2024-03-01, 13:45:00 and 2024-03-01 13:45:00.
+/// A date counts the days since 1970-01-01, a time the seconds since midnight and a timestamp the seconds since 1970-01-01 00:00:00.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Temporal {
+    Date(i64),
+    Time(i64),
+    Timestamp(i64)
+}
+
+/// The number of days between 1970-01-01 and the given day of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The year, month and day of the given number of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (year_of_era + era * 400 + (month <= 2) as i64, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+/// The number written with exactly the given digits
+fn digits(s: &str) -> Option<i64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None
+    }
+    s.parse().ok()
+}
+
+/// YYYY-MM-DD
+fn parse_date(s: &str) -> Option<i64> {
+    if s.len() != 10 || &s[4..5] != "-" || &s[7..8] != "-" {
+        return None
+    }
+    let (year, month, day) = (digits(&s[0..4])?, digits(&s[5..7])?, digits(&s[8..10])?);
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// HH:MM:SS
+fn parse_time(s: &str) -> Option<i64> {
+    if s.len() != 8 || &s[2..3] != ":" || &s[5..6] != ":" {
+        return None
+    }
+    let (hour, minute, second) = (digits(&s[0..2])?, digits(&s[3..5])?, digits(&s[6..8])?);
+    if hour >= 24 || minute >= 60 || second >= 60 {
+        return None
+    }
+    Some(hour * 3600 + minute * 60 + second)
+}
+
+impl Temporal {
+
+    /// Read a date, a time or a timestamp whose date and time are separated by a space or a T
+    pub fn parse(s: &str) -> Option<Temporal> {
+        if !s.is_ascii() {
+            return None
+        }
+        match s.len() {
+            10 => parse_date(s).map(Temporal::Date),
+            8 => parse_time(s).map(Temporal::Time),
+            19 if [" ", "T"].contains(&&s[10..11]) =>
+                Some(Temporal::Timestamp(parse_date(&s[..10])? * SECONDS_PER_DAY + parse_time(&s[11..])?)),
+            _ => None
+        }
+    }
+
+    /// The current date and time in UTC
+    pub fn now() -> Temporal {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default();
+        Temporal::Timestamp(seconds)
+    }
+
+    pub fn is_time(&self) -> bool {
+        matches!(self, Temporal::Time(_))
+    }
+
+    /// The timestamp of a date at midnight
+    pub fn to_timestamp(self) -> Temporal {
+        match self {
+            Temporal::Date(days) => Temporal::Timestamp(days * SECONDS_PER_DAY),
+            _ => self
+        }
+    }
+
+    /// Dates and timestamps can be compared between them, a date being the midnight of its day. Times are only compared between them.
+    pub fn compare(&self, other: &Temporal) -> Option<Ordering> {
+        match (self.to_timestamp(), other.to_timestamp()) {
+            (Temporal::Time(t1), Temporal::Time(t2)) | (Temporal::Timestamp(t1), Temporal::Timestamp(t2)) => Some(t1.cmp(&t2)),
+            _ => None
+        }
+    }
+
+    /// Move a date or a timestamp of the given number of days, None if the result is not between the years 0 and 9999
+    pub fn add_days(&self, days: i64) -> Option<Temporal> {
+        let result = match self {
+            Temporal::Date(date) => Temporal::Date(date.checked_add(days)?),
+            Temporal::Timestamp(seconds) => Temporal::Timestamp(seconds.checked_add(days.checked_mul(SECONDS_PER_DAY)?)?),
+            Temporal::Time(_) => return None
+        };
+        let (year, _, _) = civil_from_days(result.day()?);
+        (0..=9999).contains(&year).then_some(result)
+    }
+
+    /// The days since 1970-01-01 of a date or of the day of a timestamp
+    fn day(&self) -> Option<i64> {
+        match self {
+            Temporal::Date(days) => Some(*days),
+            Temporal::Timestamp(seconds) => Some(seconds.div_euclid(SECONDS_PER_DAY)),
+            Temporal::Time(_) => None
+        }
+    }
+
+    /// The seconds since midnight of a time or of a timestamp
+    fn seconds_of_day(&self) -> Option<i64> {
+        match self {
+            Temporal::Time(seconds) => Some(*seconds),
+            Temporal::Timestamp(seconds) => Some(seconds.rem_euclid(SECONDS_PER_DAY)),
+            Temporal::Date(_) => None
+        }
+    }
+
+    /// The YEAR, MONTH, DAY, HOUR, MINUTE or SECOND of the value, None if it doesn't have this field
+    pub fn extract(&self, field: &str) -> Option<i64> {
+        match field {
+            "YEAR" => self.day().map(|day| civil_from_days(day).0),
+            "MONTH" => self.day().map(|day| civil_from_days(day).1),
+            "DAY" => self.day().map(|day| civil_from_days(day).2),
+            "HOUR" => self.seconds_of_day().map(|seconds| seconds / 3600),
+            "MINUTE" => self.seconds_of_day().map(|seconds| seconds % 3600 / 60),
+            "SECOND" => self.seconds_of_day().map(|seconds| seconds % 60),
+            _ => None
+        }
+    }
+
+    /// DATE, TIME or TIMESTAMP
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Temporal::Date(_) => "DATE",
+            Temporal::Time(_) => "TIME",
+            Temporal::Timestamp(_) => "TIMESTAMP"
+        }
+    }
+
+}
+
+impl fmt::Display for Temporal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day() {
+            let (year, month, day) = civil_from_days(day);
+            write!(f, "{year:04}-{month:02}-{day:02}")?;
+        }
+        if let Some(seconds) = self.seconds_of_day() {
+            if !self.is_time() {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::{Path, PathBuf};
+
+/// An empty directory of a unit test, removed once the test is over
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+
+    pub fn new(name: &str) -> TestDir {
+        let path = std::env::temp_dir().join(format!("iris-unit-{}-{name}", std::process::id()));
+        let _ = remove_dir_all(&path);
+        create_dir_all(&path).expect("Failed to create the directory of the test");
+        TestDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,348 @@
+use super::include::{JsonValue, IrisError, ConsumeResult, json};
+use super::buffer_pool::PAGE_SIZE;
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions, read_dir, remove_file, rename};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// The name of the write-ahead log in the directory of the database, a table can't have this name because of the dot
+const WAL_FILE: &str = "iris.wal";
+
+/// Above this size, the log is checkpointed after the changes it contains are applied
+const CHECKPOINT_SIZE: u64 = 1 << 20;
+
+/// The extension of the temporary file written before replacing a file, it is specific to iris
+/// because the directory of the database may contain other files
+const TMP_EXTENSION: &str = "iris-tmp";
+
+/// Write the content in a temporary file, sync it, then rename it over the file.
+/// A reader or a crash only sees the old content or the new one, never a part of it.
+pub fn replace_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension(TMP_EXTENSION);
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(content)?;
+    tmp.sync_all()?;
+    rename(&tmp_path, path)
+}
+
+/// A change of a file of the database
+#[derive(Debug, Clone)]
+pub enum WalEntry {
+    /// Replace the whole content of the file, the file is created if it doesn't exist
+    Write { file: String, content: String },
+    /// Write the given pages of the file, which has then the given number of pages. Applying it again gives the same file.
+    /// The pages are written in place rather than in a temporary file: a file left half written by a crash is repaired from the log.
+    Pages { file: String, count: u64, pages: Vec<(u32, Vec<u8>)> },
+    Remove { file: String }
+}
+
+impl WalEntry {
+
+    fn file(&self) -> &String {
+        match self {
+            WalEntry::Write { file, .. } | WalEntry::Pages { file, .. } | WalEntry::Remove { file } => file
+        }
+    }
+
+    fn record(&self) -> JsonValue {
+        match self {
+            WalEntry::Write { file, content } => json!({"write": file, "content": content}),
+            WalEntry::Pages { file, count, pages } => json!({
+                "pages": file,
+                "count": count,
+                "content": pages.iter().map(|(page, data)| json!([page, to_hex(data)])).collect::<Vec<_>>()
+            }),
+            WalEntry::Remove { file } => json!({"remove": file})
+        }
+    }
+
+    fn from_record(record: &JsonValue) -> Option<WalEntry> {
+        match (&record["write"], &record["pages"], &record["content"], &record["remove"]) {
+            (JsonValue::String(file), _, JsonValue::String(content), _) => Some(WalEntry::Write { file: file.clone(), content: content.clone() }),
+            (_, JsonValue::String(file), JsonValue::Array(pages), _) => Some(WalEntry::Pages {
+                file: file.clone(),
+                count: record["count"].as_u64()?,
+                pages: pages.iter()
+                    .map(|page| Some((u32::try_from(page[0].as_u64()?).ok()?, from_hex(page[1].as_str()?)?)))
+                    .collect::<Option<Vec<_>>>()?
+            }),
+            (_, _, _, JsonValue::String(file)) => Some(WalEntry::Remove { file: file.clone() }),
+            _ => None
+        }
+    }
+
+    fn apply(&self, dir: &Path) -> ConsumeResult {
+        let path = dir.join(self.file());
+        match self {
+            WalEntry::Write { file, content } => replace_file(&path, content.as_bytes())
+                .map_err(|e| IrisError::io(&format!("Failed to write the file {file} of the database"), e)),
+            WalEntry::Pages { file, count, pages } => OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .and_then(|mut f| {
+                    for (page, data) in pages.iter() {
+                        f.seek(SeekFrom::Start(*page as u64 * PAGE_SIZE as u64))?;
+                        f.write_all(data)?;
+                    }
+                    f.set_len(count * PAGE_SIZE as u64)
+                })
+                .map_err(|e| IrisError::io(&format!("Failed to write the pages of the file {file} of the database"), e)),
+            WalEntry::Remove { file } if path.is_file() => remove_file(&path)
+                .map_err(|e| IrisError::io(&format!("Failed to remove the file {file} of the database"), e)),
+            WalEntry::Remove { .. } => Ok(())
+        }
+    }
+
+}
+
+/// The write-ahead log of a database: the changes of every commit are written and synced in the log before being applied on the files,
+/// so the committed changes interrupted by a crash are applied again when the database is loaded.
+/// The directory and the files whose pages are written are only synced at the checkpoints, which empty the log.
+#[derive(Debug)]
+pub struct Wal {
+    dir: PathBuf,
+    file: File,
+    unsynced: BTreeSet<String>
+}
+
+impl Wal {
+
+    /// Open the log of the database stored in the given directory, the committed changes it contains are applied then the log is checkpointed.
+    pub fn open(dir: &Path) -> Result<Wal, IrisError> {
+        let path = dir.join(WAL_FILE);
+        let file = OpenOptions::new()
+            .append(true)
+            .read(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| IrisError::io("Failed to open the write-ahead log", e))?;
+        let mut wal = Wal {
+            dir: dir.to_path_buf(),
+            file,
+            unsynced: BTreeSet::new()
+        };
+        wal.recover()?;
+        wal.remove_tmp_files()?;
+        Ok(wal)
+    }
+
+    /// Remove the temporary files left by a replacement interrupted before its rename
+    fn remove_tmp_files(&self) -> ConsumeResult {
+        let entries = read_dir(&self.dir).map_err(|e| IrisError::io("Failed to read the directory of the database", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && path.extension().is_some_and(|extension| extension == TMP_EXTENSION) {
+                remove_file(&path).map_err(|e| IrisError::io(&format!("Failed to remove the temporary file {}", path.display()), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the changes of the log followed by their commit record, the last changes without it were interrupted before the commit and are ignored
+    fn recover(&mut self) -> ConsumeResult {
+        let mut content = String::new();
+        self.file.read_to_string(&mut content).map_err(|e| IrisError::io("Failed to read the write-ahead log", e))?;
+        let mut pending = Vec::new();
+        for line in content.lines() {
+            let Ok(record) = serde_json::from_str::<JsonValue>(line) else {
+                break
+            };
+            match WalEntry::from_record(&record) {
+                Some(entry) => pending.push(entry),
+                None if record["commit"] == JsonValue::Bool(true) => {
+                    for entry in pending.drain(..) {
+                        self.apply(&entry)?;
+                    }
+                }
+                None => break
+            }
+        }
+        self.checkpoint()
+    }
+
+    /// Write the changes and their commit record in the log, then apply them on the files
+    pub fn commit(&mut self, entries: Vec<WalEntry>) -> ConsumeResult {
+        if entries.is_empty() {
+            return Ok(())
+        }
+        let mut log = String::new();
+        for entry in entries.iter() {
+            log += &entry.record().to_string();
+            log.push('\n');
+        }
+        log += &json!({"commit": true}).to_string();
+        log.push('\n');
+        self.file.write_all(log.as_bytes())
+            .and_then(|_| self.file.sync_data())
+            .map_err(|e| IrisError::io("Failed to write the write-ahead log", e))?;
+        for entry in entries.iter() {
+            self.apply(entry)?;
+        }
+        let size = self.file.metadata().map_err(|e| IrisError::io("Failed to read the size of the write-ahead log", e))?.len();
+        if size >= CHECKPOINT_SIZE {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    /// Open the log again in read only, so writing it fails as on a full disk
+    #[cfg(test)]
+    pub fn fail_writes(&mut self) {
+        self.file = File::open(self.dir.join(WAL_FILE)).unwrap();
+    }
+
+    fn apply(&mut self, entry: &WalEntry) -> ConsumeResult {
+        entry.apply(&self.dir)?;
+        if let WalEntry::Pages { file, .. } = entry {
+            self.unsynced.insert(file.clone());
+        }
+        Ok(())
+    }
+
+    /// Sync the files whose pages were written and the directory so the renamed and removed files are on the disk,
+    /// then empty the log whose changes are all applied
+    fn checkpoint(&mut self) -> ConsumeResult {
+        for name in std::mem::take(&mut self.unsynced) {
+            let path = self.dir.join(&name);
+            if path.is_file() {
+                File::open(&path)
+                    .and_then(|file| file.sync_all())
+                    .map_err(|e| IrisError::io(&format!("Failed to sync the file {name} of the database"), e))?;
+            }
+        }
+        sync_dir(&self.dir)?;
+        self.file.set_len(0)
+            .and_then(|_| self.file.sync_all())
+            .map_err(|e| IrisError::io("Failed to empty the write-ahead log", e))
+    }
+
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().flat_map(|byte| [HEX_DIGITS[(byte >> 4) as usize] as char, HEX_DIGITS[(byte & 15) as usize] as char]).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
+
+/// Sync the directory so the renamed and removed files are on the disk
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> ConsumeResult {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| IrisError::io("Failed to sync the directory of the database", e))
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> ConsumeResult {
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_dir::TestDir;
+    use std::fs::{read, read_to_string, write};
+
+    fn write_entry(file: &str, content: &str) -> String {
+        WalEntry::Write { file: file.to_string(), content: content.to_string() }.record().to_string() + "\n"
+    }
+
+    fn commit_record() -> String {
+        json!({"commit": true}).to_string() + "\n"
+    }
+
+    fn log_size(dir: &TestDir) -> u64 {
+        std::fs::metadata(dir.path().join(WAL_FILE)).unwrap().len()
+    }
+
+    #[test]
+    fn committed_changes_are_replayed_on_open() {
+        let dir = TestDir::new("replay");
+        write(dir.path().join("a"), "old").unwrap();
+        write(dir.path().join("b"), "removed").unwrap();
+        let log = write_entry("a", "new") + &json!({"remove": "b"}).to_string() + "\n" + &commit_record();
+        write(dir.path().join(WAL_FILE), log).unwrap();
+        Wal::open(dir.path()).unwrap();
+        assert_eq!(read_to_string(dir.path().join("a")).unwrap(), "new");
+        assert!(!dir.path().join("b").exists());
+        assert_eq!(log_size(&dir), 0);
+    }
+
+    #[test]
+    fn uncommitted_changes_are_dropped() {
+        let dir = TestDir::new("uncommitted");
+        write(dir.path().join("a"), "old").unwrap();
+        let log = write_entry("a", "first") + &commit_record() + &write_entry("a", "second");
+        write(dir.path().join(WAL_FILE), log).unwrap();
+        Wal::open(dir.path()).unwrap();
+        assert_eq!(read_to_string(dir.path().join("a")).unwrap(), "first");
+        assert_eq!(log_size(&dir), 0);
+    }
+
+    #[test]
+    fn torn_last_record_is_ignored() {
+        let dir = TestDir::new("torn");
+        write(dir.path().join("a"), "old").unwrap();
+        let torn = write_entry("a", "torn");
+        let log = write_entry("a", "kept") + &commit_record() + &torn[..torn.len() / 2];
+        write(dir.path().join(WAL_FILE), log).unwrap();
+        Wal::open(dir.path()).unwrap();
+        assert_eq!(read_to_string(dir.path().join("a")).unwrap(), "kept");
+        let torn_commit = write_entry("a", "torn") + &commit_record()[..4];
+        write(dir.path().join(WAL_FILE), torn_commit).unwrap();
+        Wal::open(dir.path()).unwrap();
+        assert_eq!(read_to_string(dir.path().join("a")).unwrap(), "kept");
+    }
+
+    #[test]
+    fn pages_are_written_in_place() {
+        let dir = TestDir::new("pages");
+        write(dir.path().join("t"), vec![7u8; 3 * PAGE_SIZE]).unwrap();
+        let mut wal = Wal::open(dir.path()).unwrap();
+        let page = (0..PAGE_SIZE).map(|i| i as u8).collect::<Vec<_>>();
+        wal.commit(vec!(WalEntry::Pages { file: String::from("t"), count: 2, pages: vec!((1, page.clone())) })).unwrap();
+        let content = read(dir.path().join("t")).unwrap();
+        assert_eq!(content.len(), 2 * PAGE_SIZE);
+        assert!(content[..PAGE_SIZE].iter().all(|byte| *byte == 7));
+        assert_eq!(&content[PAGE_SIZE..], &page[..]);
+        let record = read_to_string(dir.path().join(WAL_FILE)).unwrap();
+        let entry = WalEntry::from_record(&serde_json::from_str(record.lines().next().unwrap()).unwrap()).unwrap();
+        assert!(matches!(entry, WalEntry::Pages { count: 2, pages, .. } if pages == vec!((1, page))));
+    }
+
+    #[test]
+    fn log_is_checkpointed_above_its_size_limit() {
+        let dir = TestDir::new("checkpoint");
+        let mut wal = Wal::open(dir.path()).unwrap();
+        wal.commit(vec!(WalEntry::Write { file: String::from("a"), content: String::from("small") })).unwrap();
+        let small = log_size(&dir);
+        assert!(small > 0);
+        let big = "x".repeat(CHECKPOINT_SIZE as usize);
+        wal.commit(vec!(WalEntry::Write { file: String::from("a"), content: big.clone() })).unwrap();
+        assert_eq!(log_size(&dir), 0);
+        assert_eq!(read_to_string(dir.path().join("a")).unwrap(), big);
+        wal.commit(vec!(WalEntry::Write { file: String::from("a"), content: String::from("small") })).unwrap();
+        assert_eq!(log_size(&dir), small);
+    }
+
+    #[test]
+    fn temporary_files_are_removed_on_open() {
+        let dir = TestDir::new("tmp");
+        write(dir.path().join("a.iris-tmp"), "partial").unwrap();
+        write(dir.path().join("notes.tmp"), "not ours").unwrap();
+        Wal::open(dir.path()).unwrap();
+        assert!(!dir.path().join("a.iris-tmp").exists());
+        assert_eq!(read_to_string(dir.path().join("notes.tmp")).unwrap(), "not ours");
+    }
+
+}
@@ -1,12 +1,12 @@
 mod tokenizer;
 mod interpreteur;
 mod c_extention;
-use interpreteur::interpreteur::Interpreteur;
+mod connection;
+mod error;
+pub use connection::Connection;
+pub use error::IrisError;
+pub use interpreteur::query_result::{QueryResult, Type, Value};
 use std::process::exit;
-use std::thread::spawn;
-use tokenizer::{include::{TokenType, TokenizerMessage}, tokenizer::Tokenizer};
-use std::sync::mpsc::channel;
-use std::sync::mpsc::Receiver;
 use std::fs::{
     File,
     OpenOptions,
@@ -42,11 +42,17 @@ pub fn begin(mut args: Vec<String>) {
             _ => error_catched(&format!("Unknow parameter: {}", elt))
         }
     }
-    let (sender, receiver) = channel::<TokenizerMessage>();
-    let mut interp = Interpreteur::new();
-    let mut tokenizer = Tokenizer::new(sender);
+    let mut connection = Connection::open(get_iris_path()).unwrap_or_else(|e| error_catched(&e));
     for act in actions.iter_mut() {
-        tokenizer = act.execute(tokenizer, &mut interp, &receiver);
+        print_results(act.execute(&mut connection));
+    }
+}
+
+fn print_results(results: Vec<QueryResult>) {
+    for result in results {
+        if result.has_columns() {
+            println!("{result}");
+        }
     }
 }
 
@@ -60,24 +66,7 @@ fn pre_init_database() {
     }
 }
 
-
-fn execute(interp: &mut Interpreteur, receiver: &Receiver<TokenizerMessage>) -> Result<Tokenizer, String> {
-    let mut tokenizer: Option<Tokenizer> = None;
-    while tokenizer.is_none() {
-        match receiver.recv().expect("Something went wrong") {
-            TokenizerMessage::Token(token) =>
-                if token.token_type == TokenType::ERROR {
-                    return Err(token.content)
-                } else if let Err(e) = interp.new_token(token) {
-                    return Err(e)
-                }
-            TokenizerMessage::Tokenizer(the_tokenizer) => tokenizer = Some(the_tokenizer)
-        }
-    }
-    Ok(tokenizer.take().expect("Failed to catch the tokenizer throught the threads."))
-}
-
-fn error_catched(err: &str) {
+fn error_catched(err: impl std::fmt::Display) -> ! {
     println!("{err}");
     exit(1)
 }
@@ -91,7 +80,7 @@ pub fn get_iris_path() -> String {
 
 trait RequestParameter {
 
-    fn execute(&mut self, tokenizer: Tokenizer, interp: &mut Interpreteur, receiver: &Receiver<TokenizerMessage>) -> Tokenizer;
+    fn execute(&mut self, connection: &mut Connection) -> Vec<QueryResult>;
     
 }
 
@@ -109,19 +98,14 @@ impl OneQuery {
 
 impl RequestParameter for OneQuery {
 
-    fn execute(&mut self, tokenizer: Tokenizer, interp: &mut Interpreteur, receiver: &Receiver<TokenizerMessage>) -> Tokenizer {
-        let query = self.query.clone();
-        spawn(move ||
-              tokenizer.tokenize_query(query)
-        );
-        match execute(interp, receiver) {
-            Ok(tokenizer) => {
+    fn execute(&mut self, connection: &mut Connection) -> Vec<QueryResult> {
+        match connection.execute_all(&self.query) {
+            Ok(results) => {
                 println!("The execution of the query {} has been a success.", self.query);
-                return tokenizer
+                results
             }
             Err(e) => error_catched(&e)
         }
-        panic!("Impossible case");
     }
 }
 
@@ -140,19 +124,14 @@ impl OneFile {
 
 impl RequestParameter for OneFile {
 
-    fn execute(&mut self, tokenizer: Tokenizer, interp: &mut Interpreteur, receiver: &Receiver<TokenizerMessage>) -> Tokenizer {
-        let path = self.path.clone();
-        spawn(move ||
-              tokenizer.tokenize_file(path)
-        );
-        match execute(interp, receiver) {
-            Ok(tokenizer) => {
+    fn execute(&mut self, connection: &mut Connection) -> Vec<QueryResult> {
+        match connection.execute_file(&self.path) {
+            Ok(results) => {
                 println!("The execution of the file {} has been a success.", self.path);
-                return tokenizer;
+                results
             }
             Err(e) => error_catched(&e)
-        };
-        panic!("Impossible case");
+        }
     }
 }
 
@@ -178,12 +157,12 @@ impl Run {
 
 impl RequestParameter for Run {
 
-    fn execute(&mut self, mut tokenizer: Tokenizer, interp: &mut Interpreteur, receiver: &Receiver<TokenizerMessage>) -> Tokenizer {
+    fn execute(&mut self, connection: &mut Connection) -> Vec<QueryResult> {
         let mut executer = OneFile::new(get_iris_path() + ENTRY_FILE);
         loop {
             println!("running...");
             if self.new_request() {
-                tokenizer = executer.execute(tokenizer, interp, receiver)
+                print_results(executer.execute(connection));
             }
         }
     }
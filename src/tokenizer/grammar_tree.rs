@@ -1,6 +1,6 @@
 use std::collections::HashMap;
-use super::include::{Node, TokenType, Flag, AFFECT_OPERATOR};
-use super::tokenizer::push_token;
+use super::include::{Node, TokenType, Flag, AFFECT_OPERATOR, AGGREGATE_FUNCTIONS, TEMPORAL_FIELDS};
+use super::tokenizer::{push_token, push_negation, end_request};
 
 pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
     let mut group_map = HashMap::new();
@@ -11,6 +11,8 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
             vec!(
                 Node::leaf(TokenType::CreateReq),
                 Node::leaf(TokenType::ResetReq),
+                Node::leaf(TokenType::TransactionReq),
+                Node::leaf(TokenType::VacuumReq),
                 Node::leaf(TokenType::InsertReq),
                 Node::leaf(TokenType::SelectReq),
                 Node::leaf(TokenType::UpdateReq),
@@ -32,6 +34,36 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
         )
     );
 
+    group_map.insert(
+        TokenType::TransactionReq,
+        Node::new(
+            TokenType::TransactionReq,
+            vec!(),
+            vec!(
+                Node::comma_leaf_c(TokenType::Keyword, vec!("BEGIN", "COMMIT", "ROLLBACK")).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::VacuumReq,
+        Node::new(
+            TokenType::VacuumReq,
+            vec!(),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::comma_leaf(TokenType::Ident).react(push_token),
+                        Node::leaf_c(TokenType::Symbol, vec!(";")).react(end_request)
+                    ),
+                    vec!("VACUUM")
+                ).react(push_token)
+            )
+        )
+    );
+
     group_map.insert(
         TokenType::CreateReq,
         Node::new(
@@ -55,7 +87,47 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                                 ).react(push_token)
                             ),
                             vec!("TABLE")
-                        )
+                        ),
+                        Node::new_c(
+                            TokenType::Keyword,
+                            vec!(),
+                            vec!(
+                                Node::new(
+                                    TokenType::Ident,  // Name of the index
+                                    vec!(),
+                                    vec!(
+                                        Node::new_c(
+                                            TokenType::Keyword,
+                                            vec!(),
+                                            vec!(
+                                                Node::new(
+                                                    TokenType::Ident,  // Table
+                                                    vec!(),
+                                                    vec!(
+                                                        Node::new_c(
+                                                            TokenType::Symbol,
+                                                            vec!(),
+                                                            vec!(
+                                                                Node::new(
+                                                                    TokenType::Ident,  // Column
+                                                                    vec!(),
+                                                                    vec!(
+                                                                        Node::comma_leaf_c(TokenType::Symbol, vec!(")"))
+                                                                    )
+                                                                ).react(push_token)
+                                                            ),
+                                                            vec!("(")
+                                                        )
+                                                    )
+                                                ).react(push_token)
+                                            ),
+                                            vec!("ON")
+                                        )
+                                    )
+                                ).react(push_token)
+                            ),
+                            vec!("INDEX")
+                        ).react(push_token)
                     ),
                     vec!("CREATE")
                 ).react(push_token)
@@ -105,7 +177,7 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                             ),
                             vec!(),
                             vec!(",")
-                        )
+                        ).react(push_token).set_flag(Flag::Comma)
                     )
                 )
             ),
@@ -126,8 +198,7 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                         Node::new_end(
                             TokenType::Type,
                             vec!(
-                                Node::leaf(TokenType::PrimaryKey),
-                                Node::leaf(TokenType::DefaultValue)
+                                Node::leaf(TokenType::ColumnConstraint)
                             ),
                             vec!()
                         ).react(push_token)
@@ -137,6 +208,104 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
         )
     );
 
+    group_map.insert(
+        TokenType::ColumnConstraint,
+        Node::new(
+            TokenType::ColumnConstraint,
+            vec!(
+                Node::new_end(
+                    TokenType::PrimaryKey,
+                    vec!(
+                        Node::leaf(TokenType::ColumnConstraint)
+                    ),
+                    vec!()
+                ),
+                Node::new_end(
+                    TokenType::DefaultValue,
+                    vec!(
+                        Node::leaf(TokenType::ColumnConstraint)
+                    ),
+                    vec!()
+                )
+            ),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new_end_c(
+                            TokenType::Keyword,
+                            vec!(
+                                Node::leaf(TokenType::ColumnConstraint)
+                            ),
+                            vec!(),
+                            vec!("NULL")
+                        )
+                    ),
+                    vec!("NOT")
+                ).react(push_token).set_flag(Flag::Constraint),
+                Node::new_end_c(
+                    TokenType::Keyword,
+                    vec!(
+                        Node::leaf(TokenType::ColumnConstraint)
+                    ),
+                    vec!(),
+                    vec!("UNIQUE")
+                ).react(push_token).set_flag(Flag::Constraint),
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(
+                        Node::new_end(
+                            TokenType::Expression,
+                            vec!(
+                                Node::leaf(TokenType::ColumnConstraint)
+                            ),
+                            vec!()
+                        )
+                    ),
+                    vec!(),
+                    vec!("CHECK")
+                ).react(push_token).set_flag(Flag::Constraint),
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new(
+                            TokenType::Ident,
+                            vec!(),
+                            vec!(
+                                Node::new_c(
+                                    TokenType::Symbol,
+                                    vec!(),
+                                    vec!(
+                                        Node::new(
+                                            TokenType::Ident,
+                                            vec!(),
+                                            vec!(
+                                                Node::new_end_c(
+                                                    TokenType::Symbol,
+                                                    vec!(
+                                                        Node::leaf(TokenType::ColumnConstraint)
+                                                    ),
+                                                    vec!(
+                                                        on_delete()
+                                                    ),
+                                                    vec!(")")
+                                                )
+                                            )
+                                        ).react(push_token)
+                                    ),
+                                    vec!("(")
+                                )
+                            )
+                        ).react(push_token)
+                    ),
+                    vec!("REFERENCES")
+                ).react(push_token).set_flag(Flag::Constraint)
+            )
+        )
+    );
+
     group_map.insert(
         TokenType::PrimaryKey,
         Node::new(
@@ -147,10 +316,17 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                     TokenType::Keyword,
                     vec!(),
                     vec!(
-                        Node::leaf_c(TokenType::Keyword, vec!("KEY"))
+                        Node::new_end_c(
+                            TokenType::Keyword,
+                            vec!(),
+                            vec!(
+                                Node::leaf_c(TokenType::Keyword, vec!("AUTOINCREMENT")).react(push_token).set_flag(Flag::Constraint)
+                            ),
+                            vec!("KEY")
+                        )
                     ),
                     vec!("PRIMARY")
-                ).react(push_token)
+                ).react(push_token).set_flag(Flag::Constraint)
             )
         )
     );
@@ -168,7 +344,7 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                     ),
                     vec!(),
                     vec!("DEFAULT")
-                )
+                ).react(push_token).set_flag(Flag::Constraint)
             )
         )
     );
@@ -188,7 +364,8 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                                 Node::leaf(TokenType::Expression)
                             ),
                             vec!()
-                        ).react(push_token)
+                        ).react(push_token),
+                        null_test()
                     )
                 )
             ),
@@ -210,16 +387,33 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                                                 Node::leaf(TokenType::Expression)
                                             ),
                                             vec!()
-                                        ).react(push_token)
+                                        ).react(push_token),
+                                        null_test()
                                     ), 
                                     vec!(")") 
-                                ).react(push_token)
+                                ).react(push_token).set_flag(Flag::Parenthesis)
                             )
                         )
                     ),
                     vec!(),
                     vec!("(")
-                ).react(push_token)
+                ).react(push_token).set_flag(Flag::Parenthesis),
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(
+                        Node::leaf(TokenType::Expression)
+                    ),
+                    vec!(),
+                    vec!("NOT")
+                ).react(push_token),
+                Node::new_c(
+                    TokenType::Symbol,
+                    vec!(
+                        Node::leaf(TokenType::Expression)
+                    ),
+                    vec!(),
+                    vec!("-")
+                ).react(push_negation)
             )
         )
     );
@@ -230,19 +424,14 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
         Node::new(
             TokenType::Value,
             vec!(
-                Node::leaf(TokenType::String)
+                Node::leaf(TokenType::String),
+                Node::leaf(TokenType::FunctionCall),
+                Node::leaf(TokenType::TemporalFunction)
             ),
             vec!(
                 Node::leaf(TokenType::Ident).react(push_token),
                 Node::leaf(TokenType::Number).react(push_token),
-                Node::new_c(
-                    TokenType::Symbol,
-                    vec!(
-                        Node::leaf(TokenType::Value)
-                    ),
-                    vec!(),
-                    vec!("-")
-                )
+                Node::leaf_c(TokenType::Keyword, vec!("NULL", "TRUE", "FALSE")).react(push_token)
             )
         )
     );
@@ -476,7 +665,7 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                     TokenType::Keyword,
                     vec!(
                         Node::new(
-                            TokenType::SerieIdent,
+                            TokenType::SerieSelect,
                             vec!(
                                 Node::leaf(TokenType::FromWhereReq)
                             ),
@@ -499,6 +688,178 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
         )
     );
 
+    group_map.insert(
+        TokenType::SerieSelect,
+        Node::new(
+            TokenType::SerieSelect,
+            vec!(
+                Node::new_end(
+                    TokenType::FunctionCall,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Symbol,
+                            vec!(
+                                Node::leaf(TokenType::SerieSelect)
+                            ),
+                            vec!(),
+                            vec!(",")
+                        )
+                    )
+                ),
+                Node::new_end(
+                    TokenType::TemporalFunction,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Symbol,
+                            vec!(
+                                Node::leaf(TokenType::SerieSelect)
+                            ),
+                            vec!(),
+                            vec!(",")
+                        )
+                    )
+                )
+            ),
+            vec!(
+                Node::new_end(
+                    TokenType::Ident,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Symbol,
+                            vec!(
+                                Node::leaf(TokenType::SerieSelect)
+                            ),
+                            vec!(),
+                            vec!(",")
+                        )
+                    )
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::FunctionCall,
+        Node::new(
+            TokenType::FunctionCall,
+            vec!(),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Symbol,
+                            vec!(),
+                            vec!(
+                                Node::new(
+                                    TokenType::Ident,
+                                    vec!(),
+                                    vec!(
+                                        Node::leaf_c(TokenType::Symbol, vec!(")"))
+                                    )
+                                ).react(push_token),
+                                Node::new_c(
+                                    TokenType::Symbol,
+                                    vec!(),
+                                    vec!(
+                                        Node::leaf_c(TokenType::Symbol, vec!(")"))
+                                    ),
+                                    vec!("*")
+                                ).react(push_token)
+                            ),
+                            vec!("(")
+                        )
+                    ),
+                    Vec::from(AGGREGATE_FUNCTIONS)
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::TemporalFunction,
+        Node::new(
+            TokenType::TemporalFunction,
+            vec!(),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        parenthesis("(", vec!(), vec!(parenthesis(")", vec!(), vec!())))
+                    ),
+                    vec!("NOW")
+                ).react(push_token),
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        parenthesis(
+                            "(",
+                            vec!(
+                                Node::new(
+                                    TokenType::Expression,
+                                    vec!(),
+                                    vec!(
+                                        parenthesis(
+                                            ",",
+                                            vec!(
+                                                Node::new(
+                                                    TokenType::Expression,
+                                                    vec!(),
+                                                    vec!(parenthesis(")", vec!(), vec!()))
+                                                )
+                                            ),
+                                            vec!()
+                                        )
+                                    )
+                                )
+                            ),
+                            vec!()
+                        )
+                    ),
+                    vec!("DATE_ADD")
+                ).react(push_token),
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        parenthesis(
+                            "(",
+                            vec!(),
+                            vec!(
+                                Node::new_c(
+                                    TokenType::Keyword,
+                                    vec!(),
+                                    vec!(
+                                        Node::new_c(
+                                            TokenType::Keyword,
+                                            vec!(
+                                                Node::new(
+                                                    TokenType::Expression,
+                                                    vec!(),
+                                                    vec!(parenthesis(")", vec!(), vec!()))
+                                                )
+                                            ),
+                                            vec!(),
+                                            vec!("FROM")
+                                        )
+                                    ),
+                                    Vec::from(TEMPORAL_FIELDS)
+                                ).react(push_token)
+                            )
+                        )
+                    ),
+                    vec!("EXTRACT")
+                ).react(push_token)
+            )
+        )
+    );
+
     group_map.insert(
         TokenType::FromWhereReq,
         Node::new(
@@ -509,22 +870,234 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                     TokenType::Keyword,
                     vec!(),
                     vec!(
-                        Node::new_end(
-                            TokenType::Ident,
+                        table_reference(|| vec!(Node::leaf(TokenType::Join)), Vec::new)
+                    ),
+                    vec!("FROM")
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::Join,
+        Node::new(
+            TokenType::Join,
+            vec!(
+                Node::leaf(TokenType::Where)
+            ),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        table_reference(Vec::new, || vec!(join_condition()))
+                    ),
+                    vec!("JOIN")
+                ).react(push_token),
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Keyword,
+                            vec!(),
+                            vec!(
+                                table_reference(Vec::new, || vec!(join_condition()))
+                            ),
+                            vec!("JOIN")
+                        ).react(push_token)
+                    ),
+                    vec!("LEFT")
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::Where,
+        Node::new(
+            TokenType::Where,
+            vec!(
+                Node::leaf(TokenType::FromWhereEnd)
+            ),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(
+                        Node::new(
+                            TokenType::Expression,
+                            vec!(
+                                Node::leaf(TokenType::FromWhereEnd)
+                            ),
+                            vec!()
+                        )
+                    ),
+                    vec!(),
+                    vec!("WHERE")
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::FromWhereEnd,
+        Node::new(
+            TokenType::FromWhereEnd,
+            vec!(
+                Node::leaf(TokenType::Having)
+            ),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Keyword,
+                            vec!(
+                                Node::new(
+                                    TokenType::SerieIdent,
+                                    vec!(
+                                        Node::leaf(TokenType::Having)
+                                    ),
+                                    vec!()
+                                )
+                            ),
+                            vec!(),
+                            vec!("BY")
+                        )
+                    ),
+                    vec!("GROUP")
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::Having,
+        Node::new(
+            TokenType::Having,
+            vec!(
+                Node::leaf(TokenType::OrderBy)
+            ),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(
+                        Node::new(
+                            TokenType::Expression,
+                            vec!(
+                                Node::leaf(TokenType::OrderBy)
+                            ),
+                            vec!()
+                        )
+                    ),
+                    vec!(),
+                    vec!("HAVING")
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::OrderBy,
+        Node::new(
+            TokenType::OrderBy,
+            vec!(
+                Node::leaf(TokenType::Limit)
+            ),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new_c(
+                            TokenType::Keyword,
+                            vec!(
+                                Node::new(
+                                    TokenType::SerieOrder,
+                                    vec!(
+                                        Node::leaf(TokenType::Limit)
+                                    ),
+                                    vec!()
+                                )
+                            ),
+                            vec!(),
+                            vec!("BY")
+                        )
+                    ),
+                    vec!("ORDER")
+                ).react(push_token)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::Limit,
+        Node::new(
+            TokenType::Limit,
+            vec!(),
+            vec!(
+                Node::new_c(
+                    TokenType::Keyword,
+                    vec!(),
+                    vec!(
+                        Node::new(
+                            TokenType::Number,
                             vec!(),
                             vec!(
                                 Node::new_c(
                                     TokenType::Keyword,
+                                    vec!(),
                                     vec!(
-                                        Node::comma_leaf(TokenType::Expression)
+                                        Node::comma_leaf(TokenType::Number).react(push_token)
                                     ),
-                                    vec!(),
-                                    vec!("WHERE")
-                                ).react(push_token)
+                                    vec!("OFFSET")
+                                ).react(push_token),
+                                Node::leaf_c(TokenType::Symbol, vec!(";")).react(end_request)
                             )
                         ).react(push_token)
                     ),
-                    vec!("FROM")
+                    vec!("LIMIT")
+                ).react(push_token),
+                Node::leaf_c(TokenType::Symbol, vec!(";")).react(end_request)
+            )
+        )
+    );
+
+    group_map.insert(
+        TokenType::SerieOrder,
+        Node::new(
+            TokenType::SerieOrder,
+            vec!(),
+            vec!(
+                Node::new_end(
+                    TokenType::Ident,
+                    vec!(),
+                    vec!(
+                        Node::new_end_c(
+                            TokenType::Keyword,
+                            vec!(),
+                            vec!(
+                                Node::new_c(
+                                    TokenType::Symbol,
+                                    vec!(
+                                        Node::leaf(TokenType::SerieOrder)
+                                    ),
+                                    vec!(),
+                                    vec!(",")
+                                )
+                            ),
+                            vec!("ASC", "DESC")
+                        ).react(push_token),
+                        Node::new_c(
+                            TokenType::Symbol,
+                            vec!(
+                                Node::leaf(TokenType::SerieOrder)
+                            ),
+                            vec!(),
+                            vec!(",")
+                        )
+                    )
                 ).react(push_token)
             )
         )
@@ -638,7 +1211,15 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
                             ),
                             vec!(),
                             vec!("TABLE")
-                        )
+                        ),
+                        Node::new_c(
+                            TokenType::Keyword,
+                            vec!(),
+                            vec!(
+                                Node::comma_leaf(TokenType::Ident).react(push_token)
+                            ),
+                            vec!("INDEX")
+                        ).react(push_token)
                     ),
                     vec!("DROP")
                 ).react(push_token)
@@ -649,4 +1230,102 @@ pub fn build_grammar_tree() -> HashMap<TokenType, Node> {
     group_map
 }
 
+/// A table name, optionally followed by an alias, with the given groups and sons after it.
+fn table_reference(groups: fn() -> Vec<Node>, sons: fn() -> Vec<Node>) -> Node {
+    let alias = || Node::new(TokenType::Ident, groups(), sons()).react(push_token);
+    let mut table_sons = sons();
+    table_sons.push(alias());
+    table_sons.push(Node::new_c(TokenType::Keyword, vec!(), vec!(alias()), vec!("AS")));
+    Node::new(TokenType::Ident, groups(), table_sons).react(push_token)
+}
+
+/// The ON clause of a join, which can be followed by another join
+fn join_condition() -> Node {
+    Node::new_c(
+        TokenType::Keyword,
+        vec!(
+            Node::new(
+                TokenType::Expression,
+                vec!(
+                    Node::leaf(TokenType::Join)
+                ),
+                vec!()
+            )
+        ),
+        vec!(),
+        vec!("ON")
+    ).react(push_token)
+}
+
+/// IS NULL or IS NOT NULL after a value, the expression can continue after it
+fn null_test() -> Node {
+    let null = || Node::new_end_c(
+        TokenType::Keyword,
+        vec!(),
+        vec!(
+            Node::new(
+                TokenType::Operator,
+                vec!(
+                    Node::leaf(TokenType::Expression)
+                ),
+                vec!()
+            ).react(push_token)
+        ),
+        vec!("NULL")
+    ).react(push_token);
+    Node::new_c(
+        TokenType::Keyword,
+        vec!(),
+        vec!(
+            Node::new_c(TokenType::Keyword, vec!(), vec!(null()), vec!("NOT")).react(push_token),
+            null()
+        ),
+        vec!("IS")
+    ).react(push_token)
+}
+
+/// The parenthesis or the comma of a function call, given to the expression of the call
+fn parenthesis(symbol: &'static str, groups: Vec<Node>, sons: Vec<Node>) -> Node {
+    let node = if groups.is_empty() && sons.is_empty() {
+        Node::leaf_c(TokenType::Symbol, vec!(symbol))
+    } else {
+        Node::new_c(TokenType::Symbol, groups, sons, vec!(symbol))
+    };
+    node.react(push_token).set_flag(Flag::Parenthesis)
+}
 
+/// ON DELETE RESTRICT, CASCADE or SET NULL after the column referenced by a foreign key
+fn on_delete() -> Node {
+    let action = |keyword| Node::new_end_c(
+        TokenType::Keyword,
+        vec!(
+            Node::leaf(TokenType::ColumnConstraint)
+        ),
+        vec!(),
+        vec!(keyword)
+    );
+    Node::new_c(
+        TokenType::Keyword,
+        vec!(),
+        vec!(
+            Node::new_c(
+                TokenType::Keyword,
+                vec!(),
+                vec!(
+                    action("RESTRICT").react(push_token).set_flag(Flag::Constraint),
+                    action("CASCADE").react(push_token).set_flag(Flag::Constraint),
+                    Node::new_c(
+                        TokenType::Keyword,
+                        vec!(),
+                        vec!(
+                            action("NULL")
+                        ),
+                        vec!("SET")
+                    ).react(push_token).set_flag(Flag::Constraint)
+                ),
+                vec!("DELETE")
+            )
+        ),
+        vec!("ON")
+    )
+}
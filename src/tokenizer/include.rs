@@ -1,4 +1,5 @@
 use super::tokenizer::Tokenizer;
+use crate::error::IrisError;
 
 use super::tokenizer::end_request;
 
@@ -21,10 +22,18 @@ pub enum TokenType {
     Request,
     CreateReq,
     ResetReq,
+    TransactionReq,
+    VacuumReq,
     InsertReq,
     SelectReq,
     UpdateReq,
     FromWhereReq,
+    Join,
+    Where,
+    FromWhereEnd,
+    Having,
+    OrderBy,
+    Limit,
     DeleteReq,
     DropReq,
     
@@ -35,8 +44,12 @@ pub enum TokenType {
     ExpressionTupple,
     SerieExpression,
     Value,
+    FunctionCall,
+    TemporalFunction,
     IdentTupple,
     SerieIdent,
+    SerieOrder,
+    SerieSelect,
 
     String,
     SerieChar,
@@ -45,13 +58,13 @@ pub enum TokenType {
     Declaration,
     PrimaryKey,
     DefaultValue,
+    ColumnConstraint,
     
     DeclarationTuple,
     SerieDeclaration,
     
     End,
     BackLine,
-    ERROR,   
     
 }
 
@@ -59,22 +72,30 @@ pub enum TokenType {
 #[derive(PartialEq)]
 pub enum Flag {
     Comma,
+    Parenthesis,
+    Constraint,
     NoFlag
 }
 
 
-pub static TYPE_LIST: &[&'static str; 3] = &["BOOL", "INT", "STRING"];
+pub static TYPE_LIST: &[&'static str; 8] = &["BOOL", "INT", "FLOAT", "REAL", "STRING", "DATE", "TIME", "TIMESTAMP"];
 pub static OPERATORS: &[&'static str; 13] = &["+", "-", "*", "/", "%", "<", ">", "<=", ">=", "==", "!=", "||", "&&"];
+/// The operator of the minus sign written before a value
+pub static NEGATION: &str = "NEG";
 pub static AFFECT_OPERATOR: &[&'static str; 1] = &["="];
-static KEYWORD: &[&'static str; 13] = &["RESET", "CREATE", "TABLE", "INTO", "DROP", "VALUES", "SELECT", "PRIMARY", "KEY", "FROM", "WHERE", "UPDATE", "SET"];
+static KEYWORD: &[&'static str; 41] = &["RESET", "BEGIN", "COMMIT", "ROLLBACK", "VACUUM", "CREATE", "TABLE", "INTO", "DROP", "VALUES", "SELECT", "PRIMARY", "KEY", "FROM", "WHERE", "UPDATE", "SET", "INDEX", "ON", "ORDER", "BY", "ASC", "DESC", "LIMIT", "OFFSET", "GROUP", "HAVING", "JOIN", "LEFT", "AS", "IS", "NOT", "NULL", "UNIQUE", "CHECK", "REFERENCES", "RESTRICT", "CASCADE", "TRUE", "FALSE", "AUTOINCREMENT"];
+pub static AGGREGATE_FUNCTIONS: &[&str; 5] = &["COUNT", "SUM", "AVG", "MIN", "MAX"];
+pub static TEMPORAL_FUNCTIONS: &[&str; 3] = &["NOW", "DATE_ADD", "EXTRACT"];
+pub static TEMPORAL_FIELDS: &[&str; 6] = &["YEAR", "MONTH", "DAY", "HOUR", "MINUTE", "SECOND"];
 pub static OPERATOR_COMPONENT: &[char; 9] = &['+', '%', '/', '<', '>', '=', '|', '&', '!'];
 pub static DEFAULT_GARBAGE_CHARACTER: &[char; 3] = &[' ', '\n', '\t'];
 static PRIMITIVE_TOKENTYPE: &[TokenType; 6] = &[TokenType::Ident, TokenType::Type, TokenType::Symbol, TokenType::Number, TokenType::Operator, TokenType::Keyword];
-pub static FAIL_MESSAGE: &str = "Syntax error";
+pub static FAIL_MESSAGE: &str = "Failed to parse the request";
 
 pub enum TokenizerMessage {
 
     Token(Token),
+    Error(IrisError),
     Tokenizer(Tokenizer)
     
 }
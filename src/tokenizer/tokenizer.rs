@@ -7,6 +7,7 @@ use std::fs::File;
 use std::str::Chars;
 use std::io::prelude::*;
 use std::sync::mpsc::Sender;
+use crate::error::IrisError;
 
 static COM_CHAR: char = '#';
 
@@ -41,7 +42,7 @@ fn build_identity_map() -> HashMap<fn(char)->bool, Vec<TokenType>> {
     res
 }
 
-fn compute_next_query(chars: &mut Peekable<Chars>) -> Result<String, String> {
+fn compute_next_query(chars: &mut Peekable<Chars>) -> Result<String, IrisError> {
     let mut query = String::new();
     let mut last = ';';
     let mut comma = false;
@@ -57,8 +58,7 @@ fn compute_next_query(chars: &mut Peekable<Chars>) -> Result<String, String> {
     }
     query = query.trim().to_string();
     if last != ';' && !query.is_empty() {
-        eprintln!("ERROR: You forgot a comma");
-        Err(query)
+        Err(IrisError::Syntax(format!("You forgot a ';' at the end of the request: {query}")))
     } else {
         Ok(query)
     }
@@ -76,20 +76,27 @@ impl<'a> Tokenizer {
     }
 
     pub fn tokenize_file(mut self, path: String) {
-        let mut file = File::open(&path).expect(&format!("File {} doesn't exists", path));
         let mut file_content = String::new();
-        file.read_to_string(&mut file_content).unwrap();
+        if let Err(e) = File::open(&path).and_then(|mut file| file.read_to_string(&mut file_content)) {
+            push_error(&self, IrisError::io(&format!("Failed to read the file {path}"), e));
+            return self.end()
+        }
         match self.precompile(file_content, &path) {
             Ok(s) => {
                 let mut chars = s.chars().peekable();
                 while chars.peek().is_some() {
-                    let query = compute_next_query(&mut chars);
-                    if query.is_err() || self.tokenize_one_query(query.unwrap()).is_err() {
-                        break;
+                    match compute_next_query(&mut chars) {
+                        Ok(query) => if self.tokenize_one_query(query).is_err() {
+                            break;
+                        },
+                        Err(e) => {
+                            push_error(&self, e);
+                            break;
+                        }
                     }
                 }
             }
-            Err(e) => push_token(&self, TokenType::ERROR, &e, Flag::NoFlag)
+            Err(e) => push_error(&self, e)
         }
         self.end()
     }
@@ -110,7 +117,7 @@ impl<'a> Tokenizer {
         self.skip_garbage(&mut chars);
         while chars.peek().is_some() {
             if self.travel(first_node, &mut chars).is_err() {
-                push_token(self, TokenType::ERROR, &FAIL_MESSAGE.to_string(), Flag::NoFlag);
+                push_error(self, IrisError::Syntax(format!("{FAIL_MESSAGE}: {}", query.trim())));
                 return Err(());
             }
             self.skip_garbage(&mut chars); 
@@ -188,6 +195,17 @@ impl<'a> Tokenizer {
                         && self.clean_son_vec(path_vec, &vec!(TokenType::Ident)) {  // If we are looking for an ident
                             self.next_char_while(&mut current_token, chars, |c: char| {is_letter(c) || is_number(c)});
                     }
+                    if current_token.starts_with(is_number)
+                        && is_decimal_dot(chars) {  // A float: digits, a dot and digits
+                            current_token.push(chars.next().unwrap());
+                            self.next_char_while(&mut current_token, chars, is_number);
+                    }
+                    if current_token.starts_with(is_letter)
+                        && is_qualifier_dot(chars)
+                        && self.clean_son_vec(path_vec, &vec!(TokenType::Ident)) {  // A column qualified by its table: table.column
+                            current_token.push(chars.next().unwrap());
+                            self.next_char_while(&mut current_token, chars, |c: char| {is_letter(c) || is_number(c)});
+                    }
                     return Ok(current_token)
                 }else{
                     return Err(format!("FAILED TO TOKENIZE"))
@@ -289,7 +307,7 @@ impl<'a> Tokenizer {
         }
     }
 
-    fn precompile(&self, input: String, current_file_path: &str) -> Result<String, String> {
+    fn precompile(&self, input: String, current_file_path: &str) -> Result<String, IrisError> {
         let mut vec: Vec<String> = input.split("$").map(String::from).collect();
         let mut annalyse_next = true;
         let mut iter = vec.iter_mut();
@@ -313,12 +331,8 @@ impl<'a> Tokenizer {
         Ok(vec.join(""))
     }
 
-    fn new_precompile_macro(&self, m: String, _current_file_path: &str) -> Result<String, String> {
-        let split: Vec<&str> = m.split_whitespace().collect();
-        match &split[0] as &str {
-            _ => Err(format!("Unknow token: {}", split[0]))
-        }
-
+    fn new_precompile_macro(&self, m: String, _current_file_path: &str) -> Result<String, IrisError> {
+        Err(IrisError::Syntax(format!("Unknow macro: {}", m.split_whitespace().next().unwrap_or_default())))
     }
 
 }
@@ -328,6 +342,15 @@ pub fn push_token(tk: &Tokenizer, token_type: TokenType, content: &String, flag:
     tk.sender.send(TokenizerMessage::Token(Token::new(token_type, content.clone(), flag))).expect("Error while sending new token");
 }
 
+pub fn push_error(tk: &Tokenizer, error: IrisError) {
+    tk.sender.send(TokenizerMessage::Error(error)).expect("Error while sending an error");
+}
+
+/// The minus sign before a value is given as the negation operator, to tell it from a subtraction
+pub fn push_negation(tk: &Tokenizer, _token_type: TokenType, _content: &String, _flag: Flag) {
+    push_token(tk, TokenType::Operator, &String::from(NEGATION), Flag::NoFlag)
+}
+
 pub fn end_request(tk: &Tokenizer, _token_type: TokenType, _content: &String, _flag: Flag) {
     push_token(tk, TokenType::End, &String::new(), Flag::NoFlag)
 }
@@ -344,6 +367,18 @@ fn is_letter(c: char) -> bool {
     (c as u8) >= 65 && (c as u8) <= 122 && !((c as u8) >= 91 && (c as u8) <= 96) || c == '_'
 }
 
+/// Indicate if the next chars are a dot followed by an identificator
+fn is_qualifier_dot(chars: &Peekable<Chars>) -> bool {
+    let mut ahead = chars.clone();
+    ahead.next() == Some('.') && ahead.next().is_some_and(is_letter)
+}
+
+/// Indicate if the next chars are a dot followed by a digit
+fn is_decimal_dot(chars: &Peekable<Chars>) -> bool {
+    let mut ahead = chars.clone();
+    ahead.next() == Some('.') && ahead.next().is_some_and(is_number)
+}
+
 fn is_operator(c: char) -> bool {
     OPERATOR_COMPONENT.contains(&c)
 }
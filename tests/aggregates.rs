@@ -0,0 +1,56 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{IrisError, Type};
+
+const PETS: &str = "CREATE TABLE Pet (id INT PRIMARY KEY, species STRING, age INT, weight FLOAT);
+    INSERT INTO Pet (id, species, age, weight) VALUES (1, 'cat', 20, 4.5);
+    INSERT INTO Pet (id, species, age, weight) VALUES (2, 'cat', 19, 3.5);
+    INSERT INTO Pet (id, species, age, weight) VALUES (3, 'dog', 17, 30.0);
+    INSERT INTO Pet (id, species, age) VALUES (4, 'fish', NULL);";
+
+#[test]
+fn aggregates_of_the_whole_table() {
+    let dir = TestDir::new("aggregates-table");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT COUNT(*), COUNT(age), SUM(age), MIN(age), MAX(species) FROM Pet;"), table(&[&["4", "3", "56", "17", "fish"]]));
+    assert_eq!(rows(&mut connection, "SELECT SUM(weight), MAX(weight) FROM Pet;"), table(&[&["38.0", "30.0"]]));
+}
+
+#[test]
+fn avg_of_an_int_column_is_a_float() {
+    let dir = TestDir::new("aggregates-avg");
+    let mut connection = database(&dir, PETS);
+    run(&mut connection, "DELETE FROM Pet WHERE id == 4;");
+    let result = connection.execute("SELECT AVG(age), AVG(weight) FROM Pet;").unwrap();
+    assert_eq!(result.types(), &vec![Type::Float, Type::Float]);
+    let avg = result.rows()[0][0].float();
+    assert!((avg - 56.0 / 3.0).abs() < 1e-9, "AVG(age) gave {avg}");
+    assert_eq!(result.rows()[0][1].string(), "12.666666666666666");
+    assert_eq!(rows(&mut connection, "SELECT AVG(age) FROM Pet WHERE id != 3;"), table(&[&["19.5"]]));
+}
+
+#[test]
+fn aggregates_without_values_are_null() {
+    let dir = TestDir::new("aggregates-empty");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT COUNT(*), SUM(age), AVG(age), MIN(age) FROM Pet WHERE id > 10;"), table(&[&["0", "NULL", "NULL", "NULL"]]));
+}
+
+#[test]
+fn group_by_and_having() {
+    let dir = TestDir::new("aggregates-group");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT species, COUNT(*), AVG(age) FROM Pet GROUP BY species;"),
+               table(&[&["cat", "2", "19.5"], &["dog", "1", "17.0"], &["fish", "1", "NULL"]]));
+    assert_eq!(rows(&mut connection, "SELECT species FROM Pet GROUP BY species HAVING AVG(age) > 18;"), table(&[&["cat"]]));
+    assert_eq!(rows(&mut connection, "SELECT species, COUNT(*) FROM Pet GROUP BY species HAVING COUNT(*) > 1;"), table(&[&["cat", "2"]]));
+}
+
+#[test]
+fn aggregates_refuse_invalid_columns() {
+    let dir = TestDir::new("aggregates-invalid");
+    let mut connection = database(&dir, PETS);
+    assert!(matches!(error(&mut connection, "SELECT SUM(species) FROM Pet;"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "SELECT AVG(*) FROM Pet;"), IrisError::InvalidRequest(_)));
+    assert!(matches!(error(&mut connection, "SELECT COUNT(size) FROM Pet;"), IrisError::UnknownColumn { .. }));
+}
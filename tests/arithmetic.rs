@@ -0,0 +1,50 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{Connection, IrisError};
+
+const NUMBERS: &str = "CREATE TABLE N (id INT PRIMARY KEY, a INT, b INT);
+    INSERT INTO N (id, a, b) VALUES (1, 7, 2);
+    INSERT INTO N (id, a, b) VALUES (2, -7, 2);";
+
+fn evaluation_error(connection: &mut Connection, sql: &str) -> String {
+    match error(connection, sql) {
+        IrisError::Evaluation(msg) => msg,
+        e => panic!("{sql} gave {e} instead of an evaluation error")
+    }
+}
+
+#[test]
+fn integer_division_truncates_toward_zero() {
+    let dir = TestDir::new("arithmetic-division");
+    let mut connection = database(&dir, NUMBERS);
+    assert_eq!(rows(&mut connection, "SELECT id FROM N WHERE a / b == 3;"), table(&[&["1"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM N WHERE a / b == -3;"), table(&[&["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM N WHERE a % b == -1;"), table(&[&["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM N WHERE 1 + a / b * 2 == 7;"), table(&[&["1"]]));
+    run(&mut connection, "SET b = a / b FROM N WHERE id == 1;");
+    assert_eq!(rows(&mut connection, "SELECT b FROM N WHERE id == 1;"), table(&[&["3"]]));
+}
+
+#[test]
+fn division_by_zero_is_an_error() {
+    let dir = TestDir::new("arithmetic-zero");
+    let mut connection = database(&dir, NUMBERS);
+    run(&mut connection, "INSERT INTO N (id, a, b) VALUES (3, 5, 0);");
+    assert!(evaluation_error(&mut connection, "SELECT id FROM N WHERE a / b > 0;").contains("Division by zero"));
+    assert!(evaluation_error(&mut connection, "SELECT id FROM N WHERE a % b > 0;").contains("Division by zero"));
+    evaluation_error(&mut connection, "SET a = a / 0 FROM N WHERE id == 1;");
+    evaluation_error(&mut connection, "INSERT INTO N (id, a, b) VALUES (4, 1 / 0, 2);");
+    assert_eq!(rows(&mut connection, "SELECT a FROM N;"), table(&[&["7"], &["-7"], &["5"]]));
+}
+
+#[test]
+fn overflows_are_errors() {
+    let dir = TestDir::new("arithmetic-overflow");
+    let mut connection = database(&dir, NUMBERS);
+    run(&mut connection, "INSERT INTO N (id, a, b) VALUES (3, 9223372036854775807, -1);");
+    for expression in ["a + 1", "a * 2", "-a - 2", "(-a - 1) / b"] {
+        let msg = evaluation_error(&mut connection, &format!("SELECT id FROM N WHERE id == 3 && {expression} > 0;"));
+        assert!(msg.contains("doesn't fit in an INT"), "{expression}: {msg}");
+    }
+    assert_eq!(rows(&mut connection, "SELECT id FROM N WHERE id == 3 && a - 1 > 0;"), table(&[&["3"]]));
+}
@@ -0,0 +1,45 @@
+mod common;
+use common::{TestDir, run, rows, table};
+use std::fs::{read_to_string, write};
+
+#[test]
+fn leftover_temporary_files_are_removed_on_open() {
+    let dir = TestDir::new("atomic-tmp");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY, v STRING); INSERT INTO A (id, v) VALUES (1, 'a');");
+    drop(connection);
+    write(dir.path().join("A.iris-tmp"), "partial").unwrap();
+    write(dir.path().join("tables.iris-tmp"), "[{").unwrap();
+    write(dir.path().join("notes.tmp"), "not ours").unwrap();
+    let mut connection = dir.open();
+    assert!(!dir.path().join("A.iris-tmp").exists());
+    assert!(!dir.path().join("tables.iris-tmp").exists());
+    assert!(dir.path().join("notes.tmp").exists());
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A;"), table(&[&["1", "a"]]));
+}
+
+#[test]
+fn the_catalog_is_always_complete() {
+    let dir = TestDir::new("atomic-catalog");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY); CREATE TABLE B (id INT PRIMARY KEY); DROP TABLE A;");
+    let catalog: serde_json::Value = serde_json::from_str(&read_to_string(dir.path().join("tables.json")).unwrap()).unwrap();
+    let names: Vec<_> = catalog.as_array().unwrap().iter().map(|table| table["name"].clone()).collect();
+    assert_eq!(names, vec!["B"]);
+    assert!(!dir.path().join("A").exists());
+}
+
+#[test]
+fn reset_removes_the_data_files() {
+    let dir = TestDir::new("atomic-reset");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY); INSERT INTO A (id) VALUES (1); CREATE TABLE B (id INT PRIMARY KEY);");
+    assert!(dir.path().join("A").exists());
+    run(&mut connection, "RESET;");
+    assert!(!dir.path().join("A").exists());
+    assert!(!dir.path().join("B").exists());
+    drop(connection);
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY);");
+    assert!(rows(&mut connection, "SELECT id FROM A;").is_empty());
+}
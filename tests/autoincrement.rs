@@ -0,0 +1,45 @@
+mod common;
+use common::{TestDir, run, rows, error, table};
+use iris::IrisError;
+
+#[test]
+fn omitted_keys_are_filled() {
+    let dir = TestDir::new("autoincrement-fill");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY AUTOINCREMENT, v STRING);
+        INSERT INTO A (v) VALUES ('a');
+        INSERT INTO A (v) VALUES ('b');");
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A;"), table(&[&["1", "a"], &["2", "b"]]));
+}
+
+#[test]
+fn explicit_keys_move_the_counter() {
+    let dir = TestDir::new("autoincrement-explicit");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY AUTOINCREMENT, v STRING);
+        INSERT INTO A (v) VALUES ('a');
+        INSERT INTO A (id, v) VALUES (10, 'b');
+        INSERT INTO A (v) VALUES ('c');");
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A;"), table(&[&["1", "a"], &["10", "b"], &["11", "c"]]));
+}
+
+#[test]
+fn the_counter_survives_a_reopen() {
+    let dir = TestDir::new("autoincrement-reopen");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY AUTOINCREMENT, v STRING);
+        INSERT INTO A (v) VALUES ('a');
+        INSERT INTO A (v) VALUES ('b');
+        DELETE FROM A WHERE id == 2;");
+    drop(connection);
+    let mut connection = dir.open();
+    run(&mut connection, "INSERT INTO A (v) VALUES ('c');");
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A;"), table(&[&["1", "a"], &["3", "c"]]));
+}
+
+#[test]
+fn only_int_keys_can_be_autoincrement() {
+    let dir = TestDir::new("autoincrement-type");
+    let mut connection = dir.open();
+    assert!(matches!(error(&mut connection, "CREATE TABLE Z (id STRING PRIMARY KEY AUTOINCREMENT);"), IrisError::TypeMismatch(_)));
+}
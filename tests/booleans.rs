@@ -0,0 +1,29 @@
+mod common;
+use common::{TestDir, run, rows, error, table};
+use iris::IrisError;
+
+#[test]
+fn bool_columns_take_true_and_false() {
+    let dir = TestDir::new("bool-literals");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE B (id INT PRIMARY KEY, alive BOOL DEFAULT TRUE);
+        INSERT INTO B (id) VALUES (1);
+        INSERT INTO B (id, alive) VALUES (2, FALSE);");
+    assert_eq!(rows(&mut connection, "SELECT id FROM B WHERE alive;"), table(&[&["1"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM B WHERE NOT alive;"), table(&[&["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM B WHERE alive == FALSE;"), table(&[&["2"]]));
+    run(&mut connection, "SET alive = 1 > 0 FROM B WHERE id == 2;");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, alive FROM B;"), table(&[&["1", "TRUE"], &["2", "TRUE"]]));
+}
+
+#[test]
+fn bool_columns_refuse_other_types() {
+    let dir = TestDir::new("bool-mismatch");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE B (id INT PRIMARY KEY, alive BOOL);");
+    assert!(matches!(error(&mut connection, "INSERT INTO B (id, alive) VALUES (1, 1);"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "INSERT INTO B (id, alive) VALUES (1, 'x');"), IrisError::TypeMismatch(_)));
+    assert!(rows(&mut connection, "SELECT id FROM B;").is_empty());
+}
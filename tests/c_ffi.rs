@@ -0,0 +1,85 @@
+// The functions declared by other_languages/iris.c, called the way a C program does.
+// This file is its own test binary so the database can be moved by changing HOME.
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use serde_json::{Value, json};
+
+// Links the library which defines the functions
+extern crate iris;
+
+extern "C" {
+    fn iris_init() -> c_int;
+    fn iris_new_request(req: *const c_char) -> c_int;
+    fn iris_load_file(path: *const c_char) -> c_int;
+    fn iris_get_results() -> *mut c_char;
+    fn iris_last_error() -> *mut c_char;
+    fn iris_free_string(string: *mut c_char);
+}
+
+/// Copy a string given by Iris and release it
+fn take_string(string: *mut c_char) -> Option<String> {
+    if string.is_null() {
+        return None
+    }
+    let copy = unsafe { CStr::from_ptr(string) }.to_string_lossy().into_owned();
+    unsafe { iris_free_string(string) };
+    Some(copy)
+}
+
+fn request(sql: &str) -> c_int {
+    let sql = CString::new(sql).unwrap();
+    unsafe { iris_new_request(sql.as_ptr()) }
+}
+
+fn results() -> Value {
+    serde_json::from_str(&take_string(unsafe { iris_get_results() }).unwrap()).unwrap()
+}
+
+fn last_error() -> Option<String> {
+    take_string(unsafe { iris_last_error() })
+}
+
+#[test]
+fn c_programs_execute_requests_and_read_the_results_as_json() {
+    let home = std::env::temp_dir().join(format!("iris-test-{}-c-ffi", std::process::id()));
+    let _ = std::fs::remove_dir_all(&home);
+    std::fs::create_dir_all(&home).unwrap();
+    std::env::set_var("HOME", &home);
+
+    assert_eq!(unsafe { iris_init() }, 0);
+    assert_eq!(last_error(), None);
+    assert_eq!(request("CREATE TABLE Pet (id INT PRIMARY KEY, name STRING); INSERT INTO Pet (id, name) VALUES (1, 'Rex');"), 0);
+    assert_eq!(request("SELECT id, name FROM Pet;"), 0);
+    assert_eq!(results(), json!([{"columns": ["id", "name"], "types": ["INT", "STRING"], "rows": [[1, "Rex"]]}]));
+
+    assert_eq!(request("SELECT age FROM Pet;"), -1);
+    assert!(last_error().unwrap().contains("age"));
+    assert_eq!(results(), json!([]));
+
+    let file = home.join("requests.sql");
+    std::fs::write(&file, "INSERT INTO Pet (id, name) VALUES (2, 'Felix');\nSELECT name FROM Pet WHERE id == 2;\n").unwrap();
+    let path = CString::new(file.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { iris_load_file(path.as_ptr()) }, 0);
+    assert_eq!(last_error(), None);
+    assert_eq!(results()[1]["rows"], json!([["Felix"]]));
+    assert!(home.join(".iris").is_dir());
+
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn the_c_header_declares_the_functions_of_the_library() {
+    let header = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/other_languages/iris.c")).unwrap();
+    let declared = header.lines()
+        .filter(|line| line.starts_with("extern "))
+        .map(|line| line.trim_end_matches(';').to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(declared, vec![
+        "extern int iris_init()",
+        "extern int iris_new_request(char* req)",
+        "extern int iris_load_file(char* path)",
+        "extern char* iris_get_results()",
+        "extern char* iris_last_error()",
+        "extern void iris_free_string(char* string)"
+    ]);
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+use iris::{Connection, IrisError};
+use std::path::{Path, PathBuf};
+
+/// A database directory of a test, removed once the test is over
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+
+    pub fn new(name: &str) -> TestDir {
+        let path = std::env::temp_dir().join(format!("iris-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        TestDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn open(&self) -> Connection {
+        Connection::open(&self.0).unwrap_or_else(|e| panic!("Failed to open the database {}: {e}", self.0.display()))
+    }
+
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Execute requests which have to succeed
+pub fn run(connection: &mut Connection, sql: &str) {
+    if let Err(e) = connection.execute_all(sql) {
+        panic!("{sql} failed: {e}")
+    }
+}
+
+/// Open the database of the test with the tables and the lines created by the requests
+pub fn database(dir: &TestDir, sql: &str) -> Connection {
+    let mut connection = dir.open();
+    run(&mut connection, sql);
+    connection
+}
+
+/// Insert a line in the table for each list of values
+pub fn insert_many(connection: &mut Connection, table_name: &str, columns: &str, lines: impl Iterator<Item = String>) {
+    let requests = lines.map(|values| format!("INSERT INTO {table_name} ({columns}) VALUES ({values});")).collect::<Vec<_>>();
+    run(connection, &requests.join("\n"));
+}
+
+/// The rows selected by the request, with each value as it is displayed
+pub fn rows(connection: &mut Connection, sql: &str) -> Vec<Vec<String>> {
+    let result = connection.execute(sql).unwrap_or_else(|e| panic!("{sql} failed: {e}"));
+    result.rows().iter().map(|row| row.iter().map(|value| value.string().clone()).collect()).collect()
+}
+
+/// The error given by a request which has to fail
+pub fn error(connection: &mut Connection, sql: &str) -> IrisError {
+    match connection.execute_all(sql) {
+        Ok(_) => panic!("{sql} succeeded while it had to fail"),
+        Err(e) => e
+    }
+}
+
+/// The rows written as `[["1", "a"], ["2", "b"]]`, to compare them in the tests
+pub fn table(expected: &[&[&str]]) -> Vec<Vec<String>> {
+    expected.iter().map(|row| row.iter().map(|value| value.to_string()).collect()).collect()
+}
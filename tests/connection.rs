@@ -0,0 +1,88 @@
+mod common;
+use common::{TestDir, run, rows, table};
+use iris::{Connection, Type};
+use serde_json::json;
+
+#[test]
+fn open_creates_the_database_directory() {
+    let dir = TestDir::new("connection-open");
+    assert!(!dir.path().exists());
+    let mut connection = Connection::open(dir.path()).unwrap();
+    assert!(dir.path().is_dir());
+    let result = connection.execute("CREATE TABLE Pet (id INT PRIMARY KEY, name STRING);").unwrap();
+    assert!(!result.has_columns());
+    assert!(result.is_empty());
+}
+
+#[test]
+fn execute_all_returns_one_result_per_request() {
+    let dir = TestDir::new("connection-all");
+    let mut connection = dir.open();
+    let results = connection.execute_all("CREATE TABLE Pet (id INT PRIMARY KEY, name STRING);
+        INSERT INTO Pet (id, name) VALUES (1, 'Rex');
+        SELECT name FROM Pet;").unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(!results[0].has_columns());
+    assert!(!results[1].has_columns());
+    assert_eq!(results[2].columns(), &vec![String::from("name")]);
+    assert_eq!(results[2].len(), 1);
+}
+
+#[test]
+fn execute_returns_the_result_of_the_last_request() {
+    let dir = TestDir::new("connection-last");
+    let mut connection = dir.open();
+    let result = connection.execute("CREATE TABLE Pet (id INT PRIMARY KEY, name STRING); INSERT INTO Pet (id, name) VALUES (1, 'Rex'); SELECT id FROM Pet;").unwrap();
+    assert_eq!(result.columns(), &vec![String::from("id")]);
+    assert!(connection.execute("").unwrap().is_empty());
+}
+
+#[test]
+fn select_results_have_the_columns_the_types_and_the_rows() {
+    let dir = TestDir::new("connection-result");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Pet (id INT PRIMARY KEY, name STRING);
+        INSERT INTO Pet (id, name) VALUES (1, 'Rex');
+        INSERT INTO Pet (id, name) VALUES (2, 'Felix');");
+    let result = connection.execute("SELECT name, id FROM Pet;").unwrap();
+    assert!(result.has_columns());
+    assert_eq!(result.columns(), &vec![String::from("name"), String::from("id")]);
+    assert_eq!(result.types(), &vec![Type::String, Type::Int]);
+    assert_eq!(result.len(), 2);
+    assert_eq!(result.rows()[1][0].string(), "Felix");
+    assert_eq!(result.to_json(), json!({
+        "columns": ["name", "id"],
+        "types": ["STRING", "INT"],
+        "rows": [["Rex", 1], ["Felix", 2]]
+    }));
+    let none = connection.execute("SELECT * FROM Pet WHERE id > 5;").unwrap();
+    assert!(none.has_columns());
+    assert!(none.is_empty());
+}
+
+#[test]
+fn lines_are_reloaded_when_the_database_is_opened_again() {
+    let dir = TestDir::new("connection-reload");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Pet (id INT PRIMARY KEY, name STRING);
+        INSERT INTO Pet (id, name) VALUES (1, 'Rex');
+        INSERT INTO Pet (id, name) VALUES (2, 'Felix');");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, name FROM Pet;"), table(&[&["1", "Rex"], &["2", "Felix"]]));
+    run(&mut connection, "INSERT INTO Pet (id, name) VALUES (3, 'Nemo'); DELETE FROM Pet WHERE id == 1;");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, name FROM Pet;"), table(&[&["2", "Felix"], &["3", "Nemo"]]));
+}
+
+#[test]
+fn execute_file_runs_the_requests_of_the_file() {
+    let dir = TestDir::new("connection-file");
+    let mut connection = dir.open();
+    let file = dir.path().join("requests.sql");
+    std::fs::write(&file, "CREATE TABLE Pet (id INT PRIMARY KEY, name STRING);\nINSERT INTO Pet (id, name) VALUES (1, 'Rex');\nSELECT name FROM Pet;\n").unwrap();
+    let results = connection.execute_file(file.to_str().unwrap()).unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[2].rows()[0][0].string(), "Rex");
+}
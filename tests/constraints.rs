@@ -0,0 +1,82 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{Connection, IrisError};
+
+const PARCELS: &str = "CREATE TABLE Parcel (id INT PRIMARY KEY, code STRING NOT NULL UNIQUE, w FLOAT CHECK (w > 0.0), n INT UNIQUE CHECK (n >= -10));";
+
+/// The message of a constraint violation, which has to name the table, the column and the value
+fn violation(connection: &mut Connection, sql: &str, column: &str, value: &str) {
+    match error(connection, sql) {
+        IrisError::ConstraintViolation(msg) => {
+            for name in ["Parcel", column, value] {
+                assert!(msg.contains(name), "{msg} doesn't name {name}");
+            }
+        }
+        e => panic!("{sql} gave {e} instead of a constraint violation")
+    }
+}
+
+#[test]
+fn not_null_refuses_null() {
+    let dir = TestDir::new("constraints-not-null");
+    let mut connection = database(&dir, PARCELS);
+    violation(&mut connection, "INSERT INTO Parcel (id, code) VALUES (1, NULL);", "code", "NULL");
+    violation(&mut connection, "INSERT INTO Parcel (id, w) VALUES (1, 1.0);", "code", "NULL");
+    run(&mut connection, "INSERT INTO Parcel (id, code) VALUES (1, 'a');");
+    violation(&mut connection, "SET code = NULL FROM Parcel WHERE id == 1;", "code", "NULL");
+    assert_eq!(rows(&mut connection, "SELECT code FROM Parcel;"), table(&[&["a"]]));
+}
+
+#[test]
+fn unique_refuses_duplicated_values() {
+    let dir = TestDir::new("constraints-unique");
+    let mut connection = database(&dir, PARCELS);
+    run(&mut connection, "INSERT INTO Parcel (id, code, n) VALUES (1, 'a', -3);");
+    violation(&mut connection, "INSERT INTO Parcel (id, code, n) VALUES (2, 'a', 4);", "code", "a");
+    violation(&mut connection, "INSERT INTO Parcel (id, code, n) VALUES (2, 'b', -3);", "n", "-3");
+    // -3 and 3 are different values
+    run(&mut connection, "INSERT INTO Parcel (id, code, n) VALUES (2, 'b', 3);");
+    violation(&mut connection, "SET n = -3 FROM Parcel WHERE id == 2;", "n", "-3");
+    violation(&mut connection, "SET code = 'c' FROM Parcel;", "code", "c");
+    assert_eq!(rows(&mut connection, "SELECT id, code, n FROM Parcel;"), table(&[&["1", "a", "-3"], &["2", "b", "3"]]));
+}
+
+#[test]
+fn unique_accepts_several_nulls_and_swapped_values() {
+    let dir = TestDir::new("constraints-unique-null");
+    let mut connection = database(&dir, PARCELS);
+    run(&mut connection, "INSERT INTO Parcel (id, code) VALUES (1, 'a'); INSERT INTO Parcel (id, code) VALUES (2, 'b');");
+    run(&mut connection, "SET n = 10 - id FROM Parcel;");
+    run(&mut connection, "SET n = id + 7 FROM Parcel;");
+    assert_eq!(rows(&mut connection, "SELECT id, n FROM Parcel;"), table(&[&["1", "8"], &["2", "9"]]));
+}
+
+#[test]
+fn check_refuses_negative_values() {
+    let dir = TestDir::new("constraints-check");
+    let mut connection = database(&dir, PARCELS);
+    violation(&mut connection, "INSERT INTO Parcel (id, code, w) VALUES (1, 'a', -1.0);", "w", "-1.0");
+    violation(&mut connection, "INSERT INTO Parcel (id, code, n) VALUES (1, 'a', -11);", "n", "-11");
+    run(&mut connection, "INSERT INTO Parcel (id, code, w, n) VALUES (1, 'a', 0.5, -10);");
+    violation(&mut connection, "SET w = -w FROM Parcel;", "w", "-0.5");
+    assert_eq!(rows(&mut connection, "SELECT w, n FROM Parcel;"), table(&[&["0.5", "-10"]]));
+}
+
+#[test]
+fn check_accepts_null() {
+    let dir = TestDir::new("constraints-check-null");
+    let mut connection = database(&dir, PARCELS);
+    run(&mut connection, "INSERT INTO Parcel (id, code, w) VALUES (1, 'a', NULL);");
+    assert_eq!(rows(&mut connection, "SELECT id FROM Parcel WHERE w IS NULL;"), table(&[&["1"]]));
+}
+
+#[test]
+fn constraints_are_kept_in_the_files() {
+    let dir = TestDir::new("constraints-reload");
+    drop(database(&dir, PARCELS));
+    let mut connection = dir.open();
+    run(&mut connection, "INSERT INTO Parcel (id, code, w) VALUES (1, 'a', 1.0);");
+    violation(&mut connection, "INSERT INTO Parcel (id, code, w) VALUES (2, 'b', -2.0);", "w", "-2.0");
+    violation(&mut connection, "INSERT INTO Parcel (id, code) VALUES (2, 'a');", "code", "a");
+    violation(&mut connection, "INSERT INTO Parcel (id, w) VALUES (2, 2.0);", "code", "NULL");
+}
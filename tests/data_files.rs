@@ -0,0 +1,70 @@
+mod common;
+use common::{TestDir, run, rows, table};
+use std::fs::{create_dir_all, read, write};
+
+const CATALOG: &str = r#"[{"auto_increment":null,"columns":[
+    {"check":null,"default_value":"","name":"id","not_null":false,"references":null,"type_col":"INT","unique":false},
+    {"check":null,"default_value":"","name":"v","not_null":false,"references":null,"type_col":"STRING","unique":false}
+],"indexes":[],"name":"A","p_key":"id"}]"#;
+
+/// A database written by an older version, with the given content in the data file of A
+fn old_database(dir: &TestDir, data: &str) {
+    create_dir_all(dir.path()).unwrap();
+    write(dir.path().join("tables.json"), CATALOG).unwrap();
+    write(dir.path().join("A"), data).unwrap();
+}
+
+#[test]
+fn every_change_persists() {
+    let dir = TestDir::new("data-persist");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE A (id INT PRIMARY KEY, v STRING);");
+    for id in 0..200 {
+        run(&mut connection, &format!("INSERT INTO A (id, v) VALUES ({id}, 'v{id}');"));
+    }
+    run(&mut connection, "SET v = 'changed' FROM A WHERE id == 5; DELETE FROM A WHERE id > 2 && id != 5;");
+    drop(connection);
+    assert!(read(dir.path().join("A")).unwrap().starts_with(b"IRISPAGE"));
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A ORDER BY id;"), table(&[&["0", "v0"], &["1", "v1"], &["2", "v2"], &["5", "changed"]]));
+}
+
+#[test]
+fn json_arrays_are_migrated() {
+    let dir = TestDir::new("data-array");
+    old_database(&dir, r#"[{"id":1,"v":"a"},{"id":2,"v":"b"}]"#);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A ORDER BY id;"), table(&[&["1", "a"], &["2", "b"]]));
+    run(&mut connection, "INSERT INTO A (id, v) VALUES (3, 'c');");
+    drop(connection);
+    assert!(read(dir.path().join("A")).unwrap().starts_with(b"IRISPAGE"));
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id FROM A ORDER BY id;"), table(&[&["1"], &["2"], &["3"]]));
+}
+
+#[test]
+fn json_records_are_migrated() {
+    let dir = TestDir::new("data-records");
+    old_database(&dir, concat!(
+        r#"{"format":"iris table","version":2}"#, "\n",
+        r#"{"insert":{"id":1,"v":"a"}}"#, "\n",
+        r#"{"insert":{"id":2,"v":"b"}}"#, "\n",
+        r#"{"update":1,"row":{"id":1,"v":"changed"}}"#, "\n",
+        r#"{"delete":2}"#, "\n"
+    ));
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, v FROM A;"), table(&[&["1", "changed"]]));
+}
+
+#[test]
+fn damaged_json_is_reported() {
+    let dir = TestDir::new("data-damaged");
+    old_database(&dir, r#"{"format":"iris table","version":2}
+{"delete":7}
+"#);
+    match iris::Connection::open(dir.path()) {
+        Err(iris::IrisError::Corrupted(msg)) => assert!(msg.contains("doesn't exist"), "{msg}"),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("the damaged data file was accepted")
+    }
+}
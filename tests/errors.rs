@@ -0,0 +1,67 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{Connection, IrisError};
+
+const PETS: &str = "CREATE TABLE Pet (id INT PRIMARY KEY, name STRING);
+    INSERT INTO Pet (id, name) VALUES (1, 'Rex');";
+
+/// Close the database and remove its log, the files are left as they were written
+fn close(dir: &TestDir) {
+    drop(database(dir, PETS));
+    std::fs::remove_file(dir.path().join("iris.wal")).unwrap();
+}
+
+/// The error given when the database of the directory is opened
+fn open_error(dir: &TestDir) -> IrisError {
+    match Connection::open(dir.path()) {
+        Ok(_) => panic!("The database {} was opened while it had to fail", dir.path().display()),
+        Err(e) => e
+    }
+}
+
+#[test]
+fn each_mistake_has_its_own_error() {
+    let dir = TestDir::new("errors-kinds");
+    let mut connection = database(&dir, PETS);
+    assert!(matches!(error(&mut connection, "SELEC id FROM Pet;"), IrisError::Syntax(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Pet"), IrisError::Syntax(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Nope;"), IrisError::UnknownTable(table) if table == "Nope"));
+    assert!(matches!(error(&mut connection, "SELECT age FROM Pet;"), IrisError::UnknownColumn { table, column } if table == "Pet" && column == "age"));
+    assert!(matches!(error(&mut connection, "INSERT INTO Pet (id, name) VALUES ('x', 'a');"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "INSERT INTO Pet (id, name) VALUES (1, 'a');"), IrisError::ConstraintViolation(_)));
+    assert!(matches!(error(&mut connection, "CREATE TABLE Pet (id INT PRIMARY KEY);"), IrisError::InvalidRequest(_)));
+}
+
+#[test]
+fn a_failed_request_leaves_the_connection_usable() {
+    let dir = TestDir::new("errors-after");
+    let mut connection = database(&dir, PETS);
+    error(&mut connection, "INSERT INTO Pet (id, name) VALUES (2, 'Felix'); SELEC id FROM Pet;");
+    run(&mut connection, "INSERT INTO Pet (id, name) VALUES (3, 'Nemo');");
+    assert_eq!(rows(&mut connection, "SELECT id, name FROM Pet;"), table(&[&["1", "Rex"], &["2", "Felix"], &["3", "Nemo"]]));
+}
+
+#[test]
+fn damaged_files_are_reported_as_corrupted() {
+    let dir = TestDir::new("errors-corrupted");
+    close(&dir);
+    std::fs::write(dir.path().join("tables.json"), "{ not json").unwrap();
+    assert!(matches!(open_error(&dir), IrisError::Corrupted(_)));
+}
+
+#[test]
+fn missing_data_files_are_reported_as_corrupted() {
+    let dir = TestDir::new("errors-missing");
+    close(&dir);
+    std::fs::remove_file(dir.path().join("Pet")).unwrap();
+    assert!(matches!(open_error(&dir), IrisError::Corrupted(_)));
+}
+
+#[test]
+fn a_database_directory_which_cant_be_created_is_an_io_error() {
+    let dir = TestDir::new("errors-io");
+    std::fs::write(dir.path(), "a file instead of the directory").unwrap();
+    let error = open_error(&dir);
+    let _ = std::fs::remove_file(dir.path());
+    assert!(matches!(error, IrisError::Io(_)));
+}
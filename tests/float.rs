@@ -0,0 +1,67 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::IrisError;
+
+const MEASURES: &str = "CREATE TABLE Measure (id INT PRIMARY KEY, n INT, w FLOAT);";
+
+#[test]
+fn decimal_literals_are_stored_in_float_columns() {
+    let dir = TestDir::new("float-literals");
+    let mut connection = database(&dir, MEASURES);
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (1, 2, 0.25); INSERT INTO Measure (id, n, w) VALUES (2, 3, 4);");
+    assert_eq!(rows(&mut connection, "SELECT id, w FROM Measure;"), table(&[&["1", "0.25"], &["2", "4.0"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Measure WHERE w * 2 > n;"), table(&[&["2"]]));
+    assert!(matches!(error(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (3, 1.5, 1.0);"), IrisError::TypeMismatch(_)));
+}
+
+#[test]
+fn negative_literals_keep_their_sign_in_insert() {
+    let dir = TestDir::new("float-negative-insert");
+    let mut connection = database(&dir, MEASURES);
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (1, -5, -2.5);");
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (-2, 5 - -3, 2.5 * -2);");
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (3, -(2 + 4) * 2, - -1.5);");
+    assert_eq!(rows(&mut connection, "SELECT id, n, w FROM Measure;"), table(&[&["1", "-5", "-2.5"], &["-2", "8", "-5.0"], &["3", "-12", "1.5"]]));
+}
+
+#[test]
+fn negative_literals_keep_their_sign_in_set() {
+    let dir = TestDir::new("float-negative-set");
+    let mut connection = database(&dir, MEASURES);
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (1, 1, 1.0); INSERT INTO Measure (id, n, w) VALUES (2, 2, 2.0);");
+    run(&mut connection, "SET n = -1, w = -w - 0.5 FROM Measure WHERE id == 2;");
+    assert_eq!(rows(&mut connection, "SELECT id, n, w FROM Measure;"), table(&[&["1", "1", "1.0"], &["2", "-1", "-2.5"]]));
+}
+
+#[test]
+fn negative_literals_keep_their_sign_in_where() {
+    let dir = TestDir::new("float-negative-where");
+    let mut connection = database(&dir, MEASURES);
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (1, 5, 2.5); INSERT INTO Measure (id, n, w) VALUES (-5, -5, -2.5);");
+    assert_eq!(rows(&mut connection, "SELECT id FROM Measure WHERE n == -5;"), table(&[&["-5"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Measure WHERE w < -1.0;"), table(&[&["-5"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Measure WHERE -n > 0;"), table(&[&["-5"]]));
+    // The primary key index is used for the negative keys too
+    assert_eq!(rows(&mut connection, "SELECT n FROM Measure WHERE id == -5;"), table(&[&["-5"]]));
+}
+
+#[test]
+fn negative_values_are_kept_in_the_files() {
+    let dir = TestDir::new("float-negative-reload");
+    let mut connection = database(&dir, MEASURES);
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (1, -7, -0.125);");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT n, w FROM Measure;"), table(&[&["-7", "-0.125"]]));
+}
+
+#[test]
+fn a_misplaced_minus_is_a_syntax_error() {
+    let dir = TestDir::new("float-minus-syntax");
+    let mut connection = database(&dir, MEASURES);
+    run(&mut connection, "INSERT INTO Measure (id, n, w) VALUES (1, 5, 2.5);");
+    assert!(matches!(error(&mut connection, "SELECT id FROM Measure WHERE n == -;"), IrisError::Syntax(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Measure WHERE n ~ 5;"), IrisError::Syntax(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Measure WHERE n == 5 -;"), IrisError::Syntax(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Measure WHERE n == -'a';"), IrisError::TypeMismatch(_)));
+}
@@ -0,0 +1,75 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{IrisError};
+
+/// A parent table and a child table referencing it with the given ON DELETE clause
+fn parents(on_delete: &str) -> String {
+    format!("CREATE TABLE P (id INT PRIMARY KEY, name STRING);
+        CREATE TABLE C (id INT PRIMARY KEY, p INT REFERENCES P(id){on_delete});
+        INSERT INTO P (id, name) VALUES (1, 'a');
+        INSERT INTO P (id, name) VALUES (2, 'b');
+        INSERT INTO C (id, p) VALUES (1, 1);
+        INSERT INTO C (id, p) VALUES (2, NULL);")
+}
+
+#[test]
+fn references_are_checked_on_insert_and_set() {
+    let dir = TestDir::new("fk-check");
+    let mut connection = database(&dir, &parents(""));
+    assert!(matches!(error(&mut connection, "INSERT INTO C (id, p) VALUES (3, 9);"), IrisError::ConstraintViolation(_)));
+    assert!(matches!(error(&mut connection, "SET p = 9 FROM C WHERE id == 1;"), IrisError::ConstraintViolation(_)));
+    run(&mut connection, "SET p = 2 FROM C WHERE id == 2;");
+    assert_eq!(rows(&mut connection, "SELECT id, p FROM C;"), table(&[&["1", "1"], &["2", "2"]]));
+}
+
+#[test]
+fn restrict_refuses_to_delete_or_modify_a_referenced_line() {
+    let dir = TestDir::new("fk-restrict");
+    let mut connection = database(&dir, &parents(" ON DELETE RESTRICT"));
+    assert!(matches!(error(&mut connection, "DELETE FROM P WHERE id == 1;"), IrisError::ConstraintViolation(_)));
+    assert!(matches!(error(&mut connection, "SET id = 10 FROM P WHERE id == 1;"), IrisError::ConstraintViolation(_)));
+    assert!(error(&mut connection, "DROP TABLE P;").to_string().contains("references it"));
+    run(&mut connection, "DELETE FROM P WHERE id == 2;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM P;"), table(&[&["1"]]));
+}
+
+#[test]
+fn restrict_is_the_default() {
+    let dir = TestDir::new("fk-default");
+    let mut connection = database(&dir, &parents(""));
+    assert!(matches!(error(&mut connection, "DELETE FROM P WHERE id == 1;"), IrisError::ConstraintViolation(_)));
+}
+
+#[test]
+fn cascade_deletes_the_children() {
+    let dir = TestDir::new("fk-cascade");
+    let mut connection = database(&dir, &parents(" ON DELETE CASCADE"));
+    run(&mut connection, "DELETE FROM P WHERE id == 1;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM C;"), table(&[&["2"]]));
+}
+
+#[test]
+fn set_null_clears_the_children() {
+    let dir = TestDir::new("fk-set-null");
+    let mut connection = database(&dir, &parents(" ON DELETE SET NULL"));
+    run(&mut connection, "DELETE FROM P WHERE id == 1;");
+    assert_eq!(rows(&mut connection, "SELECT id, p FROM C;"), table(&[&["1", "NULL"], &["2", "NULL"]]));
+}
+
+#[test]
+fn references_survive_a_reopen() {
+    let dir = TestDir::new("fk-reopen");
+    drop(database(&dir, &parents(" ON DELETE CASCADE")));
+    let mut connection = dir.open();
+    assert!(matches!(error(&mut connection, "INSERT INTO C (id, p) VALUES (3, 9);"), IrisError::ConstraintViolation(_)));
+    run(&mut connection, "DELETE FROM P WHERE id == 1;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM C;"), table(&[&["2"]]));
+}
+
+#[test]
+fn invalid_references_are_refused() {
+    let dir = TestDir::new("fk-invalid");
+    let mut connection = database(&dir, &parents(""));
+    assert!(error(&mut connection, "CREATE TABLE X (id INT PRIMARY KEY, p INT REFERENCES Nope(id));").to_string().contains("Nope"));
+    assert!(error(&mut connection, "CREATE TABLE Y (id INT PRIMARY KEY, p INT REFERENCES P(name));").to_string().contains("UNIQUE"));
+}
@@ -0,0 +1,58 @@
+mod common;
+use common::{TestDir, database, insert_many, run, rows, error, table};
+use iris::{Connection, IrisError};
+
+fn measures(dir: &TestDir) -> Connection {
+    let mut connection = database(dir, "CREATE TABLE Measure (id INT PRIMARY KEY, n INT, city STRING);");
+    insert_many(&mut connection, "Measure", "id, n, city", (0..40).map(|i| format!("{i}, {}, 'c{}'", (i * 7) % 20 - 5, i % 4)));
+    connection
+}
+
+/// The ids selected by the condition, with and without the index giving the same lines
+fn ids(connection: &mut Connection, condition: &str) -> Vec<Vec<String>> {
+    rows(connection, &format!("SELECT id FROM Measure WHERE {condition};"))
+}
+
+#[test]
+fn indexes_give_the_same_lines_as_a_scan() {
+    let dir = TestDir::new("indexes-scan");
+    let mut connection = measures(&dir);
+    let conditions = ["n == 2", "n < 0", "n <= -5", "n > 10", "n >= 3 && n < 8", "n > 3 && n < 3", "-3 < n && city == 'c1'", "city == 'c2'", "city >= 'c2'", "n == 2 || n == 4", "n != 2"];
+    let scanned = conditions.iter().map(|condition| ids(&mut connection, condition)).collect::<Vec<_>>();
+    run(&mut connection, "CREATE INDEX by_n ON Measure (n); CREATE INDEX by_city ON Measure (city);");
+    for (condition, expected) in conditions.iter().zip(scanned) {
+        assert_eq!(ids(&mut connection, condition), expected, "{condition}");
+    }
+    assert_eq!(ids(&mut connection, "n == 2"), table(&[&["1"], &["21"]]));
+}
+
+#[test]
+fn indexes_follow_the_changes_and_are_persisted() {
+    let dir = TestDir::new("indexes-changes");
+    let mut connection = measures(&dir);
+    run(&mut connection, "CREATE INDEX by_n ON Measure (n);
+        DELETE FROM Measure WHERE id == 1;
+        SET n = 100 FROM Measure WHERE id == 21;
+        INSERT INTO Measure (id, n, city) VALUES (50, 2, 'c9');");
+    assert_eq!(ids(&mut connection, "n == 2"), table(&[&["50"]]));
+    assert_eq!(ids(&mut connection, "n >= 100"), table(&[&["21"]]));
+    drop(connection);
+    let mut connection = dir.open();
+    assert!(matches!(error(&mut connection, "CREATE INDEX by_n ON Measure (city);"), IrisError::InvalidRequest(_)));
+    assert_eq!(ids(&mut connection, "n == 2"), table(&[&["50"]]));
+    assert_eq!(ids(&mut connection, "n > 99"), table(&[&["21"]]));
+}
+
+#[test]
+fn drop_index_removes_it() {
+    let dir = TestDir::new("indexes-drop");
+    let mut connection = measures(&dir);
+    assert!(matches!(error(&mut connection, "CREATE INDEX by_x ON Measure (x);"), IrisError::UnknownColumn { .. }));
+    assert!(matches!(error(&mut connection, "DROP INDEX by_n;"), IrisError::InvalidRequest(_)));
+    run(&mut connection, "CREATE INDEX by_n ON Measure (n); DROP INDEX by_n;");
+    drop(connection);
+    let mut connection = dir.open();
+    assert!(matches!(error(&mut connection, "DROP INDEX by_n;"), IrisError::InvalidRequest(_)));
+    run(&mut connection, "CREATE INDEX by_n ON Measure (city);");
+    assert_eq!(ids(&mut connection, "city == 'c3' && n < 0"), table(&[&["3"], &["23"]]));
+}
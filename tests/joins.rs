@@ -0,0 +1,69 @@
+mod common;
+use common::{TestDir, database, rows, error, table};
+use iris::{IrisError};
+
+const LIBRARY: &str = "CREATE TABLE Author (id INT PRIMARY KEY, name STRING, mentor INT);
+    CREATE TABLE Book (id INT PRIMARY KEY, author INT, title STRING);
+    INSERT INTO Author (id, name) VALUES (1, 'Ann');
+    INSERT INTO Author (id, name, mentor) VALUES (2, 'Bob', 1);
+    INSERT INTO Author (id, name, mentor) VALUES (3, 'Cid', 1);
+    INSERT INTO Book (id, author, title) VALUES (10, 2, 'Rain');
+    INSERT INTO Book (id, author, title) VALUES (11, 1, 'Sun');
+    INSERT INTO Book (id, author, title) VALUES (12, 2, 'Wind');
+    INSERT INTO Book (id, author, title) VALUES (13, 9, 'Lost');";
+
+#[test]
+fn inner_join_keeps_the_matching_lines() {
+    let dir = TestDir::new("joins-inner");
+    let mut connection = database(&dir, LIBRARY);
+    assert_eq!(rows(&mut connection, "SELECT Author.name, Book.title FROM Author JOIN Book ON Author.id == Book.author;"),
+        table(&[&["Ann", "Sun"], &["Bob", "Rain"], &["Bob", "Wind"]]));
+    assert!(rows(&mut connection, "SELECT name, title FROM Book JOIN Author ON Author.id == author WHERE name == 'Cid';").is_empty());
+    assert_eq!(rows(&mut connection, "SELECT name, title FROM Book AS b JOIN Author AS a ON a.id == b.author WHERE title > 'S' ORDER BY title DESC;"),
+        table(&[&["Bob", "Wind"], &["Ann", "Sun"]]));
+}
+
+#[test]
+fn left_join_keeps_the_lines_without_match() {
+    let dir = TestDir::new("joins-left");
+    let mut connection = database(&dir, LIBRARY);
+    assert_eq!(rows(&mut connection, "SELECT Author.name, Book.title FROM Author LEFT JOIN Book ON Author.id == Book.author;"),
+        table(&[&["Ann", "Sun"], &["Bob", "Rain"], &["Bob", "Wind"], &["Cid", "NULL"]]));
+    assert_eq!(rows(&mut connection, "SELECT Book.title FROM Book LEFT JOIN Author ON Author.id == Book.author WHERE Author.id IS NULL;"), table(&[&["Lost"]]));
+    assert_eq!(rows(&mut connection, "SELECT Author.name, COUNT(Book.id) FROM Author LEFT JOIN Book ON Author.id == Book.author GROUP BY Author.name;"),
+        table(&[&["Ann", "1"], &["Bob", "2"], &["Cid", "0"]]));
+}
+
+#[test]
+fn a_table_is_joined_to_itself_with_aliases() {
+    let dir = TestDir::new("joins-self");
+    let mut connection = database(&dir, LIBRARY);
+    assert_eq!(rows(&mut connection, "SELECT student.name, teacher.name FROM Author AS student JOIN Author AS teacher ON student.mentor == teacher.id;"),
+        table(&[&["Bob", "Ann"], &["Cid", "Ann"]]));
+    assert!(matches!(error(&mut connection, "SELECT name FROM Author JOIN Author ON id == mentor;"), IrisError::InvalidRequest(_)));
+}
+
+#[test]
+fn columns_are_resolved_in_the_joined_tables() {
+    let dir = TestDir::new("joins-columns");
+    let mut connection = database(&dir, LIBRARY);
+    assert!(matches!(error(&mut connection, "SELECT id FROM Author JOIN Book ON Author.id == Book.author;"), IrisError::InvalidRequest(_)));
+    assert!(matches!(error(&mut connection, "SELECT Shelf.id FROM Author JOIN Book ON Author.id == Book.author;"), IrisError::UnknownTable(_)));
+    assert!(matches!(error(&mut connection, "SELECT Book.pages FROM Author JOIN Book ON Author.id == Book.author;"), IrisError::UnknownColumn { .. }));
+    assert!(matches!(error(&mut connection, "SELECT title FROM Author JOIN Shelf ON Author.id == Shelf.id;"), IrisError::UnknownTable(_)));
+    assert_eq!(rows(&mut connection, "SELECT name, title FROM Author JOIN Book ON Author.id == author WHERE mentor == 1;"), table(&[&["Bob", "Rain"], &["Bob", "Wind"]]));
+}
+
+#[test]
+fn on_clauses_only_use_the_tables_joined_so_far() {
+    let dir = TestDir::new("joins-on-scope");
+    let mut connection = database(&dir, LIBRARY);
+    let later = "SELECT b.title FROM Book AS b JOIN Author AS a ON a.mentor == m.id JOIN Author AS m ON m.id == b.author;";
+    match error(&mut connection, later) {
+        IrisError::InvalidRequest(msg) => assert!(msg.contains("joined after"), "{msg}"),
+        e => panic!("unexpected error: {e}")
+    }
+    assert!(matches!(error(&mut connection, "SELECT title FROM Book JOIN Author ON Author.id == Nope.id;"), IrisError::UnknownTable(_)));
+    assert_eq!(rows(&mut connection, "SELECT b.title, m.name FROM Book AS b JOIN Author AS a ON a.id == b.author JOIN Author AS m ON m.id == a.mentor;"),
+        table(&[&["Rain", "Ann"], &["Wind", "Ann"]]));
+}
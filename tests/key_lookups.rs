@@ -0,0 +1,62 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{IrisError};
+
+const PEOPLE: &str = "CREATE TABLE Person (id INT PRIMARY KEY, name STRING, age INT);
+    INSERT INTO Person (id, name, age) VALUES (1, 'Ann', 30);
+    INSERT INTO Person (id, name, age) VALUES (2, 'Bob', 25);
+    INSERT INTO Person (id, name, age) VALUES (3, 'Cid', 30);";
+
+#[test]
+fn point_lookups_on_the_primary_key() {
+    let dir = TestDir::new("key-lookups");
+    let mut connection = database(&dir, PEOPLE);
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 2;"), table(&[&["Bob"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE 3 == id && age == 30;"), table(&[&["Cid"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 2 && age == 30;"), table(&[]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 4;"), table(&[]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 1 || id == 3;"), table(&[&["Ann"], &["Cid"]]));
+}
+
+#[test]
+fn the_index_follows_the_changes() {
+    let dir = TestDir::new("key-lookups-changes");
+    let mut connection = database(&dir, PEOPLE);
+    run(&mut connection, "DELETE FROM Person WHERE id == 1;");
+    run(&mut connection, "SET id = 10 FROM Person WHERE id == 3;");
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 3;"), table(&[]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 10;"), table(&[&["Cid"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 2;"), table(&[&["Bob"]]));
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 10;"), table(&[&["Cid"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person WHERE id == 1;"), table(&[]));
+}
+
+#[test]
+fn type_errors_dont_depend_on_the_index() {
+    let dir = TestDir::new("key-lookups-types");
+    let mut connection = database(&dir, PEOPLE);
+    run(&mut connection, "CREATE INDEX by_age ON Person (age);");
+    for sql in [
+        "SELECT id FROM Person WHERE id == 1 && name == 3;",
+        "SELECT id FROM Person WHERE id == 7 && name == 3;",
+        "SELECT id FROM Person WHERE age == 99 && name == 3;",
+        "SELECT id FROM Person WHERE name == 3;",
+        "SELECT id FROM Person WHERE id == 1 && age;",
+        "SET age = 1 FROM Person WHERE id == 7 && name > 1;",
+        "DELETE FROM Person WHERE id == 7 && name > 1;"
+    ] {
+        assert!(matches!(error(&mut connection, sql), IrisError::TypeMismatch(_)), "{sql} didn't give a type mismatch");
+    }
+    run(&mut connection, "DELETE FROM Person;");
+    assert!(matches!(error(&mut connection, "SELECT id FROM Person WHERE name == 3;"), IrisError::TypeMismatch(_)));
+}
+
+#[test]
+fn value_errors_are_found_on_the_lines() {
+    let dir = TestDir::new("key-lookups-values");
+    let mut connection = database(&dir, PEOPLE);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Person WHERE id == 9 && age / 0 == 1;"), table(&[]));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Person WHERE id == 1 && age / 0 == 1;"), IrisError::Evaluation(_)));
+}
@@ -0,0 +1,40 @@
+mod common;
+use common::{TestDir, database, insert_many, run, rows, error, table};
+use iris::{Connection, IrisError};
+
+fn numbers(dir: &TestDir) -> Connection {
+    let mut connection = database(dir, "CREATE TABLE Number (id INT PRIMARY KEY, n INT);");
+    insert_many(&mut connection, "Number", "id, n", (1..=10).map(|i| format!("{i}, {}", i % 3)));
+    connection
+}
+
+#[test]
+fn select_keeps_the_asked_lines() {
+    let dir = TestDir::new("limit-select");
+    let mut connection = numbers(&dir);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Number LIMIT 3;"), table(&[&["1"], &["2"], &["3"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Number LIMIT 2 OFFSET 8;"), table(&[&["9"], &["10"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Number WHERE n == 0 LIMIT 2 OFFSET 1;"), table(&[&["6"], &["9"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Number ORDER BY id DESC LIMIT 2 OFFSET 1;"), table(&[&["9"], &["8"]]));
+    assert!(rows(&mut connection, "SELECT id FROM Number LIMIT 0;").is_empty());
+    assert!(rows(&mut connection, "SELECT id FROM Number LIMIT 5 OFFSET 20;").is_empty());
+}
+
+#[test]
+fn delete_and_set_stop_at_the_limit() {
+    let dir = TestDir::new("limit-changes");
+    let mut connection = numbers(&dir);
+    run(&mut connection, "DELETE FROM Number WHERE n == 1 LIMIT 2;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM Number WHERE n == 1;"), table(&[&["7"], &["10"]]));
+    run(&mut connection, "SET n = 50 FROM Number WHERE n == 2 LIMIT 1 OFFSET 1;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM Number WHERE n == 50;"), table(&[&["5"]]));
+}
+
+#[test]
+fn invalid_counts_are_refused() {
+    let dir = TestDir::new("limit-invalid");
+    let mut connection = numbers(&dir);
+    assert!(matches!(error(&mut connection, "SELECT id FROM Number LIMIT 99999999999999999999999;"), IrisError::InvalidRequest(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Number LIMIT 1.5;"), IrisError::InvalidRequest(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Number LIMIT -1;"), IrisError::Syntax(_)));
+}
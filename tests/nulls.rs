@@ -0,0 +1,46 @@
+mod common;
+use common::{TestDir, database, run, rows, table};
+
+const PEOPLE: &str = "CREATE TABLE P (id INT PRIMARY KEY, name STRING, age INT, ok BOOL);
+    INSERT INTO P (id, name) VALUES (1, 'a');
+    INSERT INTO P (id, name, age, ok) VALUES (2, NULL, 5, TRUE);
+    INSERT INTO P (id, name, age, ok) VALUES (3, 'c', 7, FALSE);";
+
+#[test]
+fn missing_columns_are_null() {
+    let dir = TestDir::new("nulls-missing");
+    let mut connection = database(&dir, PEOPLE);
+    assert_eq!(rows(&mut connection, "SELECT age, name, ok FROM P WHERE id == 1;"), table(&[&["NULL", "a", "NULL"]]));
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT name FROM P WHERE id == 2;"), table(&[&["NULL"]]));
+}
+
+#[test]
+fn is_null_tests_the_values() {
+    let dir = TestDir::new("nulls-is-null");
+    let mut connection = database(&dir, PEOPLE);
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE age IS NULL;"), table(&[&["1"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE name IS NOT NULL;"), table(&[&["1"], &["3"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE age + 1 IS NULL;"), table(&[&["1"]]));
+    run(&mut connection, "SET age = NULL FROM P WHERE id == 3;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE age IS NULL;"), table(&[&["1"], &["3"]]));
+}
+
+#[test]
+fn conditions_follow_the_three_valued_logic() {
+    let dir = TestDir::new("nulls-logic");
+    let mut connection = database(&dir, PEOPLE);
+    assert!(rows(&mut connection, "SELECT id FROM P WHERE age == NULL;").is_empty());
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE NOT (age > 6);"), table(&[&["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE age > 1 || age IS NULL;"), table(&[&["1"], &["2"], &["3"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE ok || age > 100;"), table(&[&["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM P WHERE NOT (ok && age > 100);"), table(&[&["2"], &["3"]]));
+}
+
+#[test]
+fn aggregates_skip_the_null_values() {
+    let dir = TestDir::new("nulls-aggregates");
+    let mut connection = database(&dir, PEOPLE);
+    assert_eq!(rows(&mut connection, "SELECT COUNT(age), COUNT(*), SUM(age), MIN(age) FROM P;"), table(&[&["2", "3", "12", "5"]]));
+}
@@ -0,0 +1,43 @@
+mod common;
+use common::{TestDir, database, rows, error, table};
+use iris::{IrisError};
+
+const PETS: &str = "CREATE TABLE Pet (id INT PRIMARY KEY, name STRING, age INT, tame BOOL);
+    INSERT INTO Pet (id, name, age, tame) VALUES (1, 'rex', 3, TRUE);
+    INSERT INTO Pet (id, name, age, tame) VALUES (2, 'Bella', 10, FALSE);
+    INSERT INTO Pet (id, name, age, tame) VALUES (3, 'bella', 3, TRUE);
+    INSERT INTO Pet (id, name, age) VALUES (4, 'max', -2);
+    INSERT INTO Pet (id, name, tame) VALUES (5, 'abe', FALSE);";
+
+#[test]
+fn numbers_are_sorted_by_value() {
+    let dir = TestDir::new("order-numbers");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Pet ORDER BY age;"), table(&[&["5"], &["4"], &["1"], &["3"], &["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Pet ORDER BY age ASC;"), table(&[&["5"], &["4"], &["1"], &["3"], &["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Pet ORDER BY age DESC;"), table(&[&["2"], &["1"], &["3"], &["4"], &["5"]]));
+}
+
+#[test]
+fn strings_are_sorted_by_content() {
+    let dir = TestDir::new("order-strings");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet ORDER BY name;"), table(&[&["Bella"], &["abe"], &["bella"], &["max"], &["rex"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE age > 0 ORDER BY name DESC;"), table(&[&["rex"], &["bella"], &["Bella"]]));
+}
+
+#[test]
+fn several_columns_break_the_ties() {
+    let dir = TestDir::new("order-several");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Pet ORDER BY tame, age DESC;"), table(&[&["4"], &["2"], &["5"], &["1"], &["3"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Pet ORDER BY age, name DESC;"), table(&[&["5"], &["4"], &["1"], &["3"], &["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet ORDER BY tame DESC, id;"), table(&[&["rex"], &["bella"], &["Bella"], &["abe"], &["max"]]));
+}
+
+#[test]
+fn unknown_columns_are_refused() {
+    let dir = TestDir::new("order-unknown");
+    let mut connection = database(&dir, PETS);
+    assert!(matches!(error(&mut connection, "SELECT id FROM Pet ORDER BY weight;"), IrisError::UnknownColumn { .. }));
+}
@@ -0,0 +1,49 @@
+mod common;
+use common::{TestDir, run, rows, error, table};
+use iris::IrisError;
+
+fn duplicated(e: IrisError) -> bool {
+    matches!(e, IrisError::ConstraintViolation(msg) if msg.contains("primary key"))
+}
+
+#[test]
+fn insert_refuses_a_duplicated_key() {
+    let dir = TestDir::new("p-key-insert");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Person (id INT PRIMARY KEY, name STRING); INSERT INTO Person (id, name) VALUES (2, 'Ann');");
+    assert!(duplicated(error(&mut connection, "INSERT INTO Person (id, name) VALUES (2, 'Bob');")));
+    assert!(matches!(error(&mut connection, "INSERT INTO Person (name) VALUES ('Cid');"), IrisError::ConstraintViolation(_)));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Person;"), table(&[&["Ann"]]));
+    drop(connection);
+    let mut connection = dir.open();
+    assert!(duplicated(error(&mut connection, "INSERT INTO Person (id, name) VALUES (2, 'Bob');")));
+    run(&mut connection, "INSERT INTO Person (id, name) VALUES (3, 'Bob');");
+}
+
+#[test]
+fn set_refuses_a_duplicated_key() {
+    let dir = TestDir::new("p-key-set");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Person (id INT PRIMARY KEY, name STRING);
+        INSERT INTO Person (id, name) VALUES (1, 'Ann');
+        INSERT INTO Person (id, name) VALUES (2, 'Bob');
+        INSERT INTO Person (id, name) VALUES (3, 'Cid');");
+    assert!(duplicated(error(&mut connection, "SET id = 2 FROM Person WHERE id == 1;")));
+    assert!(duplicated(error(&mut connection, "SET id = 10 FROM Person WHERE id > 1;")));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Person;"), table(&[&["1"], &["2"], &["3"]]));
+    run(&mut connection, "SET id = id + 1 FROM Person WHERE id > 1;");
+    run(&mut connection, "SET id = 2 FROM Person WHERE id == 1;");
+    assert_eq!(rows(&mut connection, "SELECT id, name FROM Person;"), table(&[&["2", "Ann"], &["3", "Bob"], &["4", "Cid"]]));
+}
+
+#[test]
+fn a_deleted_key_can_be_used_again() {
+    let dir = TestDir::new("p-key-delete");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Tag (label STRING PRIMARY KEY, n INT);
+        INSERT INTO Tag (label, n) VALUES ('red', 1);
+        INSERT INTO Tag (label, n) VALUES ('blue', 2);");
+    assert!(duplicated(error(&mut connection, "INSERT INTO Tag (label, n) VALUES ('red', 3);")));
+    run(&mut connection, "DELETE FROM Tag WHERE label == 'red'; INSERT INTO Tag (label, n) VALUES ('red', 3);");
+    assert_eq!(rows(&mut connection, "SELECT n FROM Tag WHERE label == 'red';"), table(&[&["3"]]));
+}
@@ -0,0 +1,142 @@
+mod common;
+use common::{TestDir, insert_many, run, rows, error, table};
+use iris::{IrisError};
+
+#[test]
+fn unique_values_are_found_after_changes_and_reload() {
+    let dir = TestDir::new("storage-unique");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE User (id INT PRIMARY KEY, mail STRING UNIQUE, n INT);");
+    insert_many(&mut connection, "User", "id, mail, n", (0..50).map(|i| format!("{i}, 'u{i}', {i}")));
+    run(&mut connection, "DELETE FROM User WHERE n < 10; SET mail = 'moved' FROM User WHERE id == 20;");
+    run(&mut connection, "INSERT INTO User (id, mail, n) VALUES (100, 'u5', 0); INSERT INTO User (id, mail, n) VALUES (101, 'u20', 0);");
+    assert!(matches!(error(&mut connection, "INSERT INTO User (id, mail, n) VALUES (102, 'u30', 0);"), IrisError::ConstraintViolation(_)));
+    drop(connection);
+    let mut connection = dir.open();
+    assert!(matches!(error(&mut connection, "INSERT INTO User (id, mail, n) VALUES (102, 'moved', 0);"), IrisError::ConstraintViolation(_)));
+    assert!(matches!(error(&mut connection, "SET mail = 'u49' FROM User WHERE id == 48;"), IrisError::ConstraintViolation(_)));
+    assert_eq!(rows(&mut connection, "SELECT id FROM User WHERE mail == 'u20';"), table(&[&["101"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM User WHERE mail == 'u5';"), table(&[&["100"]]));
+}
+
+#[test]
+fn referencing_lines_are_found_after_deletions() {
+    let dir = TestDir::new("storage-foreign-keys");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Parent (id INT PRIMARY KEY);
+        CREATE TABLE Child (id INT PRIMARY KEY, parent INT REFERENCES Parent(id) ON DELETE CASCADE);
+        CREATE TABLE Toy (id INT PRIMARY KEY, child INT REFERENCES Child(id) ON DELETE SET NULL);");
+    insert_many(&mut connection, "Parent", "id", (0..10).map(|i| i.to_string()));
+    insert_many(&mut connection, "Child", "id, parent", (0..40).map(|i| format!("{i}, {}", i % 10)));
+    insert_many(&mut connection, "Toy", "id, child", (0..40).map(|i| format!("{i}, {i}")));
+    run(&mut connection, "DELETE FROM Parent WHERE id == 3;");
+    assert_eq!(rows(&mut connection, "SELECT id FROM Toy WHERE child IS NULL ORDER BY id;"), table(&[&["3"], &["13"], &["23"], &["33"]]));
+    drop(connection);
+    let mut connection = dir.open();
+    run(&mut connection, "DELETE FROM Parent WHERE id == 7;");
+    assert_eq!(rows(&mut connection, "SELECT COUNT(id) FROM Child;"), table(&[&["32"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Child WHERE parent == 8 ORDER BY id;"), table(&[&["8"], &["18"], &["28"], &["38"]]));
+    assert!(matches!(error(&mut connection, "INSERT INTO Child (id, parent) VALUES (50, 3);"), IrisError::ConstraintViolation(_)));
+}
+
+#[test]
+fn deleted_lines_are_renumbered_in_the_indexes() {
+    let dir = TestDir::new("storage-delete");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Item (id INT PRIMARY KEY, n INT); CREATE INDEX by_n ON Item (n);");
+    insert_many(&mut connection, "Item", "id, n", (0..1200).map(|i| format!("{i}, {}", i % 7)));
+    run(&mut connection, "DELETE FROM Item WHERE id % 3 != 0 || id > 1190;");
+    assert_eq!(rows(&mut connection, "SELECT COUNT(id) FROM Item;"), table(&[&["397"]]));
+    assert_eq!(rows(&mut connection, "SELECT n FROM Item WHERE id == 1188;"), table(&[&["5"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Item WHERE n == 6 && id > 1100 ORDER BY id;"), table(&[&["1119"], &["1140"], &["1161"], &["1182"]]));
+    assert!(matches!(error(&mut connection, "INSERT INTO Item (id, n) VALUES (1188, 0);"), IrisError::ConstraintViolation(_)));
+}
+
+#[test]
+fn joins_read_the_tables_line_by_line() {
+    let dir = TestDir::new("storage-joins");
+    let mut connection = dir.open();
+    connection.set_memory_limit(8 * 4096);
+    run(&mut connection, "CREATE TABLE Author (id INT PRIMARY KEY, name STRING);
+        CREATE TABLE Book (id INT PRIMARY KEY, author INT, title STRING);
+        CREATE TABLE Review (id INT PRIMARY KEY, book INT, stars INT);");
+    insert_many(&mut connection, "Author", "id, name", (0..30).map(|i| format!("{i}, '{}'", "a".repeat(200) + &i.to_string())));
+    insert_many(&mut connection, "Book", "id, author, title", (0..60).map(|i| format!("{i}, {}, 'b{i}'", i % 20)));
+    insert_many(&mut connection, "Review", "id, book, stars", (0..30).map(|i| format!("{i}, {}, {}", i * 2, i % 5)));
+    let result = rows(&mut connection, "SELECT Author.id, Book.title, Review.stars FROM Author LEFT JOIN Book ON Book.author == Author.id LEFT JOIN Review ON Review.book == Book.id;");
+    let mut expected = Vec::new();
+    for author in 0..30 {
+        let books = (0..60).filter(|book| book % 20 == author).collect::<Vec<_>>();
+        if books.is_empty() {
+            expected.push(vec!(author.to_string(), String::from("NULL"), String::from("NULL")));
+        }
+        for book in books {
+            let stars = if book % 2 == 0 { ((book / 2) % 5).to_string() } else { String::from("NULL") };
+            expected.push(vec!(author.to_string(), format!("b{book}"), stars));
+        }
+    }
+    assert_eq!(result, expected);
+    assert_eq!(rows(&mut connection, "SELECT Book.title FROM Book JOIN Review ON Review.book == Book.id WHERE Review.stars == 4 LIMIT 2 OFFSET 1;"), table(&[&["b18"], &["b28"]]));
+}
+
+fn file_size(dir: &TestDir, table_name: &str) -> u64 {
+    std::fs::metadata(dir.path().join(table_name)).unwrap().len()
+}
+
+#[test]
+fn lines_keep_their_order_across_a_reload() {
+    let dir = TestDir::new("storage-order");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Log (id INT PRIMARY KEY, message STRING);");
+    insert_many(&mut connection, "Log", "id, message", (0..200).map(|i| format!("{i}, '{}'", "m".repeat(i % 50 + 1))));
+    run(&mut connection, "DELETE FROM Log WHERE id < 100 && id != 7;");
+    insert_many(&mut connection, "Log", "id, message", (1000..1100).map(|i| format!("{i}, 'new'")));
+    run(&mut connection, &format!("SET message = '{}' FROM Log WHERE id == 7;", "x".repeat(6000)));
+    let before = rows(&mut connection, "SELECT id FROM Log;");
+    let expected = [7].into_iter().chain(100..200).chain(1000..1100).map(|i| vec!(i.to_string())).collect::<Vec<_>>();
+    assert_eq!(before, expected);
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id FROM Log;"), expected);
+    assert_eq!(rows(&mut connection, "SELECT message FROM Log WHERE id == 7;"), vec!(vec!("x".repeat(6000))));
+}
+
+#[test]
+fn tables_bigger_than_the_memory_limit_are_browsed() {
+    let dir = TestDir::new("storage-memory");
+    let mut connection = dir.open();
+    connection.set_memory_limit(2 * 4096);
+    run(&mut connection, "CREATE TABLE Big (id INT PRIMARY KEY, payload STRING, n INT);");
+    insert_many(&mut connection, "Big", "id, payload, n", (0..500).map(|i| format!("{i}, '{}', {}", "p".repeat(300), i % 7)));
+    assert!(file_size(&dir, "Big") > 30 * 4096);
+    assert_eq!(rows(&mut connection, "SELECT COUNT(id), SUM(n) FROM Big;"), table(&[&["500", &(0..500).map(|i| i % 7).sum::<i32>().to_string()]]));
+    run(&mut connection, "SET n = n + 1 FROM Big WHERE id >= 250;");
+    drop(connection);
+    let mut connection = dir.open();
+    connection.set_memory_limit(4096);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Big WHERE n == 7;").len(), (250..500).filter(|i| i % 7 == 6).count());
+    assert_eq!(rows(&mut connection, "SELECT id FROM Big LIMIT 2 OFFSET 498;"), table(&[&["498"], &["499"]]));
+}
+
+#[test]
+fn vacuum_shrinks_the_data_file() {
+    let dir = TestDir::new("storage-vacuum");
+    let mut connection = dir.open();
+    connection.set_memory_limit(4 * 4096);
+    run(&mut connection, "CREATE TABLE Item (id INT PRIMARY KEY, label STRING);");
+    insert_many(&mut connection, "Item", "id, label", (0..400).map(|i| format!("{i}, '{}'", "l".repeat(100))));
+    run(&mut connection, "DELETE FROM Item WHERE id % 5 != 0;");
+    let before = file_size(&dir, "Item");
+    let expected = rows(&mut connection, "SELECT id, label FROM Item;");
+    run(&mut connection, "BEGIN;");
+    assert!(matches!(error(&mut connection, "VACUUM Item;"), IrisError::InvalidRequest(_)));
+    run(&mut connection, "ROLLBACK; VACUUM Item;");
+    let after = file_size(&dir, "Item");
+    assert!(after * 3 < before, "{after} bytes after the vacuum, {before} before");
+    assert_eq!(rows(&mut connection, "SELECT id, label FROM Item;"), expected);
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT id, label FROM Item;"), expected);
+    run(&mut connection, "VACUUM;");
+    assert_eq!(file_size(&dir, "Item"), after);
+}
@@ -0,0 +1,49 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{IrisError};
+
+const WORDS: &str = "CREATE TABLE Word (id INT PRIMARY KEY, w STRING);
+    INSERT INTO Word (id, w) VALUES (1, 'hello world');
+    INSERT INTO Word (id, w) VALUES (2, 'héllo');
+    INSERT INTO Word (id, w) VALUES (3, 'Hello');
+    INSERT INTO Word (id, w) VALUES (4, 'hello');
+    INSERT INTO Word (id, w) VALUES (5, 'a, b; c');";
+
+#[test]
+fn strings_are_equal_on_their_exact_content() {
+    let dir = TestDir::new("strings-equal");
+    let mut connection = database(&dir, WORDS);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Word WHERE w == 'hello';"), table(&[&["4"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Word WHERE w == 'a, b; c';"), table(&[&["5"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Word WHERE w != 'hello' && w != 'Hello';"), table(&[&["1"], &["2"], &["5"]]));
+    assert!(rows(&mut connection, "SELECT id FROM Word WHERE w == 'hell';").is_empty());
+}
+
+#[test]
+fn strings_are_compared_in_the_lexicographic_order() {
+    let dir = TestDir::new("strings-order");
+    let mut connection = database(&dir, WORDS);
+    assert_eq!(rows(&mut connection, "SELECT id FROM Word WHERE w < 'hello';"), table(&[&["3"], &["5"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Word WHERE w > 'hello' && w <= 'hello world';"), table(&[&["1"]]));
+    assert_eq!(rows(&mut connection, "SELECT id FROM Word WHERE 'hello' < w;"), table(&[&["1"], &["2"]]));
+    assert_eq!(rows(&mut connection, "SELECT w FROM Word ORDER BY w DESC LIMIT 2;"), table(&[&["héllo"], &["hello world"]]));
+}
+
+#[test]
+fn strings_are_kept_as_they_are_written() {
+    let dir = TestDir::new("strings-stored");
+    let mut connection = database(&dir, WORDS);
+    run(&mut connection, "SET w = 'SELECT * FROM Word;' FROM Word WHERE id == 4;");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT w FROM Word WHERE id == 4 || id == 2;"), table(&[&["héllo"], &["SELECT * FROM Word;"]]));
+}
+
+#[test]
+fn strings_are_not_compared_with_numbers() {
+    let dir = TestDir::new("strings-numbers");
+    let mut connection = database(&dir, WORDS);
+    assert!(matches!(error(&mut connection, "SELECT id FROM Word WHERE w == 1;"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "SELECT id FROM Word WHERE w < id;"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "INSERT INTO Word (id, w) VALUES (6, 7);"), IrisError::TypeMismatch(_)));
+}
@@ -0,0 +1,99 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::{IrisError, Type};
+
+const PETS: &str = "CREATE TABLE Pet (id INT PRIMARY KEY, name STRING, born DATE, fed TIME, seen TIMESTAMP);
+    INSERT INTO Pet (id, name, born, fed, seen) VALUES (1, 'Rex', '2019-03-04', '08:30:00', '2024-01-02 10:20:30');
+    INSERT INTO Pet (id, name, born, fed, seen) VALUES (2, 'Tom', '2021-12-31', '19:05:00', '2023-12-31 23:59:59');
+    INSERT INTO Pet (id, name, born) VALUES (3, 'Kit', '2020-02-29');";
+
+#[test]
+fn temporal_literals_are_checked_and_kept() {
+    let dir = TestDir::new("temporal-literals");
+    let mut connection = database(&dir, PETS);
+    for value in ["'2021-13-01'", "'2021-02-29'", "'24:00:00'", "'2021-01-01'", "12"] {
+        let sql = format!("INSERT INTO Pet (id, fed) VALUES (4, {value});");
+        assert!(matches!(error(&mut connection, &sql), IrisError::TypeMismatch(_)), "{sql} was accepted");
+    }
+    drop(connection);
+    let mut connection = dir.open();
+    let result = connection.execute("SELECT born, fed, seen FROM Pet WHERE id == 1;").unwrap();
+    assert_eq!(result.types(), &vec![Type::Date, Type::Time, Type::Timestamp]);
+    assert_eq!(rows(&mut connection, "SELECT born, fed, seen FROM Pet WHERE id == 1;"), table(&[&["2019-03-04", "08:30:00", "2024-01-02 10:20:30"]]));
+}
+
+#[test]
+fn temporal_values_are_compared_and_sorted_by_time() {
+    let dir = TestDir::new("temporal-order");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet ORDER BY born;"), table(&[&["Rex"], &["Kit"], &["Tom"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet ORDER BY fed DESC;"), table(&[&["Tom"], &["Rex"], &["Kit"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE born < '2020-03-01' && born > '2019-12-31';"), table(&[&["Kit"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE seen >= '2024-01-01 00:00:00';"), table(&[&["Rex"]]));
+    // A date is the midnight of its day when it is compared with a timestamp
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE seen < '2024-01-01';"), table(&[&["Tom"]]));
+    assert!(matches!(error(&mut connection, "SELECT name FROM Pet WHERE fed < born;"), IrisError::TypeMismatch(_)));
+}
+
+#[test]
+fn date_functions_in_where() {
+    let dir = TestDir::new("temporal-where");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE EXTRACT(YEAR FROM born) == 2020;"), table(&[&["Kit"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE DATE_ADD(born, 1) == '2020-03-01';"), table(&[&["Kit"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE seen < NOW();"), table(&[&["Rex"], &["Tom"]]));
+    assert_eq!(rows(&mut connection, "SELECT name FROM Pet WHERE EXTRACT(HOUR FROM fed) > 12;"), table(&[&["Tom"]]));
+}
+
+#[test]
+fn date_functions_in_the_selected_columns() {
+    let dir = TestDir::new("temporal-select");
+    let mut connection = database(&dir, PETS);
+    let result = connection.execute("SELECT name, EXTRACT(YEAR FROM born), DATE_ADD(born, -4), DATE_ADD(seen, 1) FROM Pet WHERE id < 3;").unwrap();
+    assert_eq!(result.columns(), &vec!["name", "EXTRACT(YEAR FROM born)", "DATE_ADD(born, -4)", "DATE_ADD(seen, 1)"]);
+    assert_eq!(result.types(), &vec![Type::String, Type::Int, Type::Date, Type::Timestamp]);
+    assert_eq!(rows(&mut connection, "SELECT name, EXTRACT(YEAR FROM born), DATE_ADD(born, -4), DATE_ADD(seen, 1) FROM Pet WHERE id < 3;"),
+               table(&[&["Rex", "2019", "2019-02-28", "2024-01-03 10:20:30"], &["Tom", "2021", "2021-12-27", "2024-01-01 23:59:59"]]));
+    assert_eq!(rows(&mut connection, "SELECT DATE_ADD('2020-02-28', 1 + 1), EXTRACT(SECOND FROM seen) FROM Pet WHERE id == 2;"), table(&[&["2020-03-01", "59"]]));
+    assert_eq!(rows(&mut connection, "SELECT EXTRACT(MINUTE FROM seen) FROM Pet WHERE id == 3;"), table(&[&["NULL"]]));
+    let now = connection.execute("SELECT NOW() FROM Pet WHERE id == 1;").unwrap();
+    assert_eq!(now.types(), &vec![Type::Timestamp]);
+    assert_eq!(now.rows()[0][0].string().len(), "2024-01-01 00:00:00".len());
+}
+
+#[test]
+fn date_functions_with_groups_and_joins() {
+    let dir = TestDir::new("temporal-groups");
+    let mut connection = database(&dir, PETS);
+    assert_eq!(rows(&mut connection, "SELECT EXTRACT(MONTH FROM born), COUNT(*) FROM Pet GROUP BY born ORDER BY born;"),
+               table(&[&["3", "1"], &["2", "1"], &["12", "1"]]));
+    assert!(matches!(error(&mut connection, "SELECT EXTRACT(YEAR FROM born), COUNT(*) FROM Pet;"), IrisError::InvalidRequest(_)));
+    assert_eq!(rows(&mut connection, "SELECT p.name, EXTRACT(DAY FROM q.born) FROM Pet AS p JOIN Pet AS q ON p.id == q.id WHERE p.id == 3;"), table(&[&["Kit", "29"]]));
+}
+
+#[test]
+fn date_functions_refuse_other_types() {
+    let dir = TestDir::new("temporal-functions-types");
+    let mut connection = database(&dir, PETS);
+    assert!(matches!(error(&mut connection, "SELECT EXTRACT(YEAR FROM id) FROM Pet;"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "SELECT EXTRACT(YEAR FROM name) FROM Pet;"), IrisError::Evaluation(_)));
+    assert!(matches!(error(&mut connection, "SELECT DATE_ADD(fed, 1) FROM Pet;"), IrisError::TypeMismatch(_)));
+    assert!(matches!(error(&mut connection, "SELECT EXTRACT(YEAR FROM size) FROM Pet;"), IrisError::UnknownColumn { .. }));
+    assert!(matches!(error(&mut connection, "SELECT name FROM Pet WHERE DATE_ADD(born, 9999999) > born;"), IrisError::Evaluation(_)));
+}
+
+#[test]
+fn date_functions_read_the_dates_of_strings() {
+    let dir = TestDir::new("temporal-functions-strings");
+    let mut connection = dir.open();
+    run(&mut connection, "CREATE TABLE Event (id INT PRIMARY KEY, day STRING);
+        INSERT INTO Event (id, day) VALUES (1, '2024-01-31');
+        INSERT INTO Event (id, day) VALUES (2, '2023-06-01 12:00:00');
+        INSERT INTO Event (id, day) VALUES (3, 'soon');");
+    assert_eq!(rows(&mut connection, "SELECT id FROM Event WHERE EXTRACT(YEAR FROM day) == 2024 && id == 1;"), table(&[&["1"]]));
+    assert_eq!(rows(&mut connection, "SELECT DATE_ADD(day, 1) FROM Event WHERE id < 3;"), table(&[&["2024-02-01"], &["2023-06-02 12:00:00"]]));
+    match error(&mut connection, "SELECT id FROM Event WHERE EXTRACT(YEAR FROM day) == 2024;") {
+        IrisError::Evaluation(msg) => assert!(msg.contains("'soon'"), "{msg}"),
+        e => panic!("unexpected error: {e}")
+    }
+}
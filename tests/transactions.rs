@@ -0,0 +1,108 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::IrisError;
+
+const ACCOUNTS: &str = "CREATE TABLE Account (id INT PRIMARY KEY, balance INT NOT NULL);
+    CREATE TABLE Transfer (id INT PRIMARY KEY, amount INT);
+    INSERT INTO Account (id, balance) VALUES (1, 100);";
+
+#[test]
+fn commit_writes_every_table() {
+    let dir = TestDir::new("transaction-commit");
+    let mut connection = database(&dir, ACCOUNTS);
+    run(&mut connection, "BEGIN;
+        INSERT INTO Transfer (id, amount) VALUES (1, 30);
+        SET balance = balance - 30 FROM Account WHERE id == 1;
+        COMMIT;");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT balance FROM Account;"), table(&[&["70"]]));
+    assert_eq!(rows(&mut connection, "SELECT amount FROM Transfer;"), table(&[&["30"]]));
+}
+
+#[test]
+fn rollback_forgets_every_table() {
+    let dir = TestDir::new("transaction-rollback");
+    let mut connection = database(&dir, ACCOUNTS);
+    run(&mut connection, "BEGIN;
+        INSERT INTO Transfer (id, amount) VALUES (1, 30);
+        SET balance = balance - 30 FROM Account WHERE id == 1;");
+    assert_eq!(rows(&mut connection, "SELECT balance FROM Account;"), table(&[&["70"]]));
+    run(&mut connection, "ROLLBACK;");
+    assert_eq!(rows(&mut connection, "SELECT balance FROM Account;"), table(&[&["100"]]));
+    assert!(rows(&mut connection, "SELECT amount FROM Transfer;").is_empty());
+}
+
+#[test]
+fn uncommitted_changes_are_lost_on_reopen() {
+    let dir = TestDir::new("transaction-reopen");
+    let mut connection = database(&dir, ACCOUNTS);
+    run(&mut connection, "BEGIN; INSERT INTO Transfer (id, amount) VALUES (1, 30);");
+    drop(connection);
+    let mut connection = dir.open();
+    assert!(rows(&mut connection, "SELECT amount FROM Transfer;").is_empty());
+    assert!(matches!(error(&mut connection, "COMMIT;"), IrisError::InvalidRequest(_)));
+}
+
+#[test]
+fn failed_request_aborts_the_transaction_until_commit() {
+    let dir = TestDir::new("transaction-abort-commit");
+    let mut connection = database(&dir, ACCOUNTS);
+    run(&mut connection, "BEGIN; INSERT INTO Transfer (id, amount) VALUES (1, 30);");
+    assert!(matches!(error(&mut connection, "INSERT INTO Account (id, balance) VALUES (2, NULL);"), IrisError::ConstraintViolation(_)));
+    match error(&mut connection, "SELECT amount FROM Transfer;") {
+        IrisError::InvalidRequest(msg) => assert!(msg.contains("aborted"), "{msg}"),
+        e => panic!("unexpected error: {e}")
+    }
+    match error(&mut connection, "COMMIT;") {
+        IrisError::InvalidRequest(msg) => assert!(msg.contains("nothing was committed"), "{msg}"),
+        e => panic!("unexpected error: {e}")
+    }
+    assert!(rows(&mut connection, "SELECT amount FROM Transfer;").is_empty());
+    assert_eq!(rows(&mut connection, "SELECT id FROM Account;"), table(&[&["1"]]));
+    run(&mut connection, "INSERT INTO Transfer (id, amount) VALUES (2, 10);");
+    assert_eq!(rows(&mut connection, "SELECT amount FROM Transfer;"), table(&[&["10"]]));
+}
+
+#[test]
+fn failed_request_aborts_the_transaction_until_rollback() {
+    let dir = TestDir::new("transaction-abort-rollback");
+    let mut connection = database(&dir, ACCOUNTS);
+    run(&mut connection, "BEGIN; SET balance = 0 FROM Account WHERE id == 1;");
+    assert!(matches!(error(&mut connection, "INSERT INTO Account (id, balance) VALUES (1, 5);"), IrisError::ConstraintViolation(_)));
+    assert!(matches!(error(&mut connection, "INSERT INTO Transfer (id, amount) VALUES (1, 30);"), IrisError::InvalidRequest(_)));
+    run(&mut connection, "ROLLBACK;");
+    assert_eq!(rows(&mut connection, "SELECT balance FROM Account;"), table(&[&["100"]]));
+    run(&mut connection, "BEGIN; INSERT INTO Transfer (id, amount) VALUES (1, 30); COMMIT;");
+    assert_eq!(rows(&mut connection, "SELECT amount FROM Transfer;"), table(&[&["30"]]));
+}
+
+#[test]
+fn failed_request_outside_a_transaction_keeps_the_previous_ones() {
+    let dir = TestDir::new("transaction-autocommit");
+    let mut connection = database(&dir, ACCOUNTS);
+    assert!(matches!(error(&mut connection, "INSERT INTO Transfer (id, amount) VALUES (1, 30); INSERT INTO Transfer (id, amount) VALUES (1, 40);"), IrisError::ConstraintViolation(_)));
+    assert_eq!(rows(&mut connection, "SELECT amount FROM Transfer;"), table(&[&["30"]]));
+    assert!(matches!(error(&mut connection, "ROLLBACK;"), IrisError::InvalidRequest(_)));
+}
+
+#[test]
+fn rollback_restores_the_created_and_dropped_tables() {
+    let dir = TestDir::new("transaction-tables");
+    let mut connection = database(&dir, ACCOUNTS);
+    run(&mut connection, "INSERT INTO Transfer (id, amount) VALUES (1, 30);");
+    run(&mut connection, "BEGIN;
+        CREATE TABLE Log (id INT PRIMARY KEY);
+        INSERT INTO Log (id) VALUES (1);
+        DROP TABLE Transfer;
+        CREATE INDEX by_balance ON Account (balance);
+        SET balance = 0 FROM Account WHERE id == 1;
+        ROLLBACK;");
+    assert!(matches!(error(&mut connection, "SELECT id FROM Log;"), IrisError::UnknownTable(_)));
+    assert_eq!(rows(&mut connection, "SELECT amount FROM Transfer;"), table(&[&["30"]]));
+    assert_eq!(rows(&mut connection, "SELECT balance FROM Account;"), table(&[&["100"]]));
+    assert!(matches!(error(&mut connection, "DROP INDEX by_balance;"), IrisError::InvalidRequest(_)));
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT amount FROM Transfer;"), table(&[&["30"]]));
+}
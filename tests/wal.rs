@@ -0,0 +1,48 @@
+mod common;
+use common::{TestDir, database, run, rows, error, table};
+use iris::IrisError;
+use serde_json::json;
+
+/// Write a log whose records replace the tables data by an empty list, followed or not by their commit record
+fn write_log(dir: &TestDir, committed: bool) {
+    let mut log = json!({"write": "tables.json", "content": "[]"}).to_string() + "\n";
+    if committed {
+        log += &(json!({"commit": true}).to_string() + "\n");
+    }
+    std::fs::write(dir.path().join("iris.wal"), log).unwrap();
+}
+
+const ITEMS: &str = "CREATE TABLE Item (id INT PRIMARY KEY, name STRING); INSERT INTO Item (id, name) VALUES (1, 'a');";
+
+#[test]
+fn committed_log_is_replayed_when_the_database_is_opened() {
+    let dir = TestDir::new("wal-replay");
+    drop(database(&dir, ITEMS));
+    write_log(&dir, true);
+    let mut connection = dir.open();
+    assert!(matches!(error(&mut connection, "SELECT id FROM Item;"), IrisError::UnknownTable(_)));
+    assert_eq!(std::fs::metadata(dir.path().join("iris.wal")).unwrap().len(), 0);
+}
+
+#[test]
+fn uncommitted_log_is_dropped_when_the_database_is_opened() {
+    let dir = TestDir::new("wal-uncommitted");
+    drop(database(&dir, ITEMS));
+    write_log(&dir, false);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT name FROM Item;"), table(&[&["a"]]));
+    assert_eq!(std::fs::metadata(dir.path().join("iris.wal")).unwrap().len(), 0);
+}
+
+#[test]
+fn committed_requests_are_kept_in_the_log_until_the_checkpoint() {
+    let dir = TestDir::new("wal-log");
+    drop(database(&dir, ITEMS));
+    let mut connection = dir.open();
+    run(&mut connection, "INSERT INTO Item (id, name) VALUES (2, 'b');");
+    let log = std::fs::read_to_string(dir.path().join("iris.wal")).unwrap();
+    assert!(log.lines().last().is_some_and(|line| line.contains("commit")), "{log}");
+    drop(connection);
+    let mut connection = dir.open();
+    assert_eq!(rows(&mut connection, "SELECT name FROM Item ORDER BY id;"), table(&[&["a"], &["b"]]));
+}